@@ -0,0 +1,547 @@
+//! Assignment 1 Part 1: Doubly Linked List in Unsafe Rust
+//!
+//! Lab 1 lessons you must apply:
+//! A) Lifetime: nodes must not outlive the list (no dangling pointers, correct Drop).
+//! B) Bounds: index-based operations must reject invalid indices.
+//! C) Aliasing: list mutations must assume exclusive access; do not overwrite what you still rely on.
+//! D) Type integrity: every `NonNull<Node<T>>` must point to a real Node allocated as Node.
+//!
+//! Constraints:
+//! - You may use `unsafe` and raw pointers.
+//! - Do NOT expose raw node pointers in the public API.
+//! - Enforce invariants with checks and `Result` errors.
+//!
+//! Required invariants (must hold after every operation):
+//! 1) If len == 0: head == None && tail == None
+//! 2) If len > 0: head != None && tail != None
+//! 3) head.prev == None, tail.next == None
+//! 4) For any node n: if n.next != None then n.next.prev == n; if n.prev != None then n.prev.next == n
+//! 5) len matches the number of reachable nodes from head
+//!
+//! Node memory comes from a `NodeAlloc` (see `assignment_1::node_alloc`):
+//! the global allocator by default, or any other via `new_in`, e.g. a `Bump`
+//! arena, a fixed-capacity `Slab`, or a `Failing` allocator in tests.
+//!
+//! Ownership: links are `Option<NonNull<Node<T>>>`, and the list carries a
+//! `PhantomData<Box<Node<T>>>` to say it owns its nodes. That makes it
+//! covariant in `T` (a list of `&'static str` is a list of `&'a str`), tells
+//! drop check that dropping the list drops `T`s, and leaves `Send`/`Sync` to
+//! the explicit impls below, which hold exactly when they hold for `T` and
+//! the allocator. `tests/ui` has the programs these rules reject.
+
+use std::alloc::{handle_alloc_error, Layout};
+use std::marker::PhantomData;
+use std::ptr::{self, NonNull};
+
+use assignment_1::node_alloc::{Global, NodeAlloc};
+
+/// A link to a neighbouring node; `None` past either end of the list.
+type Link<T> = Option<NonNull<Node<T>>>;
+
+#[repr(C)]
+struct Node<T> {
+    value: T,
+    prev: Link<T>,
+    next: Link<T>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListError {
+    Empty,
+    IndexOutOfBounds { index: usize, len: usize },
+    InvariantViolation,
+    /// The allocator could not provide a node of this layout.
+    AllocFailed(Layout),
+}
+
+pub struct DoublyLinkedList<T, A: NodeAlloc = Global> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    alloc: A,
+    /// The list owns its nodes, and through them values of type `T`.
+    marker: PhantomData<Box<Node<T>>>,
+}
+
+// SAFETY: every node is owned by exactly one list and no node pointer leaves
+// it, so moving the list to another thread moves its values (`T: Send`) and
+// its allocator (`A: Send`) and nothing else.
+unsafe impl<T: Send, A: NodeAlloc + Send> Send for DoublyLinkedList<T, A> {}
+
+// SAFETY: through `&DoublyLinkedList` one can only read: `&T` via the values
+// and `&A` via `allocator()`. Every mutation takes `&mut self`.
+unsafe impl<T: Sync, A: NodeAlloc + Sync> Sync for DoublyLinkedList<T, A> {}
+
+impl<T> DoublyLinkedList<T> {
+    pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<T> Default for DoublyLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, A: NodeAlloc> DoublyLinkedList<T, A> {
+    /// An empty list whose nodes come from `alloc`.
+    pub fn new_in(alloc: A) -> Self {
+        Self { head: None, tail: None, len: 0, alloc, marker: PhantomData }
+    }
+
+    /// The allocator the nodes come from.
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // ---------- Allocation helpers (Lesson D: type integrity) ----------
+
+    /// Allocate a Node from the list's allocator with Layout::new::<Node<T>>().
+    /// Initialize prev/next to None.
+    /// On failure `value` is dropped and nothing else changes.
+    unsafe fn alloc_node(&self, value: T) -> Result<NonNull<Node<T>>, ListError> {
+        let layout = Layout::new::<Node<T>>();
+        let n = match self.alloc.allocate(layout) {
+            Ok(ptr) => ptr.cast::<Node<T>>(),
+            Err(_) => return Err(ListError::AllocFailed(layout)),
+        };
+        unsafe { n.write(Node { value, prev: None, next: None }) };
+        Ok(n)
+    }
+
+    /// Deallocate a Node allocated by alloc_node. The value must already have
+    /// been moved out or dropped.
+    /// IMPORTANT: must be called exactly once per node (Lesson A: lifetime).
+    unsafe fn dealloc_node(&self, node: NonNull<Node<T>>) {
+        unsafe { self.alloc.deallocate(node.cast(), Layout::new::<Node<T>>()) };
+    }
+
+    // ---------- Internal traversal (Lesson B: bounds) ----------
+
+    /// Return pointer to node at `index` (0-based).
+    /// Must return Err if index >= len.
+    /// Must not follow a missing link.
+    ///
+    /// Walks from whichever end is closer.
+    unsafe fn node_at(&self, index: usize) -> Result<NonNull<Node<T>>, ListError> {
+        if index >= self.len {
+            return Err(ListError::IndexOutOfBounds { index, len: self.len });
+        }
+
+        unsafe {
+            let mut cur;
+            if index < self.len / 2 {
+                cur = self.head;
+                for _ in 0..index {
+                    cur = (*cur.ok_or(ListError::InvariantViolation)?.as_ptr()).next;
+                }
+            } else {
+                cur = self.tail;
+                for _ in 0..(self.len - 1 - index) {
+                    cur = (*cur.ok_or(ListError::InvariantViolation)?.as_ptr()).prev;
+                }
+            }
+            cur.ok_or(ListError::InvariantViolation)
+        }
+    }
+
+    // ---------- Core operations ----------
+
+    pub fn push_front(&mut self, value: T) {
+        if let Err(ListError::AllocFailed(layout)) = self.try_push_front(value) {
+            handle_alloc_error(layout);
+        }
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        if let Err(ListError::AllocFailed(layout)) = self.try_push_back(value) {
+            handle_alloc_error(layout);
+        }
+    }
+
+    /// Like push_front, but a failed allocation returns AllocFailed (dropping
+    /// `value`) and leaves the list unchanged.
+    pub fn try_push_front(&mut self, value: T) -> Result<(), ListError> {
+        unsafe {
+            let n = self.alloc_node(value)?;
+
+            match self.head {
+                None => self.tail = Some(n),
+                Some(head) => {
+                    (*n.as_ptr()).next = Some(head);
+                    (*head.as_ptr()).prev = Some(n);
+                }
+            }
+            self.head = Some(n);
+
+            self.len += 1;
+        }
+        // Optional: in debug builds, you can validate invariants
+        debug_assert!(self.check_invariants().is_ok());
+        Ok(())
+    }
+
+    /// Like push_back, but a failed allocation returns AllocFailed (dropping
+    /// `value`) and leaves the list unchanged.
+    pub fn try_push_back(&mut self, value: T) -> Result<(), ListError> {
+        unsafe {
+            let n = self.alloc_node(value)?;
+
+            match self.tail {
+                None => self.head = Some(n),
+                Some(tail) => {
+                    (*n.as_ptr()).prev = Some(tail);
+                    (*tail.as_ptr()).next = Some(n);
+                }
+            }
+            self.tail = Some(n);
+
+            self.len += 1;
+        }
+        debug_assert!(self.check_invariants().is_ok());
+        Ok(())
+    }
+
+    pub fn pop_front(&mut self) -> Result<T, ListError> {
+        let Some(n) = self.head else {
+            return Err(ListError::Empty);
+        };
+
+        unsafe {
+            let next = (*n.as_ptr()).next;
+            match next {
+                // removing last element
+                None => self.tail = None,
+                Some(next) => (*next.as_ptr()).prev = None,
+            }
+            self.head = next;
+
+            self.len -= 1;
+
+            // Move value out, then free node.
+            // NOTE: this is unsafe because we're moving out of raw pointer memory.
+            let value = ptr::read(&(*n.as_ptr()).value);
+            self.dealloc_node(n);
+
+            debug_assert!(self.check_invariants().is_ok());
+            Ok(value)
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Result<T, ListError> {
+        let Some(n) = self.tail else {
+            return Err(ListError::Empty);
+        };
+
+        unsafe {
+            let prev = (*n.as_ptr()).prev;
+            match prev {
+                None => self.head = None,
+                Some(prev) => (*prev.as_ptr()).next = None,
+            }
+            self.tail = prev;
+
+            self.len -= 1;
+
+            let value = ptr::read(&(*n.as_ptr()).value);
+            self.dealloc_node(n);
+
+            debug_assert!(self.check_invariants().is_ok());
+            Ok(value)
+        }
+    }
+
+    /// Insert at position index:
+    /// - index == 0 => push_front
+    /// - index == len => push_back
+    /// - otherwise splice in the middle
+    ///
+    /// Lesson B: must bounds-check (index <= len)
+    /// Lesson C: rewire pointers carefully; do not overwrite pointers you still need
+    pub fn insert_at(&mut self, index: usize, value: T) -> Result<(), ListError> {
+        match self.try_insert_at(index, value) {
+            Err(ListError::AllocFailed(layout)) => handle_alloc_error(layout),
+            result => result,
+        }
+    }
+
+    /// Like insert_at, but a failed allocation returns AllocFailed (dropping
+    /// `value`) and leaves the list unchanged.
+    pub fn try_insert_at(&mut self, index: usize, value: T) -> Result<(), ListError> {
+        if index > self.len {
+            return Err(ListError::IndexOutOfBounds { index, len: self.len });
+        }
+        if index == 0 {
+            return self.try_push_front(value);
+        }
+        if index == self.len {
+            return self.try_push_back(value);
+        }
+
+        unsafe {
+            // 0 < index < len: the new node goes between `prev` and `next`.
+            // Nothing is rewired until the node exists.
+            let next = self.node_at(index)?;
+            let prev = (*next.as_ptr()).prev.ok_or(ListError::InvariantViolation)?;
+            let n = self.alloc_node(value)?;
+
+            (*n.as_ptr()).prev = Some(prev);
+            (*n.as_ptr()).next = Some(next);
+            (*prev.as_ptr()).next = Some(n);
+            (*next.as_ptr()).prev = Some(n);
+        }
+
+        self.len += 1;
+        debug_assert!(self.check_invariants().is_ok());
+        Ok(())
+    }
+
+    /// Remove at position index:
+    /// - index must be < len
+    /// - return removed value
+    pub fn remove_at(&mut self, index: usize) -> Result<T, ListError> {
+        unsafe {
+            let n = self.node_at(index)?;
+            let prev = (*n.as_ptr()).prev;
+            let next = (*n.as_ptr()).next;
+
+            match prev {
+                None => self.head = next,
+                Some(prev) => (*prev.as_ptr()).next = next,
+            }
+            match next {
+                None => self.tail = prev,
+                Some(next) => (*next.as_ptr()).prev = prev,
+            }
+
+            self.len -= 1;
+
+            let value = ptr::read(&(*n.as_ptr()).value);
+            self.dealloc_node(n);
+
+            debug_assert!(self.check_invariants().is_ok());
+            Ok(value)
+        }
+    }
+
+    // ---------- Invariant checking (ties everything together) ----------
+
+    /// Checks invariants listed at the top.
+    /// Students should use this while debugging.
+    pub fn check_invariants(&self) -> Result<(), ListError> {
+        unsafe {
+            let (head, tail) = match (self.head, self.tail) {
+                (None, None) if self.len == 0 => return Ok(()),
+                (Some(head), Some(tail)) if self.len > 0 => (head, tail),
+                _ => return Err(ListError::InvariantViolation),
+            };
+
+            if (*head.as_ptr()).prev.is_some() {
+                return Err(ListError::InvariantViolation);
+            }
+            if (*tail.as_ptr()).next.is_some() {
+                return Err(ListError::InvariantViolation);
+            }
+
+            // Walk from head, verify links + count
+            let mut count = 0usize;
+            let mut cur = self.head;
+            let mut prev: Link<T> = None;
+
+            while let Some(n) = cur {
+                if (*n.as_ptr()).prev != prev {
+                    return Err(ListError::InvariantViolation);
+                }
+                if let Some(p) = prev
+                    && (*p.as_ptr()).next != cur
+                {
+                    return Err(ListError::InvariantViolation);
+                }
+                prev = cur;
+                cur = (*n.as_ptr()).next;
+                count += 1;
+
+                // Guard against cycles causing infinite loop
+                if count > self.len + 1 {
+                    return Err(ListError::InvariantViolation);
+                }
+            }
+
+            if prev != self.tail {
+                return Err(ListError::InvariantViolation);
+            }
+            if count != self.len {
+                return Err(ListError::InvariantViolation);
+            }
+            Ok(())
+        }
+    }
+}
+
+impl<T, A: NodeAlloc> Drop for DoublyLinkedList<T, A> {
+    /// Lesson A: Lifetime safety.
+    /// Must free each node exactly once, and leave no dangling pointers.
+    fn drop(&mut self) {
+        // No public methods here: they may rely on invariants that no longer
+        // hold once teardown has started.
+        let mut cur = self.head.take();
+        while let Some(n) = cur {
+            unsafe {
+                cur = (*n.as_ptr()).next;
+                ptr::drop_in_place(&mut (*n.as_ptr()).value);
+                self.dealloc_node(n);
+            }
+        }
+
+        self.tail = None;
+        self.len = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assignment_1::node_alloc::{Bump, Failing, Slab};
+    use std::rc::Rc;
+    use std::thread;
+
+    fn values<T: Copy, A: NodeAlloc>(list: &DoublyLinkedList<T, A>) -> Vec<T> {
+        (0..list.len()).map(|i| unsafe { (*list.node_at(i).unwrap().as_ptr()).value }).collect()
+    }
+
+    #[test]
+    fn insert_at_and_remove_at_cover_ends_and_middle() {
+        let mut list = DoublyLinkedList::new();
+        list.insert_at(0, 2).unwrap(); // [2]
+        list.insert_at(0, 1).unwrap(); // [1,2]
+        list.insert_at(2, 4).unwrap(); // [1,2,4]
+        list.insert_at(2, 3).unwrap(); // [1,2,3,4]
+        assert_eq!(list.insert_at(5, 9), Err(ListError::IndexOutOfBounds { index: 5, len: 4 }));
+        assert_eq!(values(&list), vec![1, 2, 3, 4]);
+
+        assert_eq!(list.remove_at(2), Ok(3)); // middle
+        assert_eq!(list.remove_at(2), Ok(4)); // tail
+        assert_eq!(list.remove_at(0), Ok(1)); // head
+        assert_eq!(list.remove_at(1), Err(ListError::IndexOutOfBounds { index: 1, len: 1 }));
+        assert_eq!(list.pop_back(), Ok(2));
+        assert_eq!(list.pop_front(), Err(ListError::Empty));
+        assert!(list.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn drop_frees_every_node_and_value() {
+        let counting = Failing::after(usize::MAX);
+        {
+            let mut list = DoublyLinkedList::new_in(&counting);
+            for i in 0..10 {
+                list.push_back(format!("v{}", i));
+            }
+            list.insert_at(3, "x".to_string()).unwrap();
+            list.remove_at(7).unwrap();
+            list.pop_front().unwrap();
+            assert_eq!(counting.live(), 9);
+        }
+        assert_eq!(counting.live(), 0);
+    }
+
+    #[test]
+    fn arena_and_slab_back_the_list() {
+        let arena = Bump::new();
+        let mut list = DoublyLinkedList::new_in(&arena);
+        for i in 0..100u64 {
+            list.push_front(i);
+        }
+        assert_eq!(list.len(), 100);
+        assert!(arena.chunks() >= 1);
+
+        let mut list = DoublyLinkedList::new_in(Slab::new(2));
+        for i in 0..50 {
+            list.push_back(i);
+            list.push_back(-i);
+            assert_eq!(list.allocator().live(), 2);
+            assert_eq!(list.pop_front(), Ok(i));
+            assert_eq!(list.pop_front(), Ok(-i));
+        }
+        assert!(list.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn failed_allocations_leave_the_list_unchanged() {
+        let failing = Failing::after(3);
+        let token = Rc::new(());
+        let oom = Err(ListError::AllocFailed(Layout::new::<Node<Rc<()>>>()));
+        {
+            let mut list = DoublyLinkedList::new_in(&failing);
+            for _ in 0..3 {
+                list.try_push_back(Rc::clone(&token)).unwrap();
+            }
+
+            assert_eq!(list.try_push_front(Rc::clone(&token)), oom);
+            assert_eq!(list.try_push_back(Rc::clone(&token)), oom);
+            assert_eq!(list.try_insert_at(1, Rc::clone(&token)), oom);
+            assert_eq!(
+                list.try_insert_at(4, Rc::clone(&token)),
+                Err(ListError::IndexOutOfBounds { index: 4, len: 3 })
+            );
+            assert_eq!(failing.failures(), 3);
+
+            // The refused values were dropped, not leaked or half-linked.
+            assert_eq!(Rc::strong_count(&token), 4);
+            assert_eq!(list.len(), 3);
+            assert!(list.check_invariants().is_ok());
+            assert_eq!(failing.live(), 3);
+
+            failing.set_budget(1);
+            list.try_insert_at(1, Rc::clone(&token)).unwrap();
+            assert_eq!(list.len(), 4);
+        }
+        assert_eq!(Rc::strong_count(&token), 1);
+        assert_eq!(failing.live(), 0);
+    }
+
+    #[test]
+    fn list_is_covariant_in_t() {
+        fn shorten<'a>(list: DoublyLinkedList<&'static str>) -> DoublyLinkedList<&'a str> {
+            list
+        }
+
+        let local = String::from("local");
+        let mut list = shorten(DoublyLinkedList::new());
+        list.push_back("static");
+        list.push_back(&local);
+        assert_eq!(list.pop_back(), Ok("local"));
+    }
+
+    #[test]
+    fn list_is_send_and_sync_when_values_are() {
+        fn assert_send_sync<X: Send + Sync>() {}
+        assert_send_sync::<DoublyLinkedList<String>>();
+
+        let mut list = DoublyLinkedList::new();
+        list.push_back("a".to_string());
+        let mut list = thread::spawn(move || {
+            list.push_back("b".to_string());
+            list
+        })
+        .join()
+        .unwrap();
+
+        let shared = &list;
+        let lens = thread::scope(|s| {
+            let a = s.spawn(|| shared.len());
+            let b = s.spawn(|| shared.check_invariants());
+            (a.join().unwrap(), b.join().unwrap())
+        });
+        assert_eq!(lens, (2, Ok(())));
+        assert_eq!(list.pop_front(), Ok("a".to_string()));
+    }
+}
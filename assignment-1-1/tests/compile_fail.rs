@@ -0,0 +1,9 @@
+// Programs the list must reject at compile time. Each tests/ui/*.rs has the
+// expected compiler output next to it in a .stderr file; after an intended
+// change, regenerate those with `TRYBUILD=overwrite cargo test`.
+
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
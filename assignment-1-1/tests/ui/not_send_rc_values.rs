@@ -0,0 +1,12 @@
+// `Rc` is not `Send`, so a list of them cannot move to another thread.
+
+use std::rc::Rc;
+use std::thread;
+
+use assignment_1_1::DoublyLinkedList;
+
+fn main() {
+    let mut list = DoublyLinkedList::new();
+    list.push_back(Rc::new(1));
+    thread::spawn(move || list.len());
+}
@@ -0,0 +1,16 @@
+// A list allocating from `&Bump` is only `Send` if the arena is `Sync`, and
+// it is not: two threads would bump the same pointer.
+
+use std::thread;
+
+use assignment_1::node_alloc::Bump;
+use assignment_1_1::DoublyLinkedList;
+
+fn main() {
+    let arena = Bump::new();
+    let mut list = DoublyLinkedList::new_in(&arena);
+    list.push_back(1);
+    thread::scope(|s| {
+        s.spawn(move || list.len());
+    });
+}
@@ -0,0 +1,15 @@
+// `Cell` is not `Sync`, so a list of them cannot be shared between threads.
+
+use std::cell::Cell;
+use std::thread;
+
+use assignment_1_1::DoublyLinkedList;
+
+fn main() {
+    let mut list = DoublyLinkedList::new();
+    list.push_back(Cell::new(1));
+    let list = &list;
+    thread::scope(|s| {
+        s.spawn(|| list.len());
+    });
+}
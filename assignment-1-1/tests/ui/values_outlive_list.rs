@@ -0,0 +1,10 @@
+// Dropping the list drops its values, so a borrowed value must outlive it.
+// Here `s` is declared after the list and so is dropped first.
+
+use assignment_1_1::DoublyLinkedList;
+
+fn main() {
+    let mut list = DoublyLinkedList::new();
+    let s = String::from("short-lived");
+    list.push_back(&s);
+}
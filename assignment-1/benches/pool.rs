@@ -0,0 +1,71 @@
+// Queue-like workload on `unsafe_list::DoublyLinkedList`: keep `DEPTH`
+// values in the list and push one / pop one per step, so every step frees a
+// node and needs a new one.
+//
+//   no pool:  `new()`, one `alloc` per push and one `dealloc` per pop
+//   pool:     `with_capacity(DEPTH)`, pops park nodes for the next push
+//   arena:    `new_in(&Bump)`, pushes bump a pointer and pops free nothing
+//
+// Run with:
+//
+//   cargo bench --bench pool [-- STEPS]
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use assignment_1::node_alloc::{Bump, NodeAlloc};
+use assignment_1::unsafe_list::DoublyLinkedList;
+
+const DEPTH: usize = 64;
+const ROUNDS: usize = 3;
+
+fn churn<A: NodeAlloc>(mut list: DoublyLinkedList<i64, A>, steps: u64) -> i64 {
+    list.extend(0..DEPTH as i64);
+    let mut sum = 0;
+    for i in 0..steps {
+        list.push_back(black_box(i as i64));
+        sum += list.pop_front().unwrap();
+    }
+    sum
+}
+
+/// Best of `ROUNDS` runs.
+fn measure(run: impl Fn() -> i64) -> Duration {
+    let mut best = Duration::MAX;
+    for _ in 0..ROUNDS {
+        let t0 = Instant::now();
+        black_box(run());
+        best = best.min(t0.elapsed());
+    }
+    best
+}
+
+fn main() {
+    let steps: u64 = std::env::args()
+        .skip(1)
+        .find_map(|a| a.parse().ok())
+        .unwrap_or(5_000_000);
+
+    println!("queue churn: depth={} steps={}", DEPTH, steps);
+    let cases: [(&str, &dyn Fn() -> i64); 3] = [
+        ("no pool", &|| churn(DoublyLinkedList::new(), steps)),
+        ("pool", &|| {
+            churn(DoublyLinkedList::with_capacity(DEPTH), steps)
+        }),
+        ("arena", &|| {
+            let arena = Bump::new();
+            churn(DoublyLinkedList::new_in(&arena), steps)
+        }),
+    ];
+    let base = measure(cases[0].1);
+    for (name, run) in cases {
+        let t = measure(run);
+        println!(
+            "{:<8} {:>8.1} ms  {:>6.1} ns/step  {:.2}x",
+            name,
+            t.as_secs_f64() * 1e3,
+            t.as_nanos() as f64 / steps as f64,
+            base.as_secs_f64() / t.as_secs_f64()
+        );
+    }
+}
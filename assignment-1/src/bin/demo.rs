@@ -37,7 +37,10 @@ fn fmt_value(v: &assignment_1::value::Value) -> String {
     }
 }
 
-fn print_safe_list(label: &str, list: &assignment_1::safe_list::DoublyLinkedList) {
+fn print_safe_list(
+    label: &str,
+    list: &assignment_1::safe_list::DoublyLinkedList<assignment_1::value::Value>,
+) {
     let fwd = list.iter_forward().collect::<Vec<_>>();
     let bwd = list.iter_backward().collect::<Vec<_>>();
 
@@ -54,7 +57,7 @@ fn print_safe_list(label: &str, list: &assignment_1::safe_list::DoublyLinkedList
     println!("  invariants ok? {}", list.check_invariants());
 }
 
-fn print_unsafe_list(label: &str, list: &assignment_1::unsafe_list::DoublyLinkedList) {
+fn print_unsafe_list(label: &str, list: &assignment_1::unsafe_list::DoublyLinkedList<i64>) {
     let fwd = list.iter_forward().collect::<Vec<_>>();
     let bwd = list.iter_backward().collect::<Vec<_>>();
 
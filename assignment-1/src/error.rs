@@ -0,0 +1,62 @@
+// =======================
+// List Errors
+// =======================
+//
+// Shared by `safe_list` and `unsafe_list`. Positional operations report a
+// bad index as an error instead of panicking, and the `try_*` operations of
+// `unsafe_list` report a failed node allocation instead of aborting.
+
+use std::alloc::Layout;
+use std::fmt;
+
+/// Errors returned by list operations that can fail under normal use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListError {
+    /// `index` is not a valid position in a list of length `len`.
+    IndexOutOfBounds { index: usize, len: usize },
+    /// The allocator could not provide a node of this layout.
+    AllocFailed(Layout),
+}
+
+impl fmt::Display for ListError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListError::IndexOutOfBounds { index, len } => {
+                write!(
+                    f,
+                    "index {} out of bounds for list of length {}",
+                    index, len
+                )
+            }
+            ListError::AllocFailed(layout) => write!(
+                f,
+                "failed to allocate a list node ({} bytes, align {})",
+                layout.size(),
+                layout.align()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ListError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_describe_what_went_wrong() {
+        let err = ListError::IndexOutOfBounds { index: 5, len: 3 };
+        assert_eq!(err.to_string(), "index 5 out of bounds for list of length 3");
+
+        let layout = Layout::new::<(u64, *mut u8, *mut u8)>();
+        assert_eq!(
+            ListError::AllocFailed(layout).to_string(),
+            format!(
+                "failed to allocate a list node ({} bytes, align {})",
+                layout.size(),
+                layout.align()
+            )
+        );
+    }
+}
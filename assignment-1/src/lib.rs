@@ -1,3 +1,7 @@
 pub mod safe_list; 
 pub mod value;
 pub mod unsafe_list; 
+pub mod teardown;
+pub mod error;
+pub mod node_alloc;
+pub mod sync_list;
@@ -0,0 +1,485 @@
+// =======================
+// Node Allocators
+// =======================
+//
+// The raw-pointer lists get their node memory from a `NodeAlloc` instead of
+// calling `std::alloc::{alloc, dealloc}` directly, so the caller decides
+// where nodes live:
+//
+//   - `Global`   the global allocator (the default; one `malloc` per node).
+//   - `Bump`     an arena that hands out slices of large chunks and frees
+//                them all at once when it is dropped.
+//   - `Slab`     a fixed number of equal slots, recycled through a free
+//                list; fails once every slot is in use.
+//   - `Failing`  wraps another allocator and fails after a set number of
+//                allocations, to exercise allocation-failure paths in tests.
+//
+// The methods take `&self`, like `std::alloc::Allocator`, and `&A` is an
+// allocator too, so several lists can share one arena or slab by borrowing
+// it: `DoublyLinkedList::new_in(&bump)`. The borrow keeps the arena alive for
+// as long as any list that uses it.
+
+use std::alloc::{self, Layout};
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::ptr::{self, NonNull};
+
+/// An allocation request could not be satisfied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError;
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("memory allocation failed")
+    }
+}
+
+impl std::error::Error for AllocError {}
+
+/// Source of node memory for the raw-pointer lists.
+///
+/// # Safety
+///
+/// A block returned by `allocate` must be valid for reads and writes of
+/// `layout.size()` bytes, aligned to `layout.align()`, and must not overlap
+/// any other live block until it is passed to `deallocate` or the allocator
+/// is dropped.
+pub unsafe trait NodeAlloc {
+    /// Allocate a block for `layout`.
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError>;
+
+    /// Give back a block.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have come from `allocate` on this allocator with the same
+    /// `layout`, and must not be used or deallocated again afterwards.
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+}
+
+unsafe impl<A: NodeAlloc + ?Sized> NodeAlloc for &A {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        (**self).allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { (**self).deallocate(ptr, layout) }
+    }
+}
+
+/// A well-aligned, non-null pointer for a zero-sized block.
+fn dangling(layout: Layout) -> NonNull<u8> {
+    NonNull::new(ptr::without_provenance_mut(layout.align())).unwrap()
+}
+
+// --------------------------------------------------
+// Global
+// --------------------------------------------------
+
+/// The global allocator: `std::alloc::alloc` / `dealloc` per node.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Global;
+
+unsafe impl NodeAlloc for Global {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        if layout.size() == 0 {
+            return Ok(dangling(layout));
+        }
+        NonNull::new(unsafe { alloc::alloc(layout) }).ok_or(AllocError)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() != 0 {
+            unsafe { alloc::dealloc(ptr.as_ptr(), layout) };
+        }
+    }
+}
+
+// --------------------------------------------------
+// Bump
+// --------------------------------------------------
+
+/// Bump (arena) allocator.
+///
+/// Carves blocks out of chunks taken from the global allocator, moving a
+/// cursor forward. `deallocate` does nothing; every chunk is freed when the
+/// arena is dropped, so a list that churns through nodes keeps growing the
+/// arena. Suited to lists that are built, used and thrown away together.
+pub struct Bump {
+    chunk_size: usize,
+    /// Chunks taken so far, last one current.
+    chunks: RefCell<Vec<(NonNull<u8>, Layout)>>,
+    /// Bytes of the current chunk handed out.
+    used: Cell<usize>,
+}
+
+impl Bump {
+    /// Chunk size used by `new`.
+    pub const DEFAULT_CHUNK_SIZE: usize = 4096;
+
+    pub fn new() -> Self {
+        Self::with_chunk_size(Self::DEFAULT_CHUNK_SIZE)
+    }
+
+    /// An arena that takes `chunk_size` bytes at a time (more for a block
+    /// that would not fit).
+    pub fn with_chunk_size(chunk_size: usize) -> Self {
+        Self {
+            chunk_size: chunk_size.max(1),
+            chunks: RefCell::new(Vec::new()),
+            used: Cell::new(0),
+        }
+    }
+
+    /// Number of chunks taken from the global allocator.
+    pub fn chunks(&self) -> usize {
+        self.chunks.borrow().len()
+    }
+
+    /// Bytes taken from the global allocator, handed out or not.
+    pub fn capacity(&self) -> usize {
+        self.chunks.borrow().iter().map(|(_, l)| l.size()).sum()
+    }
+
+    /// Take a new chunk with room for `layout` and make it current.
+    fn grow(&self, layout: Layout) -> Result<(), AllocError> {
+        let size = self.chunk_size.max(layout.size());
+        let chunk = Layout::from_size_align(size, layout.align()).map_err(|_| AllocError)?;
+        let ptr = NonNull::new(unsafe { alloc::alloc(chunk) }).ok_or(AllocError)?;
+        self.chunks.borrow_mut().push((ptr, chunk));
+        self.used.set(0);
+        Ok(())
+    }
+
+    /// Hand out the next block of the current chunk, if it has room.
+    fn bump(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let &(base, chunk) = self.chunks.borrow().last()?;
+        let used = self.used.get();
+        let start = used + unsafe { base.add(used) }.align_offset(layout.align());
+        if start + layout.size() > chunk.size() {
+            return None;
+        }
+        self.used.set(start + layout.size());
+        Some(unsafe { base.add(start) })
+    }
+}
+
+impl Default for Bump {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Bump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bump")
+            .field("chunk_size", &self.chunk_size)
+            .field("chunks", &self.chunks())
+            .field("used", &self.used.get())
+            .finish()
+    }
+}
+
+unsafe impl NodeAlloc for Bump {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        if layout.size() == 0 {
+            return Ok(dangling(layout));
+        }
+        if let Some(ptr) = self.bump(layout) {
+            return Ok(ptr);
+        }
+        self.grow(layout)?;
+        Ok(self
+            .bump(layout)
+            .expect("a fresh chunk has room for the block"))
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+        // Freed with the arena.
+    }
+}
+
+impl Drop for Bump {
+    fn drop(&mut self) {
+        for (ptr, layout) in self.chunks.get_mut().drain(..) {
+            unsafe { alloc::dealloc(ptr.as_ptr(), layout) };
+        }
+    }
+}
+
+// --------------------------------------------------
+// Slab
+// --------------------------------------------------
+
+/// Fixed-capacity slab of equal slots.
+///
+/// The first allocation fixes the slot layout and takes room for all
+/// `capacity` slots in one block; later requests must fit in a slot, which
+/// they do when they all come from lists of the same node type. Freed slots
+/// go on an intrusive free list and are handed out again, so a list that
+/// never holds more than `capacity` nodes never touches `malloc` after the
+/// first push. Once every slot is in use, `allocate` fails.
+pub struct Slab {
+    capacity: usize,
+    /// Slot layout and the block holding every slot, once known.
+    block: Cell<Option<(Layout, NonNull<u8>)>>,
+    /// Slots never handed out, from the front of the block.
+    untouched: Cell<usize>,
+    /// Freed slots; each stores the address of the next.
+    free: Cell<*mut u8>,
+    live: Cell<usize>,
+}
+
+impl Slab {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            block: Cell::new(None),
+            untouched: Cell::new(0),
+            free: Cell::new(ptr::null_mut()),
+            live: Cell::new(0),
+        }
+    }
+
+    /// Number of slots.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Slots currently handed out.
+    pub fn live(&self) -> usize {
+        self.live.get()
+    }
+
+    /// Pick the slot layout for `layout` and take the block.
+    fn init(&self, layout: Layout) -> Result<(Layout, NonNull<u8>), AllocError> {
+        // A free slot holds a pointer to the next one.
+        let slot = Layout::from_size_align(
+            layout.size().max(size_of::<*mut u8>()),
+            layout.align().max(align_of::<*mut u8>()),
+        )
+        .map_err(|_| AllocError)?
+        .pad_to_align();
+        let all = Layout::from_size_align(
+            slot.size().checked_mul(self.capacity).ok_or(AllocError)?,
+            slot.align(),
+        )
+        .map_err(|_| AllocError)?;
+        let base = Global.allocate(all)?;
+        self.block.set(Some((slot, base)));
+        Ok((slot, base))
+    }
+}
+
+impl fmt::Debug for Slab {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Slab")
+            .field("capacity", &self.capacity)
+            .field("live", &self.live())
+            .finish()
+    }
+}
+
+unsafe impl NodeAlloc for Slab {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        if self.capacity == 0 {
+            return Err(AllocError);
+        }
+        let (slot, base) = match self.block.get() {
+            Some(block) => block,
+            None => self.init(layout)?,
+        };
+        if layout.size() > slot.size() || layout.align() > slot.align() {
+            return Err(AllocError);
+        }
+
+        let ptr = if let Some(ptr) = NonNull::new(self.free.get()) {
+            self.free.set(unsafe { ptr.cast::<*mut u8>().read() });
+            ptr
+        } else if self.untouched.get() < self.capacity {
+            let i = self.untouched.get();
+            self.untouched.set(i + 1);
+            unsafe { base.add(i * slot.size()) }
+        } else {
+            return Err(AllocError);
+        };
+        self.live.set(self.live.get() + 1);
+        Ok(ptr)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, _layout: Layout) {
+        unsafe { ptr.cast::<*mut u8>().write(self.free.get()) };
+        self.free.set(ptr.as_ptr());
+        self.live.set(self.live.get() - 1);
+    }
+}
+
+impl Drop for Slab {
+    fn drop(&mut self) {
+        if let Some((slot, base)) = self.block.get() {
+            let all = Layout::from_size_align(slot.size() * self.capacity, slot.align()).unwrap();
+            unsafe { Global.deallocate(base, all) };
+        }
+    }
+}
+
+// --------------------------------------------------
+// Failing
+// --------------------------------------------------
+
+/// Test allocator: forwards to `inner` for the first `budget` allocations,
+/// then fails every one after that.
+///
+/// Also counts blocks handed out and not yet returned, so a test can check
+/// that a list freed every node it allocated.
+#[derive(Debug)]
+pub struct Failing<A: NodeAlloc = Global> {
+    inner: A,
+    budget: Cell<usize>,
+    live: Cell<usize>,
+    failures: Cell<usize>,
+}
+
+impl Failing {
+    /// Succeed `budget` times using the global allocator, then fail.
+    pub fn after(budget: usize) -> Self {
+        Self::after_in(budget, Global)
+    }
+}
+
+impl<A: NodeAlloc> Failing<A> {
+    /// Succeed `budget` times using `inner`, then fail.
+    pub fn after_in(budget: usize, inner: A) -> Self {
+        Self {
+            inner,
+            budget: Cell::new(budget),
+            live: Cell::new(0),
+            failures: Cell::new(0),
+        }
+    }
+
+    /// Allow `budget` more allocations from now on.
+    pub fn set_budget(&self, budget: usize) {
+        self.budget.set(budget);
+    }
+
+    /// Blocks allocated and not yet deallocated.
+    pub fn live(&self) -> usize {
+        self.live.get()
+    }
+
+    /// Allocations refused so far.
+    pub fn failures(&self) -> usize {
+        self.failures.get()
+    }
+}
+
+unsafe impl<A: NodeAlloc> NodeAlloc for Failing<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        if self.budget.get() == 0 {
+            self.failures.set(self.failures.get() + 1);
+            return Err(AllocError);
+        }
+        let ptr = self.inner.allocate(layout)?;
+        self.budget.set(self.budget.get() - 1);
+        self.live.set(self.live.get() + 1);
+        Ok(ptr)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.live.set(self.live.get() - 1);
+        unsafe { self.inner.deallocate(ptr, layout) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Allocate `n` blocks of `layout`, write each one, and return them.
+    fn fill(a: &impl NodeAlloc, layout: Layout, n: usize) -> Vec<NonNull<u8>> {
+        (0..n)
+            .map(|i| {
+                let p = a.allocate(layout).unwrap();
+                assert_eq!(p.as_ptr() as usize % layout.align(), 0);
+                unsafe { p.as_ptr().write_bytes(i as u8, layout.size()) };
+                p
+            })
+            .collect()
+    }
+
+    #[test]
+    fn global_round_trips_and_allows_zero_sized_blocks() {
+        let layout = Layout::new::<[u64; 3]>();
+        let blocks = fill(&Global, layout, 4);
+        for p in blocks {
+            unsafe { Global.deallocate(p, layout) };
+        }
+
+        let zst = Layout::new::<()>();
+        let p = Global.allocate(zst).unwrap();
+        unsafe { Global.deallocate(p, zst) };
+    }
+
+    #[test]
+    fn bump_packs_blocks_into_chunks_and_frees_on_drop() {
+        let bump = Bump::with_chunk_size(64);
+        let layout = Layout::new::<u64>();
+
+        let blocks = fill(&bump, layout, 8);
+        assert_eq!(bump.chunks(), 1);
+        for (i, p) in blocks.iter().enumerate() {
+            assert_eq!(unsafe { p.as_ptr().read() }, i as u8);
+        }
+
+        // Full: the next block starts a new chunk, as does one too big for
+        // a chunk.
+        fill(&bump, layout, 1);
+        assert_eq!(bump.chunks(), 2);
+        fill(&bump, Layout::new::<[u64; 16]>(), 1);
+        assert_eq!(bump.chunks(), 3);
+        assert_eq!(bump.capacity(), 64 + 64 + 128);
+
+        // Alignment padding is respected after an odd-sized block.
+        fill(&bump, Layout::new::<u8>(), 1);
+        fill(&bump, Layout::new::<u64>(), 1);
+    }
+
+    #[test]
+    fn slab_recycles_slots_and_fails_when_full() {
+        let slab = Slab::new(3);
+        let layout = Layout::new::<[u32; 3]>();
+
+        let mut blocks = fill(&slab, layout, 3);
+        assert_eq!(slab.live(), 3);
+        assert_eq!(slab.allocate(layout), Err(AllocError));
+
+        let freed = blocks.pop().unwrap();
+        unsafe { slab.deallocate(freed, layout) };
+        assert_eq!(slab.live(), 2);
+        assert_eq!(slab.allocate(layout), Ok(freed));
+
+        // Bigger or more aligned than a slot: refused.
+        assert_eq!(slab.allocate(Layout::new::<[u64; 4]>()), Err(AllocError));
+        assert_eq!(Slab::new(0).allocate(layout), Err(AllocError));
+    }
+
+    #[test]
+    fn failing_counts_and_refuses_after_its_budget() {
+        let failing = Failing::after(2);
+        let layout = Layout::new::<u64>();
+
+        let blocks = fill(&failing, layout, 2);
+        assert_eq!(failing.allocate(layout), Err(AllocError));
+        assert_eq!((failing.live(), failing.failures()), (2, 1));
+
+        for p in blocks {
+            unsafe { failing.deallocate(p, layout) };
+        }
+        assert_eq!(failing.live(), 0);
+
+        failing.set_budget(1);
+        let p = failing.allocate(layout).unwrap();
+        unsafe { failing.deallocate(p, layout) };
+    }
+}
@@ -18,29 +18,23 @@
 //      - `Rc<RefCell<Node>>` for forward (`next`) links
 //      - `Weak<RefCell<Node>>` for backward (`prev`) links
 // 3) You MUST NOT use raw pointers or `union` here.
-// 4) The list stores `Value` (an enum provided in value.rs).
+// 4) The list is generic over its payload `T`; the tests use `Value`
+//    (an enum provided in value.rs).
 // 5) Your implementation must not panic under correct usage.
 //
 // ---------------------------------
-// TODOs (What you must implement)
+// What is implemented here
 // ---------------------------------
 //
-// You must implement ALL of the following:
-//
-// [value.rs]
-//   - Value::as_int
-//   - Value::as_float
-//
 // [Node]
-//   - Node::new
-//   - Node::next
-//   - Node::prev
+//   - Node::new, Node::next, Node::prev, Node::into_value
 //
 // [DoublyLinkedList]
-//   - push_front
-//   - push_back
-//   - pop_front
-//   - pop_back
+//   - push/pop at both ends in O(1)
+//   - indexed access and positional insert/remove
+//   - a `CursorMut` for O(1) splicing at an arbitrary position
+//   - the std collection traits (`Extend`, `FromIterator`, iterators, ...)
+//   - an iterative `Drop`, so long lists cannot overflow the stack
 //
 // ---------------------------------
 // What this assignment is testing
@@ -53,17 +47,21 @@
 // - Encoding invariants using types instead of comments
 //
 
-use std::cell::RefCell;
+use std::cell::{Ref, RefCell, RefMut};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
 use std::rc::{Rc, Weak};
 
-use crate::value::Value;
+use crate::error::ListError;
+use crate::teardown::drop_chain;
 
 /// A link to another node in the list.
 ///
 /// We use `Option` because:
 /// - the head has no previous node
 /// - the tail has no next node
-type Link = Option<Rc<RefCell<Node>>>;
+type Link<T> = Option<Rc<RefCell<Node<T>>>>;
 
 /// A node in the doubly linked list.
 ///
@@ -72,50 +70,54 @@ type Link = Option<Rc<RefCell<Node>>>;
 /// - `prev` uses `Weak` to avoid reference cycles
 ///
 /// If `prev` were `Rc`, the list would leak memory.
-struct Node {
-    value: Value,
-    prev: Option<Weak<RefCell<Node>>>,
-    next: Link,
+struct Node<T> {
+    value: T,
+    prev: Option<Weak<RefCell<Node<T>>>>,
+    next: Link<T>,
 }
 
-impl Node {
+impl<T> Node<T> {
     /// Create a new node containing `value`.
     ///
     /// The new node:
     ///   - has no previous node
     ///   - has no next node
     ///
-    /// TODO:
-    ///   - Allocate a `Node`
-    ///   - Wrap it in `Rc<RefCell<_>>`
-    ///   - Initialize `prev = None`, `next = None`
-    fn new(value: Value) -> Rc<RefCell<Self>> {
-        todo!("Create Rc<RefCell<Node>> with prev=None and next=None");
+    /// The node is returned already wrapped in `Rc<RefCell<_>>`, ready to be
+    /// linked into a list.
+    fn new(value: T) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Node {
+            value,
+            prev: None,
+            next: None,
+        }))
     }
 
     /// Return the next node in the list.
     ///
-    /// IMPORTANT:
-    ///   - This returns an owned `Rc`, not a reference.
-    ///   - You must CLONE the `Rc` stored in `self.next`.
-    ///
-    /// TODO:
-    ///   - Return `self.next.clone()`
-    fn next(&self) -> Link {
-        todo!("Return self.next cloned");
+    /// This returns an owned `Rc` (a clone of `self.next`), not a reference,
+    /// so the caller can keep walking after the current borrow ends.
+    fn next(&self) -> Link<T> {
+        self.next.clone()
     }
 
     /// Return the previous node in the list.
     ///
-    /// IMPORTANT:
-    ///   - `prev` is stored as `Weak`
-    ///   - You must call `upgrade()` to get `Option<Rc<_>>`
+    /// `prev` is stored as `Weak`, so this upgrades it. `None` means either
+    /// this is the head or the previous node has already been dropped.
+    fn prev(&self) -> Link<T> {
+        self.prev.as_ref().and_then(|w| w.upgrade())
+    }
+
+    /// Move the value out of a node that has been fully unlinked.
     ///
-    /// TODO:
-    ///   - If `self.prev` is `Some(w)`, return `w.upgrade()`
-    ///   - Otherwise, return `None`
-    fn prev(&self) -> Link {
-        todo!("Upgrade self.prev (Weak) into Rc and return it");
+    /// Once a node is detached from its neighbours (and the list's
+    /// head/tail), the caller holds the only strong reference to it.
+    fn into_value(node: Rc<RefCell<Self>>) -> T {
+        match Rc::try_unwrap(node) {
+            Ok(cell) => cell.into_inner().value,
+            Err(_) => unreachable!("unlinked node is still shared"),
+        }
     }
 }
 
@@ -128,13 +130,13 @@ impl Node {
 ///   - `tail.next == None`
 ///   - Forward traversal from head reaches exactly `len` nodes
 ///   - Backward traversal from tail reaches exactly `len` nodes
-pub struct DoublyLinkedList {
-    head: Link,
-    tail: Link,
+pub struct DoublyLinkedList<T> {
+    head: Link<T>,
+    tail: Link<T>,
     len: usize,
 }
 
-impl DoublyLinkedList {
+impl<T> DoublyLinkedList<T> {
     /// Create an empty list.
     pub fn new() -> Self {
         Self {
@@ -156,107 +158,282 @@ impl DoublyLinkedList {
 
     /// Insert a value at the front of the list.
     ///
-    /// TODO:
-    ///   Handle the following cases:
-    ///
-    ///   1) Empty list:
-    ///      - head = tail = new_node
-    ///
-    ///   2) Non-empty list:
-    ///      - new_node.next = old_head
-    ///      - old_head.prev = Weak(new_node)
-    ///      - head = new_node
-    ///
-    /// HINT:
-    ///   - Keep `borrow_mut()` scopes SHORT.
-    ///   - Never hold two mutable borrows at once.
-    pub fn push_front(&mut self, value: Value) {
-        todo!("Implement push_front");
+    /// On an empty list the new node becomes both head and tail. Otherwise it
+    /// takes ownership of the old head, which gets a `Weak` back-link to it.
+    /// Each `borrow_mut()` is a single statement, so no two mutable borrows
+    /// are ever alive at once.
+    pub fn push_front(&mut self, value: T) {
+        let new_node = Node::new(value);
+
+        match self.head.take() {
+            None => {
+                self.tail = Some(new_node.clone());
+            }
+            Some(old_head) => {
+                old_head.borrow_mut().prev = Some(Rc::downgrade(&new_node));
+                new_node.borrow_mut().next = Some(old_head);
+            }
+        }
+
+        self.head = Some(new_node);
+        self.len += 1;
     }
 
     /// Insert a value at the back of the list.
     ///
-    /// TODO:
-    ///   Handle the following cases:
-    ///
-    ///   1) Empty list:
-    ///      - head = tail = new_node
-    ///
-    ///   2) Non-empty list:
-    ///      - old_tail.next = new_node
-    ///      - new_node.prev = Weak(old_tail)
-    ///      - tail = new_node
-    pub fn push_back(&mut self, value: Value) {
-        todo!("Implement push_back");
+    /// On an empty list the new node becomes both head and tail. Otherwise the
+    /// old tail takes ownership of it and the new node gets a `Weak` back-link.
+    pub fn push_back(&mut self, value: T) {
+        let new_node = Node::new(value);
+
+        match self.tail.take() {
+            None => {
+                self.head = Some(new_node.clone());
+            }
+            Some(old_tail) => {
+                new_node.borrow_mut().prev = Some(Rc::downgrade(&old_tail));
+                old_tail.borrow_mut().next = Some(new_node.clone());
+            }
+        }
+
+        self.tail = Some(new_node);
+        self.len += 1;
     }
 
     /// Remove and return the value at the front of the list.
     ///
-    /// TODO:
-    ///   Handle the following cases:
-    ///
-    ///   1) Empty list:
-    ///      - return None
-    ///
-    ///   2) Single element:
-    ///      - clear head and tail
-    ///      - len becomes 0
-    ///
-    ///   3) Multiple elements:
-    ///      - new_head = old_head.next
-    ///      - new_head.prev = None
-    ///
-    /// NOTE:
-    ///   - Detaching the removed node's links is good hygiene.
-    pub fn pop_front(&mut self) -> Option<Value> {
-        todo!("Implement pop_front");
+    /// Returns `None` on an empty list. Popping the last element clears both
+    /// head and tail; otherwise the next node becomes the head and loses its
+    /// back-link. The removed node is fully detached before its value is
+    /// moved out.
+    pub fn pop_front(&mut self) -> Option<T> {
+        let old_head = self.head.take()?;
+
+        let next = old_head.borrow_mut().next.take();
+        match next {
+            None => {
+                self.tail = None;
+            }
+            Some(new_head) => {
+                new_head.borrow_mut().prev = None;
+                self.head = Some(new_head);
+            }
+        }
+
+        self.len -= 1;
+
+        Some(Node::into_value(old_head))
     }
 
     /// Remove and return the value at the back of the list.
     ///
-    /// TODO:
-    ///   Handle the following cases:
+    /// Returns `None` on an empty list. Popping the last element clears both
+    /// head and tail; otherwise the upgraded `prev` node becomes the tail and
+    /// drops its `next` link.
+    pub fn pop_back(&mut self) -> Option<T> {
+        let old_tail = self.tail.take()?;
+
+        let prev = old_tail.borrow_mut().prev.take().and_then(|w| w.upgrade());
+        match prev {
+            None => {
+                self.head = None;
+            }
+            Some(new_tail) => {
+                new_tail.borrow_mut().next = None;
+                self.tail = Some(new_tail);
+            }
+        }
+
+        self.len -= 1;
+
+        Some(Node::into_value(old_tail))
+    }
+
+    /// Return the node at `index`, walking from whichever end is closer.
+    fn node_at(&self, index: usize) -> Result<Rc<RefCell<Node<T>>>, ListError> {
+        let out_of_bounds = ListError::IndexOutOfBounds {
+            index,
+            len: self.len,
+        };
+        if index >= self.len {
+            return Err(out_of_bounds);
+        }
+
+        let node = if index < self.len / 2 {
+            self.nodes().nth(index)
+        } else {
+            std::iter::successors(self.tail.clone(), |node| node.borrow().prev())
+                .nth(self.len - 1 - index)
+        };
+        node.ok_or(out_of_bounds)
+    }
+
+    /// Return a clone of the value at `index`.
+    pub fn get(&self, index: usize) -> Result<T, ListError>
+    where
+        T: Clone,
+    {
+        let node = self.node_at(index)?;
+        let value = node.borrow().value.clone();
+        Ok(value)
+    }
+
+    /// Run `f` on the value at `index` and return its result.
     ///
-    ///   1) Empty list:
-    ///      - return None
+    /// This takes a closure rather than returning a `RefMut` because the
+    /// borrow would otherwise have to outlive the node handle found by the
+    /// walk. For repeated edits around one position, use `cursor_at_mut`.
+    pub fn get_mut<R>(
+        &mut self,
+        index: usize,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Result<R, ListError> {
+        let node = self.node_at(index)?;
+        let out = f(&mut node.borrow_mut().value);
+        Ok(out)
+    }
+
+    /// Insert `value` so that it ends up at position `index`.
+    ///
+    /// `index == len` appends; anything larger is an error.
+    pub fn insert_at(&mut self, index: usize, value: T) -> Result<(), ListError> {
+        if index == self.len {
+            self.push_back(value);
+            return Ok(());
+        }
+
+        self.cursor_at_mut(index)?.insert_before(value);
+        Ok(())
+    }
+
+    /// Remove and return the value at `index`.
+    pub fn remove_at(&mut self, index: usize) -> Result<T, ListError> {
+        let len = self.len;
+        self.cursor_at_mut(index)?
+            .remove_current()
+            .ok_or(ListError::IndexOutOfBounds { index, len })
+    }
+
+    /// Swap the values at positions `i` and `j`. The nodes stay in place.
+    pub fn swap(&mut self, i: usize, j: usize) -> Result<(), ListError> {
+        let a = self.node_at(i)?;
+        let b = self.node_at(j)?;
+        if i == j {
+            return Ok(());
+        }
+
+        std::mem::swap(&mut a.borrow_mut().value, &mut b.borrow_mut().value);
+        Ok(())
+    }
+
+    /// Keep the first `len` elements and drop the rest.
     ///
-    ///   2) Single element:
-    ///      - clear head and tail
-    ///      - len becomes 0
+    /// Does nothing if the list is already `len` elements or shorter.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        if len == 0 {
+            *self = Self::new();
+            return;
+        }
+
+        if let Ok(mut cursor) = self.cursor_at_mut(len - 1) {
+            drop(cursor.split_after());
+        }
+    }
+
+    /// Borrow the value at the front of the list.
     ///
-    ///   3) Multiple elements:
-    ///      - new_tail = old_tail.prev (upgrade Weak)
-    ///      - new_tail.next = None
-    pub fn pop_back(&mut self) -> Option<Value> {
-        todo!("Implement pop_back");
+    /// The returned `Ref` keeps the head node borrowed; drop it before
+    /// mutating the list.
+    pub fn peek_front(&self) -> Option<Ref<'_, T>> {
+        self.head
+            .as_ref()
+            .map(|node| Ref::map(node.borrow(), |n| &n.value))
+    }
+
+    /// Borrow the value at the back of the list.
+    pub fn peek_back(&self) -> Option<Ref<'_, T>> {
+        self.tail
+            .as_ref()
+            .map(|node| Ref::map(node.borrow(), |n| &n.value))
     }
 
     /// Iterate from head to tail.
     ///
     /// This iterator:
-    /// - yields `Value` by copy
+    /// - yields clones of `T` (requires `T: Clone`)
     /// - uses `Node::next()` internally
     /// - stops after `len` elements (cycle guard)
-    pub fn iter_forward(&self) -> IterForward {
+    pub fn iter_forward(&self) -> IterForward<'_, T> {
         IterForward {
-            cur: self.head.clone(),
+            front: self.head.clone(),
+            back: self.tail.clone(),
             remaining: self.len,
+            _list: PhantomData,
         }
     }
 
     /// Iterate from tail to head.
     ///
     /// This iterator:
-    /// - yields `Value` by copy
+    /// - yields clones of `T` (requires `T: Clone`)
     /// - uses `Node::prev()` internally
     /// - stops after `len` elements (cycle guard)
-    pub fn iter_backward(&self) -> IterBackward {
+    pub fn iter_backward(&self) -> IterBackward<'_, T> {
         IterBackward {
-            cur: self.tail.clone(),
+            front: self.head.clone(),
+            back: self.tail.clone(),
             remaining: self.len,
+            _list: PhantomData,
+        }
+    }
+
+    /// Return a cursor positioned at the front element.
+    ///
+    /// If the list is empty, the cursor starts on the "ghost" position
+    /// (see `CursorMut`).
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.head.clone(),
+            index: 0,
+            list: self,
+        }
+    }
+
+    /// Return a cursor positioned at the element at `index`.
+    ///
+    /// The walk to `index` starts from whichever end is closer.
+    pub fn cursor_at_mut(&mut self, index: usize) -> Result<CursorMut<'_, T>, ListError> {
+        let node = self.node_at(index)?;
+        Ok(CursorMut {
+            current: Some(node),
+            index,
+            list: self,
+        })
+    }
+
+    /// Return a cursor positioned at the back element.
+    ///
+    /// If the list is empty, the cursor starts on the "ghost" position
+    /// (see `CursorMut`).
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.tail.clone(),
+            index: self.len.saturating_sub(1),
+            list: self,
         }
     }
 
+    /// Walk the node handles from head to tail.
+    ///
+    /// Unlike `iter_forward`, this does not need `T: Clone`; callers
+    /// borrow each node briefly to look at its value.
+    fn nodes(&self) -> impl Iterator<Item = Rc<RefCell<Node<T>>>> + '_ {
+        std::iter::successors(self.head.clone(), |node| node.borrow().next()).take(self.len)
+    }
+
     /// Check all list invariants.
     ///
     /// This function is extremely helpful for debugging and testing.
@@ -271,23 +448,23 @@ impl DoublyLinkedList {
         }
 
         // head.prev must be None
-        if let Some(h) = &self.head {
-            if h.borrow().prev.is_some() {
-                return false;
-            }
+        if let Some(h) = &self.head
+            && h.borrow().prev.is_some()
+        {
+            return false;
         }
 
         // tail.next must be None
-        if let Some(t) = &self.tail {
-            if t.borrow().next.is_some() {
-                return false;
-            }
+        if let Some(t) = &self.tail
+            && t.borrow().next.is_some()
+        {
+            return false;
         }
 
         // Traverse forward, count, and verify back-links
         let mut count = 0usize;
         let mut cur = self.head.clone();
-        let mut prev: Link = None;
+        let mut prev: Link<T> = None;
 
         while let Some(node_rc) = cur {
             count += 1;
@@ -335,59 +512,451 @@ impl DoublyLinkedList {
     }
 }
 
+impl<T> Default for DoublyLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for DoublyLinkedList<T> {
+    /// Free the nodes front to back in a loop.
+    ///
+    /// The default drop would recurse through every `next` link and
+    /// overflow the stack on long lists.
+    fn drop(&mut self) {
+        // `tail` holds a second strong reference to the last node.
+        self.tail = None;
+        self.len = 0;
+
+        drop_chain(self.head.take(), |node| match Rc::try_unwrap(node) {
+            Ok(cell) => cell.into_inner().next,
+            Err(_) => None,
+        });
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DoublyLinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        for node in self.nodes() {
+            list.entry(&node.borrow().value);
+        }
+        list.finish()
+    }
+}
+
+impl<T: Clone> Clone for DoublyLinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter_forward().collect()
+    }
+}
+
+impl<T: PartialEq> PartialEq for DoublyLinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && self
+                .nodes()
+                .zip(other.nodes())
+                .all(|(a, b)| a.borrow().value == b.borrow().value)
+    }
+}
+
+impl<T: Eq> Eq for DoublyLinkedList<T> {}
+
+impl<T: Hash> Hash for DoublyLinkedList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for node in self.nodes() {
+            node.borrow().value.hash(state);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for DoublyLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for DoublyLinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<T> IntoIterator for DoublyLinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    /// Consume the list, yielding its values from head to tail.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T: Clone> IntoIterator for &'a DoublyLinkedList<T> {
+    type Item = T;
+    type IntoIter = IterForward<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_forward()
+    }
+}
+
+/// A cursor over a `DoublyLinkedList` that can edit the list in place.
+///
+/// The cursor always points either at an element or at the "ghost"
+/// position, which sits between the tail and the head:
+///   - moving next from the tail (or prev from the head) lands on the ghost
+///   - moving next from the ghost lands on the head
+///   - moving prev from the ghost lands on the tail
+///
+/// Every operation is O(1): the cursor keeps its own `Rc` to the current
+/// node and tracks its index, so no traversal from head/tail is needed.
+///
+/// INVARIANTS:
+///   - `current == None` iff the cursor is on the ghost
+///   - `index` is the position of `current`, or `list.len` on the ghost
+pub struct CursorMut<'a, T> {
+    list: &'a mut DoublyLinkedList<T>,
+    current: Link<T>,
+    index: usize,
+}
+
+impl<T> CursorMut<'_, T> {
+    /// Return the index of the current element, or `None` on the ghost.
+    pub fn index(&self) -> Option<usize> {
+        self.current.as_ref().map(|_| self.index)
+    }
+
+    /// Borrow the current element, or `None` on the ghost.
+    pub fn current(&self) -> Option<Ref<'_, T>> {
+        self.current
+            .as_ref()
+            .map(|node| Ref::map(node.borrow(), |n| &n.value))
+    }
+
+    /// Mutably borrow the current element, or `None` on the ghost.
+    pub fn current_mut(&mut self) -> Option<RefMut<'_, T>> {
+        self.current
+            .as_ref()
+            .map(|node| RefMut::map(node.borrow_mut(), |n| &mut n.value))
+    }
+
+    /// Move to the next element (towards the tail).
+    pub fn move_next(&mut self) {
+        match self.current.take() {
+            Some(node) => {
+                self.current = node.borrow().next();
+                self.index += 1;
+            }
+            None => {
+                self.current = self.list.head.clone();
+                self.index = 0;
+            }
+        }
+    }
+
+    /// Move to the previous element (towards the head).
+    pub fn move_prev(&mut self) {
+        match self.current.take() {
+            Some(node) => {
+                self.current = node.borrow().prev();
+                self.index = match self.index {
+                    0 => self.list.len,
+                    i => i - 1,
+                };
+            }
+            None => {
+                self.current = self.list.tail.clone();
+                self.index = self.list.len.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Insert `value` immediately before the current element.
+    ///
+    /// On the ghost, this appends to the back of the list.
+    pub fn insert_before(&mut self, value: T) {
+        let Some(cur) = self.current.clone() else {
+            self.list.push_back(value);
+            self.index += 1;
+            return;
+        };
+
+        let new_node = Node::new(value);
+        let prev = cur.borrow().prev();
+
+        new_node.borrow_mut().next = Some(cur.clone());
+        cur.borrow_mut().prev = Some(Rc::downgrade(&new_node));
+
+        match prev {
+            None => {
+                self.list.head = Some(new_node);
+            }
+            Some(prev) => {
+                new_node.borrow_mut().prev = Some(Rc::downgrade(&prev));
+                prev.borrow_mut().next = Some(new_node);
+            }
+        }
+
+        self.list.len += 1;
+        self.index += 1;
+    }
+
+    /// Insert `value` immediately after the current element.
+    ///
+    /// On the ghost, this prepends to the front of the list.
+    pub fn insert_after(&mut self, value: T) {
+        let Some(cur) = self.current.clone() else {
+            self.list.push_front(value);
+            self.index += 1;
+            return;
+        };
+
+        let new_node = Node::new(value);
+        let next = cur.borrow_mut().next.take();
+
+        new_node.borrow_mut().prev = Some(Rc::downgrade(&cur));
+
+        match next {
+            None => {
+                self.list.tail = Some(new_node.clone());
+            }
+            Some(next) => {
+                next.borrow_mut().prev = Some(Rc::downgrade(&new_node));
+                new_node.borrow_mut().next = Some(next);
+            }
+        }
+
+        cur.borrow_mut().next = Some(new_node);
+        self.list.len += 1;
+    }
+
+    /// Remove the current element and return it.
+    ///
+    /// The cursor moves to the element that followed the removed one
+    /// (or the ghost, if the tail was removed). Returns `None` on the ghost.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let cur = self.current.take()?;
+
+        let prev = cur.borrow_mut().prev.take().and_then(|w| w.upgrade());
+        let next = cur.borrow_mut().next.take();
+
+        match &prev {
+            None => self.list.head = next.clone(),
+            Some(prev) => prev.borrow_mut().next = next.clone(),
+        }
+        match &next {
+            None => self.list.tail = prev.clone(),
+            Some(next) => next.borrow_mut().prev = prev.as_ref().map(Rc::downgrade),
+        }
+
+        self.list.len -= 1;
+        self.current = next;
+
+        Some(Node::into_value(cur))
+    }
+
+    /// Split the list after the current element.
+    ///
+    /// Everything after the cursor is moved into a new list, which is
+    /// returned. On the ghost, the whole list is moved out.
+    pub fn split_after(&mut self) -> DoublyLinkedList<T> {
+        let Some(cur) = self.current.clone() else {
+            self.index = 0;
+            return std::mem::take(self.list);
+        };
+
+        let Some(next) = cur.borrow_mut().next.take() else {
+            return DoublyLinkedList::new();
+        };
+        next.borrow_mut().prev = None;
+
+        let split_len = self.list.len - self.index - 1;
+        let split_tail = self.list.tail.replace(cur);
+        self.list.len = self.index + 1;
+
+        DoublyLinkedList {
+            head: Some(next),
+            tail: split_tail,
+            len: split_len,
+        }
+    }
+
+    /// Move every element of `other` into this list, right after the
+    /// current element. On the ghost, `other` is spliced in at the front.
+    pub fn splice_after(&mut self, mut other: DoublyLinkedList<T>) {
+        let (Some(other_head), Some(other_tail)) = (other.head.take(), other.tail.take()) else {
+            return;
+        };
+        let other_len = std::mem::take(&mut other.len);
+
+        let next = match &self.current {
+            Some(cur) => {
+                let next = cur.borrow_mut().next.replace(other_head.clone());
+                other_head.borrow_mut().prev = Some(Rc::downgrade(cur));
+                next
+            }
+            None => {
+                self.index += other_len;
+                self.list.head.replace(other_head)
+            }
+        };
+
+        match next {
+            None => {
+                self.list.tail = Some(other_tail);
+            }
+            Some(next) => {
+                next.borrow_mut().prev = Some(Rc::downgrade(&other_tail));
+                other_tail.borrow_mut().next = Some(next);
+            }
+        }
+
+        self.list.len += other_len;
+    }
+}
+
+/// Yield the value at `cur` and advance `cur` towards the tail.
+fn step_forward<T: Clone>(cur: &mut Link<T>) -> Option<T> {
+    let node_rc = cur.take()?;
+    let node = node_rc.borrow();
+
+    *cur = node.next();
+    Some(node.value.clone())
+}
+
+/// Yield the value at `cur` and advance `cur` towards the head.
+fn step_backward<T: Clone>(cur: &mut Link<T>) -> Option<T> {
+    let node_rc = cur.take()?;
+    let node = node_rc.borrow();
+
+    *cur = node.prev();
+    Some(node.value.clone())
+}
+
 /// Forward iterator: head → tail
-pub struct IterForward {
-    cur: Link,
+///
+/// Borrows the list so it cannot be mutated while iterating.
+/// `front` and `back` walk towards each other; `remaining` stops them
+/// before they cross.
+pub struct IterForward<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
     remaining: usize,
+    _list: PhantomData<&'a DoublyLinkedList<T>>,
 }
 
-impl Iterator for IterForward {
-    type Item = Value;
+impl<T: Clone> Iterator for IterForward<'_, T> {
+    type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.remaining == 0 {
             return None;
         }
 
-        let node_rc = self.cur.clone()?;
-        let node = node_rc.borrow();
+        let value = step_forward(&mut self.front)?;
+        self.remaining -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
 
-        let value = node.value;
-        self.cur = node.next();
+impl<T: Clone> DoubleEndedIterator for IterForward<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
 
+        let value = step_backward(&mut self.back)?;
         self.remaining -= 1;
         Some(value)
     }
 }
 
+impl<T: Clone> ExactSizeIterator for IterForward<'_, T> {}
+
 /// Backward iterator: tail → head
-pub struct IterBackward {
-    cur: Link,
+///
+/// Borrows the list so it cannot be mutated while iterating.
+pub struct IterBackward<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
     remaining: usize,
+    _list: PhantomData<&'a DoublyLinkedList<T>>,
 }
 
-impl Iterator for IterBackward {
-    type Item = Value;
+impl<T: Clone> Iterator for IterBackward<'_, T> {
+    type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.remaining == 0 {
             return None;
         }
 
-        let node_rc = self.cur.clone()?;
-        let node = node_rc.borrow();
+        let value = step_backward(&mut self.back)?;
+        self.remaining -= 1;
+        Some(value)
+    }
 
-        let value = node.value;
-        self.cur = node.prev();
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
 
+impl<T: Clone> DoubleEndedIterator for IterBackward<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let value = step_forward(&mut self.front)?;
         self.remaining -= 1;
         Some(value)
     }
 }
 
+impl<T: Clone> ExactSizeIterator for IterBackward<'_, T> {}
+
+/// Owning iterator: drains the list from head to tail.
+///
+/// Created by `DoublyLinkedList::into_iter`.
+pub struct IntoIter<T> {
+    list: DoublyLinkedList<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::value::Value;
 
     fn v_int(x: i64) -> Value {
         Value::int(x)
@@ -398,7 +967,7 @@ mod tests {
 
     #[test]
     fn empty_list_iterators_and_pops() {
-        let mut list = DoublyLinkedList::new();
+        let mut list = DoublyLinkedList::<Value>::new();
 
         assert_eq!(list.len(), 0);
         assert!(list.is_empty());
@@ -596,4 +1165,425 @@ mod tests {
         assert_eq!(bwd.len(), list.len());
         assert!(list.check_invariants());
     }
+
+    fn ints(list: &DoublyLinkedList<Value>) -> Vec<i64> {
+        list.iter_forward().map(|v| v.as_int().unwrap()).collect()
+    }
+
+    fn list_of(xs: &[i64]) -> DoublyLinkedList<Value> {
+        let mut list = DoublyLinkedList::new();
+        for &x in xs {
+            list.push_back(v_int(x));
+        }
+        list
+    }
+
+    #[test]
+    fn cursor_moves_through_ghost_in_both_directions() {
+        let mut list = list_of(&[1, 2, 3]);
+        let mut cur = list.cursor_front_mut();
+
+        assert_eq!(cur.current().as_deref(), Some(&v_int(1)));
+        assert_eq!(cur.index(), Some(0));
+
+        cur.move_next();
+        cur.move_next();
+        assert_eq!(cur.current().as_deref(), Some(&v_int(3)));
+        assert_eq!(cur.index(), Some(2));
+
+        // tail -> ghost -> head
+        cur.move_next();
+        assert_eq!(cur.current().as_deref(), None);
+        assert_eq!(cur.index(), None);
+        cur.move_next();
+        assert_eq!(cur.current().as_deref(), Some(&v_int(1)));
+
+        // head -> ghost -> tail
+        cur.move_prev();
+        assert_eq!(cur.current().as_deref(), None);
+        cur.move_prev();
+        assert_eq!(cur.current().as_deref(), Some(&v_int(3)));
+        assert_eq!(cur.index(), Some(2));
+    }
+
+    #[test]
+    fn cursor_on_empty_list_stays_on_ghost() {
+        let mut list = DoublyLinkedList::<Value>::new();
+        let mut cur = list.cursor_back_mut();
+
+        assert_eq!(cur.current().as_deref(), None);
+        cur.move_next();
+        cur.move_prev();
+        assert_eq!(cur.current().as_deref(), None);
+        assert_eq!(cur.remove_current(), None);
+        assert!(list.check_invariants());
+    }
+
+    #[test]
+    fn cursor_insert_before_and_after_in_middle_and_ends() {
+        let mut list = list_of(&[1, 3]);
+        {
+            let mut cur = list.cursor_front_mut();
+            cur.insert_after(v_int(2)); // [1,2,3], cursor on 1
+            cur.insert_before(v_int(0)); // [0,1,2,3], cursor on 1
+            assert_eq!(cur.index(), Some(1));
+            assert_eq!(cur.current().as_deref(), Some(&v_int(1)));
+
+            cur.move_next();
+            cur.move_next(); // on 3 (tail)
+            cur.insert_after(v_int(4)); // new tail
+            assert_eq!(cur.index(), Some(3));
+        }
+        assert!(list.check_invariants());
+        assert_eq!(ints(&list), vec![0, 1, 2, 3, 4]);
+        assert_eq!(
+            list.iter_backward()
+                .map(|v| v.as_int().unwrap())
+                .collect::<Vec<_>>(),
+            vec![4, 3, 2, 1, 0]
+        );
+    }
+
+    #[test]
+    fn cursor_insert_on_ghost_wraps_to_the_ends() {
+        let mut list = list_of(&[2]);
+        {
+            let mut cur = list.cursor_front_mut();
+            cur.move_next(); // ghost
+            cur.insert_before(v_int(3)); // push_back
+            cur.insert_after(v_int(1)); // push_front
+            assert_eq!(cur.index(), None);
+            cur.move_prev();
+            assert_eq!(cur.current().as_deref(), Some(&v_int(3)));
+            assert_eq!(cur.index(), Some(2));
+        }
+        assert!(list.check_invariants());
+        assert_eq!(ints(&list), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn cursor_remove_current_everywhere_keeps_invariants() {
+        let mut list = list_of(&[0, 1, 2, 3, 4]);
+        {
+            let mut cur = list.cursor_front_mut();
+            cur.move_next();
+            cur.move_next();
+            assert_eq!(cur.remove_current(), Some(v_int(2))); // middle
+            assert_eq!(cur.current().as_deref(), Some(&v_int(3)));
+            assert_eq!(cur.index(), Some(2));
+        }
+        assert!(list.check_invariants());
+        {
+            let mut cur = list.cursor_front_mut();
+            assert_eq!(cur.remove_current(), Some(v_int(0))); // head
+            assert_eq!(cur.current().as_deref(), Some(&v_int(1)));
+        }
+        assert!(list.check_invariants());
+        {
+            let mut cur = list.cursor_back_mut();
+            assert_eq!(cur.remove_current(), Some(v_int(4))); // tail
+            assert_eq!(cur.current().as_deref(), None);
+        }
+        assert!(list.check_invariants());
+        assert_eq!(ints(&list), vec![1, 3]);
+
+        {
+            let mut cur = list.cursor_front_mut();
+            cur.remove_current();
+            cur.remove_current();
+            assert_eq!(cur.current().as_deref(), None);
+        }
+        assert!(list.is_empty());
+        assert!(list.check_invariants());
+    }
+
+    #[test]
+    fn cursor_split_after_detaches_the_tail_part() {
+        let mut list = list_of(&[0, 1, 2, 3, 4]);
+        let rest = {
+            let mut cur = list.cursor_front_mut();
+            cur.move_next();
+            cur.split_after()
+        };
+        assert!(list.check_invariants());
+        assert!(rest.check_invariants());
+        assert_eq!(ints(&list), vec![0, 1]);
+        assert_eq!(ints(&rest), vec![2, 3, 4]);
+
+        // Splitting after the tail yields an empty list.
+        let empty = list.cursor_back_mut().split_after();
+        assert!(empty.is_empty());
+        assert!(empty.check_invariants());
+        assert_eq!(ints(&list), vec![0, 1]);
+
+        // Splitting on the ghost moves everything out.
+        let all = {
+            let mut cur = list.cursor_back_mut();
+            cur.move_next();
+            cur.split_after()
+        };
+        assert!(list.is_empty());
+        assert!(list.check_invariants());
+        assert_eq!(ints(&all), vec![0, 1]);
+    }
+
+    #[test]
+    fn cursor_splice_after_in_middle_tail_and_ghost() {
+        let mut list = list_of(&[0, 3]);
+        {
+            let mut cur = list.cursor_front_mut();
+            cur.splice_after(list_of(&[1, 2]));
+            assert_eq!(cur.current().as_deref(), Some(&v_int(0)));
+            cur.move_next();
+            assert_eq!(cur.current().as_deref(), Some(&v_int(1)));
+        }
+        assert!(list.check_invariants());
+        assert_eq!(ints(&list), vec![0, 1, 2, 3]);
+
+        list.cursor_back_mut().splice_after(list_of(&[4, 5]));
+        assert!(list.check_invariants());
+        assert_eq!(ints(&list), vec![0, 1, 2, 3, 4, 5]);
+
+        {
+            let mut cur = list.cursor_front_mut();
+            cur.move_prev(); // ghost
+            cur.splice_after(list_of(&[-2, -1]));
+            assert_eq!(cur.index(), None);
+            cur.move_prev();
+            assert_eq!(cur.index(), Some(7));
+            cur.splice_after(DoublyLinkedList::new());
+        }
+        assert!(list.check_invariants());
+        assert_eq!(ints(&list), vec![-2, -1, 0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn cursor_split_then_splice_round_trips() {
+        let mut list = list_of(&(0..10).collect::<Vec<_>>());
+        let mut cur = list.cursor_front_mut();
+        for _ in 0..4 {
+            cur.move_next();
+        }
+        let rest = cur.split_after();
+        cur.splice_after(rest);
+        drop(cur);
+
+        assert!(list.check_invariants());
+        assert_eq!(ints(&list), (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn non_copy_payloads_are_moved_in_and_out() {
+        let mut list = DoublyLinkedList::new();
+        list.push_back("b".to_string());
+        list.push_front("a".to_string());
+        list.push_back("c".to_string());
+
+        assert_eq!(list.peek_front().as_deref(), Some(&"a".to_string()));
+        assert_eq!(list.peek_back().as_deref(), Some(&"c".to_string()));
+        assert_eq!(list.iter_forward().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+        assert_eq!(
+            list.iter_backward().collect::<Vec<_>>(),
+            vec!["c", "b", "a"]
+        );
+        assert!(list.check_invariants());
+
+        assert_eq!(list.pop_front(), Some("a".to_string()));
+        assert_eq!(list.pop_back(), Some("c".to_string()));
+        assert_eq!(list.pop_back(), Some("b".to_string()));
+        assert_eq!(list.pop_back(), None);
+        assert!(list.peek_front().is_none());
+        assert!(list.check_invariants());
+    }
+
+    #[test]
+    fn cursor_current_mut_edits_in_place() {
+        let mut list = DoublyLinkedList::new();
+        list.push_back(vec![1]);
+        list.push_back(vec![2]);
+
+        {
+            let mut cur = list.cursor_back_mut();
+            cur.current_mut().unwrap().push(3);
+            assert_eq!(cur.remove_current(), Some(vec![2, 3]));
+        }
+
+        assert_eq!(list.iter_forward().collect::<Vec<_>>(), vec![vec![1]]);
+        assert!(list.check_invariants());
+    }
+
+    fn hash_of<H: Hash>(x: &H) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+
+        let mut h = DefaultHasher::new();
+        x.hash(&mut h);
+        h.finish()
+    }
+
+    #[test]
+    fn collect_extend_and_equality() {
+        let mut list: DoublyLinkedList<i32> = (1..=3).collect();
+        list.extend([4, 5]);
+        assert!(list.check_invariants());
+
+        let other: DoublyLinkedList<i32> = (1..=5).collect();
+        assert_eq!(list, other);
+        assert_eq!(hash_of(&list), hash_of(&other));
+
+        list.pop_back();
+        assert_ne!(list, other);
+        assert_eq!(DoublyLinkedList::<i32>::default(), DoublyLinkedList::new());
+    }
+
+    #[test]
+    fn clone_is_deep_and_debug_prints_like_a_vec() {
+        let list: DoublyLinkedList<String> = ["x", "y"].iter().map(|s| s.to_string()).collect();
+        let mut copy = list.clone();
+        copy.push_back("z".to_string());
+
+        assert_eq!(format!("{:?}", list), r#"["x", "y"]"#);
+        assert_eq!(format!("{:?}", copy), r#"["x", "y", "z"]"#);
+        assert!(copy.check_invariants());
+    }
+
+    #[test]
+    fn owned_into_iter_drains_from_both_ends() {
+        let list: DoublyLinkedList<i32> = (0..5).collect();
+        let mut it = list.into_iter();
+
+        assert_eq!(it.len(), 5);
+        assert_eq!(it.next(), Some(0));
+        assert_eq!(it.next_back(), Some(4));
+        assert_eq!(it.len(), 3);
+        assert_eq!(it.collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn borrowed_iterators_are_double_ended_and_exact_size() {
+        let list: DoublyLinkedList<i32> = (0..6).collect();
+
+        let mut fwd = list.iter_forward();
+        assert_eq!(fwd.len(), 6);
+        assert_eq!(fwd.next_back(), Some(5));
+        assert_eq!(fwd.next(), Some(0));
+        assert_eq!(fwd.len(), 4);
+        assert_eq!(fwd.rev().collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+
+        let mut bwd = list.iter_backward();
+        assert_eq!(bwd.next_back(), Some(0));
+        assert_eq!(bwd.collect::<Vec<_>>(), vec![5, 4, 3, 2, 1]);
+
+        // The two ends never cross, even when alternating.
+        let mut it = list.iter_forward();
+        let mut seen = Vec::new();
+        while let (Some(a), b) = (it.next(), it.next_back()) {
+            seen.push(a);
+            seen.extend(b);
+        }
+        seen.sort();
+        assert_eq!(seen, (0..6).collect::<Vec<_>>());
+
+        let mut total = 0;
+        for v in &list {
+            total += v;
+        }
+        assert_eq!(total, 15);
+    }
+
+    #[test]
+    fn dropping_ten_million_nodes_does_not_overflow_the_stack() {
+        let mut list = DoublyLinkedList::new();
+        for i in 0..10_000_000u32 {
+            list.push_back(i as u8);
+        }
+        assert_eq!(list.len(), 10_000_000);
+        drop(list);
+    }
+
+    #[test]
+    fn drop_releases_every_payload() {
+        let payload = Rc::new(());
+        let mut list = DoublyLinkedList::new();
+        for _ in 0..1000 {
+            list.push_back(payload.clone());
+        }
+        list.cursor_front_mut().move_next();
+        assert_eq!(Rc::strong_count(&payload), 1001);
+
+        drop(list);
+        assert_eq!(Rc::strong_count(&payload), 1);
+    }
+
+    #[test]
+    fn get_and_get_mut_walk_from_either_end() {
+        let mut list: DoublyLinkedList<i32> = (0..9).collect();
+
+        for i in 0..9 {
+            assert_eq!(list.get(i), Ok(i as i32));
+        }
+        assert_eq!(
+            list.get(9),
+            Err(ListError::IndexOutOfBounds { index: 9, len: 9 })
+        );
+
+        assert_eq!(list.get_mut(7, |v| std::mem::replace(v, 70)), Ok(7));
+        assert_eq!(list.get(7), Ok(70));
+        assert!(list.get_mut(100, |_| ()).is_err());
+        assert!(list.check_invariants());
+    }
+
+    #[test]
+    fn insert_at_and_remove_at_cover_ends_and_middle() {
+        let mut list = DoublyLinkedList::new();
+        list.insert_at(0, 'b').unwrap(); // [b]
+        list.insert_at(0, 'a').unwrap(); // [a,b]
+        list.insert_at(2, 'd').unwrap(); // [a,b,d]
+        list.insert_at(2, 'c').unwrap(); // [a,b,c,d]
+        assert_eq!(
+            list.insert_at(5, 'x'),
+            Err(ListError::IndexOutOfBounds { index: 5, len: 4 })
+        );
+        assert!(list.check_invariants());
+        assert_eq!(list.iter_forward().collect::<String>(), "abcd");
+
+        assert_eq!(list.remove_at(3), Ok('d'));
+        assert_eq!(list.remove_at(1), Ok('b'));
+        assert_eq!(list.remove_at(0), Ok('a'));
+        assert_eq!(
+            list.remove_at(1),
+            Err(ListError::IndexOutOfBounds { index: 1, len: 1 })
+        );
+        assert!(list.check_invariants());
+        assert_eq!(list.iter_forward().collect::<String>(), "c");
+    }
+
+    #[test]
+    fn swap_exchanges_values_only() {
+        let mut list: DoublyLinkedList<i32> = (0..5).collect();
+        list.swap(0, 4).unwrap();
+        list.swap(1, 1).unwrap();
+        list.swap(3, 1).unwrap();
+        assert_eq!(
+            list.swap(0, 5),
+            Err(ListError::IndexOutOfBounds { index: 5, len: 5 })
+        );
+
+        assert!(list.check_invariants());
+        assert_eq!(list.iter_forward().collect::<Vec<_>>(), vec![4, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn truncate_keeps_prefix() {
+        let mut list: DoublyLinkedList<i32> = (0..6).collect();
+        list.truncate(10);
+        assert_eq!(list.len(), 6);
+
+        list.truncate(2);
+        assert!(list.check_invariants());
+        assert_eq!(list.iter_backward().collect::<Vec<_>>(), vec![1, 0]);
+
+        list.truncate(0);
+        assert!(list.is_empty());
+        assert!(list.check_invariants());
+    }
 }
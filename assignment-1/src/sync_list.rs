@@ -0,0 +1,1203 @@
+// =======================
+// Thread-safe Doubly Linked List
+// =======================
+//
+// This is the `Send + Sync` counterpart to `safe_list.rs`. The node layout
+// and the public API are the same, but the single-threaded smart pointers
+// are swapped for their thread-safe equivalents:
+//
+//      - `Arc<Mutex<Node>>` for forward (`next`) links
+//      - `sync::Weak<Mutex<Node>>` for backward (`prev`) links
+//
+// ---------------------------------
+// Locking rules
+// ---------------------------------
+//
+// 1) head/tail/len live in `Inner`, behind a single `RwLock`.
+//      - Operations that only look (len, get, iterators, check_invariants)
+//        take the read lock and may run in parallel.
+//      - Operations that rewire links take the write lock.
+// 2) A node's `Mutex` is only locked while the list lock is held.
+// 3) Two node locks are only held at once under the WRITE lock, where no
+//    other thread can be holding any node lock. Readers lock one node at a
+//    time. Together these rule out deadlock.
+//
+// Every method takes `&self`, so the list can be shared between threads as
+// `Arc<DoublyLinkedList<T>>`. It is `Send + Sync` whenever `T: Send`.
+//
+// A poisoned lock (another thread panicked while holding it) is recovered
+// rather than propagated: no code in this file panics mid-rewire, so the
+// data behind the lock is still consistent.
+//
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, DerefMut};
+use std::sync::{
+    Arc, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, Weak,
+};
+
+use crate::error::ListError;
+use crate::teardown::drop_chain;
+
+/// A link to another node in the list.
+type Link<T> = Option<Arc<Mutex<Node<T>>>>;
+
+/// A node in the doubly linked list.
+///
+/// Same shape as `safe_list::Node`: `next` owns, `prev` is weak so the
+/// list has no reference cycles.
+struct Node<T> {
+    value: T,
+    prev: Option<Weak<Mutex<Node<T>>>>,
+    next: Link<T>,
+}
+
+/// Lock a node, recovering the guard if the mutex is poisoned.
+fn lock<T>(node: &Mutex<Node<T>>) -> MutexGuard<'_, Node<T>> {
+    node.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<T> Node<T> {
+    /// Create a new unlinked node containing `value`.
+    fn new(value: T) -> Arc<Mutex<Self>> {
+        Arc::new(Mutex::new(Node {
+            value,
+            prev: None,
+            next: None,
+        }))
+    }
+
+    /// Return the next node in the list.
+    fn next(&self) -> Link<T> {
+        self.next.clone()
+    }
+
+    /// Return the previous node in the list.
+    fn prev(&self) -> Link<T> {
+        self.prev.as_ref().and_then(|w| w.upgrade())
+    }
+
+    /// Move the value out of a node that has been fully unlinked.
+    ///
+    /// Once a node is detached from its neighbours (and the list's
+    /// head/tail), the caller holds the only strong reference to it.
+    fn into_value(node: Arc<Mutex<Self>>) -> T {
+        match Arc::try_unwrap(node) {
+            Ok(mutex) => {
+                mutex
+                    .into_inner()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .value
+            }
+            Err(_) => unreachable!("unlinked node is still shared"),
+        }
+    }
+}
+
+/// The lock-protected part of the list.
+///
+/// INVARIANTS: same as `safe_list::DoublyLinkedList`.
+struct Inner<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+}
+
+impl<T> Inner<T> {
+    fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+        }
+    }
+
+    fn push_front(&mut self, value: T) {
+        let new_node = Node::new(value);
+
+        match self.head.take() {
+            None => {
+                self.tail = Some(new_node.clone());
+            }
+            Some(old_head) => {
+                lock(&old_head).prev = Some(Arc::downgrade(&new_node));
+                lock(&new_node).next = Some(old_head);
+            }
+        }
+
+        self.head = Some(new_node);
+        self.len += 1;
+    }
+
+    fn push_back(&mut self, value: T) {
+        let new_node = Node::new(value);
+
+        match self.tail.take() {
+            None => {
+                self.head = Some(new_node.clone());
+            }
+            Some(old_tail) => {
+                lock(&new_node).prev = Some(Arc::downgrade(&old_tail));
+                lock(&old_tail).next = Some(new_node.clone());
+            }
+        }
+
+        self.tail = Some(new_node);
+        self.len += 1;
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        let old_head = self.head.take()?;
+
+        let next = lock(&old_head).next.take();
+        match next {
+            None => {
+                self.tail = None;
+            }
+            Some(new_head) => {
+                lock(&new_head).prev = None;
+                self.head = Some(new_head);
+            }
+        }
+
+        self.len -= 1;
+
+        Some(Node::into_value(old_head))
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        let old_tail = self.tail.take()?;
+
+        let prev = lock(&old_tail).prev.take().and_then(|w| w.upgrade());
+        match prev {
+            None => {
+                self.head = None;
+            }
+            Some(new_tail) => {
+                lock(&new_tail).next = None;
+                self.tail = Some(new_tail);
+            }
+        }
+
+        self.len -= 1;
+
+        Some(Node::into_value(old_tail))
+    }
+
+    /// Walk the node handles from head to tail.
+    fn nodes(&self) -> impl Iterator<Item = Arc<Mutex<Node<T>>>> + '_ {
+        std::iter::successors(self.head.clone(), |node| lock(node).next()).take(self.len)
+    }
+
+    /// Return the node at `index`, walking from whichever end is closer.
+    fn node_at(&self, index: usize) -> Result<Arc<Mutex<Node<T>>>, ListError> {
+        let out_of_bounds = ListError::IndexOutOfBounds {
+            index,
+            len: self.len,
+        };
+        if index >= self.len {
+            return Err(out_of_bounds);
+        }
+
+        let node = if index < self.len / 2 {
+            self.nodes().nth(index)
+        } else {
+            std::iter::successors(self.tail.clone(), |node| lock(node).prev())
+                .nth(self.len - 1 - index)
+        };
+        node.ok_or(out_of_bounds)
+    }
+
+    fn check_invariants(&self) -> bool {
+        if self.len == 0 {
+            return self.head.is_none() && self.tail.is_none();
+        }
+        let (Some(head), Some(tail)) = (&self.head, &self.tail) else {
+            return false;
+        };
+        // head.prev must be None (lock one node at a time: head may be tail)
+        if lock(head).prev.is_some() {
+            return false;
+        }
+        // tail.next must be None
+        if lock(tail).next.is_some() {
+            return false;
+        }
+
+        // Traverse forward, count, and verify back-links
+        let mut count = 0usize;
+        let mut cur = self.head.clone();
+        let mut prev: Link<T> = None;
+
+        while let Some(node_arc) = cur {
+            count += 1;
+            if count > self.len + 1 {
+                // cycle guard
+                return false;
+            }
+
+            let node = lock(&node_arc);
+            let back_link_ok = match (&prev, node.prev()) {
+                (None, None) => node.prev.is_none(),
+                (Some(expected), Some(actual)) => Arc::ptr_eq(expected, &actual),
+                _ => false,
+            };
+            if !back_link_ok {
+                return false;
+            }
+
+            cur = node.next();
+            drop(node);
+            prev = Some(node_arc);
+        }
+
+        count == self.len && prev.is_some_and(|last| Arc::ptr_eq(&last, tail))
+    }
+}
+
+/// A thread-safe doubly linked list.
+///
+/// Shares the API of `safe_list::DoublyLinkedList`, except that every
+/// method takes `&self` and anything that would hand out a borrow of a
+/// value (`peek_*`, `get`) returns a clone instead, since the borrow could
+/// not outlive the list lock.
+pub struct DoublyLinkedList<T> {
+    inner: RwLock<Inner<T>>,
+}
+
+impl<T> DoublyLinkedList<T> {
+    /// Create an empty list.
+    pub fn new() -> Self {
+        Self {
+            inner: RwLock::new(Inner::new()),
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Inner<T>> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Inner<T>> {
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Return the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.read().len
+    }
+
+    /// Return true if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Insert a value at the front of the list.
+    pub fn push_front(&self, value: T) {
+        self.write().push_front(value);
+    }
+
+    /// Insert a value at the back of the list.
+    pub fn push_back(&self, value: T) {
+        self.write().push_back(value);
+    }
+
+    /// Remove and return the value at the front of the list.
+    pub fn pop_front(&self) -> Option<T> {
+        self.write().pop_front()
+    }
+
+    /// Remove and return the value at the back of the list.
+    pub fn pop_back(&self) -> Option<T> {
+        self.write().pop_back()
+    }
+
+    /// Return a clone of the value at the front of the list.
+    pub fn peek_front(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        let inner = self.read();
+        inner.head.as_ref().map(|node| lock(node).value.clone())
+    }
+
+    /// Return a clone of the value at the back of the list.
+    pub fn peek_back(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        let inner = self.read();
+        inner.tail.as_ref().map(|node| lock(node).value.clone())
+    }
+
+    /// Return a clone of the value at `index`.
+    pub fn get(&self, index: usize) -> Result<T, ListError>
+    where
+        T: Clone,
+    {
+        let inner = self.read();
+        let node = inner.node_at(index)?;
+        let value = lock(&node).value.clone();
+        Ok(value)
+    }
+
+    /// Run `f` on the value at `index` and return its result.
+    ///
+    /// `f` runs with the list locked, so keep it short.
+    pub fn get_mut<R>(&self, index: usize, f: impl FnOnce(&mut T) -> R) -> Result<R, ListError> {
+        let inner = self.write();
+        let node = inner.node_at(index)?;
+        let out = f(&mut lock(&node).value);
+        Ok(out)
+    }
+
+    /// Insert `value` so that it ends up at position `index`.
+    ///
+    /// `index == len` appends; anything larger is an error.
+    pub fn insert_at(&self, index: usize, value: T) -> Result<(), ListError> {
+        let mut inner = self.write();
+        if index == inner.len {
+            inner.push_back(value);
+            return Ok(());
+        }
+
+        CursorMut::at(inner, index)?.insert_before(value);
+        Ok(())
+    }
+
+    /// Remove and return the value at `index`.
+    pub fn remove_at(&self, index: usize) -> Result<T, ListError> {
+        let inner = self.write();
+        let len = inner.len;
+        CursorMut::at(inner, index)?
+            .remove_current()
+            .ok_or(ListError::IndexOutOfBounds { index, len })
+    }
+
+    /// Swap the values at positions `i` and `j`. The nodes stay in place.
+    pub fn swap(&self, i: usize, j: usize) -> Result<(), ListError> {
+        let inner = self.write();
+        let a = inner.node_at(i)?;
+        let b = inner.node_at(j)?;
+        if i == j {
+            return Ok(());
+        }
+
+        // Two node locks at once: allowed, we hold the write lock.
+        std::mem::swap(&mut lock(&a).value, &mut lock(&b).value);
+        Ok(())
+    }
+
+    /// Keep the first `len` elements and drop the rest.
+    ///
+    /// Does nothing if the list is already `len` elements or shorter.
+    pub fn truncate(&self, len: usize) {
+        let mut inner = self.write();
+        if len >= inner.len {
+            return;
+        }
+        if len == 0 {
+            let rest = std::mem::replace(&mut *inner, Inner::new());
+            drop(inner);
+            drop(DoublyLinkedList {
+                inner: RwLock::new(rest),
+            });
+            return;
+        }
+
+        if let Ok(mut cursor) = CursorMut::at(inner, len - 1) {
+            drop(cursor.split_after());
+        }
+    }
+
+    /// Iterate from head to tail.
+    ///
+    /// The iterator holds the list's read lock until it is dropped, so it
+    /// sees a consistent snapshot and writers wait for it.
+    pub fn iter_forward(&self) -> IterForward<'_, T> {
+        let inner = self.read();
+        IterForward {
+            front: inner.head.clone(),
+            back: inner.tail.clone(),
+            remaining: inner.len,
+            _guard: inner,
+        }
+    }
+
+    /// Iterate from tail to head.
+    ///
+    /// Holds the list's read lock, like `iter_forward`.
+    pub fn iter_backward(&self) -> IterBackward<'_, T> {
+        let inner = self.read();
+        IterBackward {
+            front: inner.head.clone(),
+            back: inner.tail.clone(),
+            remaining: inner.len,
+            _guard: inner,
+        }
+    }
+
+    /// Return a cursor positioned at the front element.
+    ///
+    /// The cursor holds the list's write lock until it is dropped.
+    pub fn cursor_front_mut(&self) -> CursorMut<'_, T> {
+        let inner = self.write();
+        CursorMut {
+            current: inner.head.clone(),
+            index: 0,
+            list: inner,
+        }
+    }
+
+    /// Return a cursor positioned at the back element.
+    ///
+    /// The cursor holds the list's write lock until it is dropped.
+    pub fn cursor_back_mut(&self) -> CursorMut<'_, T> {
+        let inner = self.write();
+        CursorMut {
+            current: inner.tail.clone(),
+            index: inner.len.saturating_sub(1),
+            list: inner,
+        }
+    }
+
+    /// Return a cursor positioned at the element at `index`.
+    ///
+    /// The cursor holds the list's write lock until it is dropped.
+    pub fn cursor_at_mut(&self, index: usize) -> Result<CursorMut<'_, T>, ListError> {
+        CursorMut::at(self.write(), index)
+    }
+
+    /// Check all list invariants.
+    ///
+    /// Only meaningful at quiescence in the sense that it checks one
+    /// consistent snapshot: it holds the read lock for the whole walk.
+    pub fn check_invariants(&self) -> bool {
+        self.read().check_invariants()
+    }
+}
+
+impl<T> Drop for DoublyLinkedList<T> {
+    /// Free the nodes front to back in a loop, like `safe_list`.
+    fn drop(&mut self) {
+        let inner = self.inner.get_mut().unwrap_or_else(PoisonError::into_inner);
+        inner.tail = None;
+        inner.len = 0;
+
+        drop_chain(inner.head.take(), |node| match Arc::try_unwrap(node) {
+            Ok(mutex) => {
+                mutex
+                    .into_inner()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .next
+            }
+            Err(_) => None,
+        });
+    }
+}
+
+impl<T> Default for DoublyLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DoublyLinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.read();
+        let mut list = f.debug_list();
+        for node in inner.nodes() {
+            list.entry(&lock(&node).value);
+        }
+        list.finish()
+    }
+}
+
+impl<T: Clone> Clone for DoublyLinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter_forward().collect()
+    }
+}
+
+impl<T: PartialEq> PartialEq for DoublyLinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        if std::ptr::eq(self, other) {
+            return true;
+        }
+
+        // Comparing holds one node lock from each list at once, so rule 3
+        // needs both WRITE locks. Take them in address order so that `a == b`
+        // and `b == a` running concurrently cannot deadlock on each other.
+        let (first, second) = if (self as *const Self) < (other as *const Self) {
+            (self.write(), other.write())
+        } else {
+            let second = other.write();
+            (self.write(), second)
+        };
+
+        first.len == second.len
+            && first
+                .nodes()
+                .zip(second.nodes())
+                .all(|(a, b)| lock(&a).value == lock(&b).value)
+    }
+}
+
+impl<T: Eq> Eq for DoublyLinkedList<T> {}
+
+impl<T: Hash> Hash for DoublyLinkedList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let inner = self.read();
+        inner.len.hash(state);
+        for node in inner.nodes() {
+            lock(&node).value.hash(state);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for DoublyLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let list = Self::new();
+        {
+            let mut inner = list.write();
+            for value in iter {
+                inner.push_back(value);
+            }
+        }
+        list
+    }
+}
+
+impl<T> Extend<T> for DoublyLinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let inner = self.inner.get_mut().unwrap_or_else(PoisonError::into_inner);
+        for value in iter {
+            inner.push_back(value);
+        }
+    }
+}
+
+impl<T> IntoIterator for DoublyLinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    /// Consume the list, yielding its values from head to tail.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T: Clone> IntoIterator for &'a DoublyLinkedList<T> {
+    type Item = T;
+    type IntoIter = IterForward<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_forward()
+    }
+}
+
+/// Exclusive access to the value under a `CursorMut`.
+///
+/// Holds the node's lock; derefs to the value.
+pub struct ValueGuard<'a, T> {
+    node: MutexGuard<'a, Node<T>>,
+}
+
+impl<T> Deref for ValueGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.node.value
+    }
+}
+
+impl<T> DerefMut for ValueGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.node.value
+    }
+}
+
+/// A cursor over a `DoublyLinkedList` that can edit the list in place.
+///
+/// Behaves exactly like `safe_list::CursorMut` (including the "ghost"
+/// position between tail and head). It holds the list's write lock for its
+/// whole lifetime, so a sequence of cursor edits is atomic with respect to
+/// other threads.
+pub struct CursorMut<'a, T> {
+    list: RwLockWriteGuard<'a, Inner<T>>,
+    current: Link<T>,
+    index: usize,
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    /// Position a cursor on `index` of an already write-locked list.
+    fn at(list: RwLockWriteGuard<'a, Inner<T>>, index: usize) -> Result<Self, ListError> {
+        let node = list.node_at(index)?;
+        Ok(CursorMut {
+            list,
+            current: Some(node),
+            index,
+        })
+    }
+}
+
+impl<T> CursorMut<'_, T> {
+    /// Return the index of the current element, or `None` on the ghost.
+    pub fn index(&self) -> Option<usize> {
+        self.current.as_ref().map(|_| self.index)
+    }
+
+    /// Return a clone of the current element, or `None` on the ghost.
+    pub fn current(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.current.as_ref().map(|node| lock(node).value.clone())
+    }
+
+    /// Lock and mutably borrow the current element, or `None` on the ghost.
+    ///
+    /// The guard borrows the cursor mutably, so only one can exist at a time
+    /// and the node's lock can never be taken twice.
+    pub fn current_mut(&mut self) -> Option<ValueGuard<'_, T>> {
+        self.current
+            .as_ref()
+            .map(|node| ValueGuard { node: lock(node) })
+    }
+
+    /// Move to the next element (towards the tail).
+    pub fn move_next(&mut self) {
+        match self.current.take() {
+            Some(node) => {
+                self.current = lock(&node).next();
+                self.index += 1;
+            }
+            None => {
+                self.current = self.list.head.clone();
+                self.index = 0;
+            }
+        }
+    }
+
+    /// Move to the previous element (towards the head).
+    pub fn move_prev(&mut self) {
+        match self.current.take() {
+            Some(node) => {
+                self.current = lock(&node).prev();
+                self.index = match self.index {
+                    0 => self.list.len,
+                    i => i - 1,
+                };
+            }
+            None => {
+                self.current = self.list.tail.clone();
+                self.index = self.list.len.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Insert `value` immediately before the current element.
+    ///
+    /// On the ghost, this appends to the back of the list.
+    pub fn insert_before(&mut self, value: T) {
+        let Some(cur) = self.current.clone() else {
+            self.list.push_back(value);
+            self.index += 1;
+            return;
+        };
+
+        let new_node = Node::new(value);
+        let prev = lock(&cur).prev();
+
+        lock(&new_node).next = Some(cur.clone());
+        lock(&cur).prev = Some(Arc::downgrade(&new_node));
+
+        match prev {
+            None => {
+                self.list.head = Some(new_node);
+            }
+            Some(prev) => {
+                lock(&new_node).prev = Some(Arc::downgrade(&prev));
+                lock(&prev).next = Some(new_node);
+            }
+        }
+
+        self.list.len += 1;
+        self.index += 1;
+    }
+
+    /// Insert `value` immediately after the current element.
+    ///
+    /// On the ghost, this prepends to the front of the list.
+    pub fn insert_after(&mut self, value: T) {
+        let Some(cur) = self.current.clone() else {
+            self.list.push_front(value);
+            self.index += 1;
+            return;
+        };
+
+        let new_node = Node::new(value);
+        let next = lock(&cur).next.take();
+
+        lock(&new_node).prev = Some(Arc::downgrade(&cur));
+
+        match next {
+            None => {
+                self.list.tail = Some(new_node.clone());
+            }
+            Some(next) => {
+                lock(&next).prev = Some(Arc::downgrade(&new_node));
+                lock(&new_node).next = Some(next);
+            }
+        }
+
+        lock(&cur).next = Some(new_node);
+        self.list.len += 1;
+    }
+
+    /// Remove the current element and return it.
+    ///
+    /// The cursor moves to the element that followed the removed one
+    /// (or the ghost, if the tail was removed). Returns `None` on the ghost.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let cur = self.current.take()?;
+
+        let (prev, next) = {
+            let mut node = lock(&cur);
+            (node.prev.take().and_then(|w| w.upgrade()), node.next.take())
+        };
+
+        match &prev {
+            None => self.list.head = next.clone(),
+            Some(prev) => lock(prev).next = next.clone(),
+        }
+        match &next {
+            None => self.list.tail = prev.clone(),
+            Some(next) => lock(next).prev = prev.as_ref().map(Arc::downgrade),
+        }
+
+        self.list.len -= 1;
+        self.current = next;
+
+        Some(Node::into_value(cur))
+    }
+
+    /// Split the list after the current element.
+    ///
+    /// Everything after the cursor is moved into a new list, which is
+    /// returned. On the ghost, the whole list is moved out.
+    pub fn split_after(&mut self) -> DoublyLinkedList<T> {
+        let Some(cur) = self.current.clone() else {
+            self.index = 0;
+            return DoublyLinkedList {
+                inner: RwLock::new(std::mem::replace(&mut *self.list, Inner::new())),
+            };
+        };
+
+        let Some(next) = lock(&cur).next.take() else {
+            return DoublyLinkedList::new();
+        };
+        lock(&next).prev = None;
+
+        let split_len = self.list.len - self.index - 1;
+        let split_tail = self.list.tail.replace(cur);
+        self.list.len = self.index + 1;
+
+        DoublyLinkedList {
+            inner: RwLock::new(Inner {
+                head: Some(next),
+                tail: split_tail,
+                len: split_len,
+            }),
+        }
+    }
+
+    /// Move every element of `other` into this list, right after the
+    /// current element. On the ghost, `other` is spliced in at the front.
+    pub fn splice_after(&mut self, mut other: DoublyLinkedList<T>) {
+        let other = other
+            .inner
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        let (Some(other_head), Some(other_tail)) = (other.head.take(), other.tail.take()) else {
+            return;
+        };
+        let other_len = std::mem::take(&mut other.len);
+
+        let next = match &self.current {
+            Some(cur) => {
+                let next = lock(cur).next.replace(other_head.clone());
+                lock(&other_head).prev = Some(Arc::downgrade(cur));
+                next
+            }
+            None => {
+                self.index += other_len;
+                self.list.head.replace(other_head)
+            }
+        };
+
+        match next {
+            None => {
+                self.list.tail = Some(other_tail);
+            }
+            Some(next) => {
+                lock(&next).prev = Some(Arc::downgrade(&other_tail));
+                lock(&other_tail).next = Some(next);
+            }
+        }
+
+        self.list.len += other_len;
+    }
+}
+
+/// Yield the value at `cur` and advance `cur` towards the tail.
+fn step_forward<T: Clone>(cur: &mut Link<T>) -> Option<T> {
+    let node_arc = cur.take()?;
+    let node = lock(&node_arc);
+
+    *cur = node.next();
+    Some(node.value.clone())
+}
+
+/// Yield the value at `cur` and advance `cur` towards the head.
+fn step_backward<T: Clone>(cur: &mut Link<T>) -> Option<T> {
+    let node_arc = cur.take()?;
+    let node = lock(&node_arc);
+
+    *cur = node.prev();
+    Some(node.value.clone())
+}
+
+/// Forward iterator: head → tail
+///
+/// Holds the list's read lock while alive.
+pub struct IterForward<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    remaining: usize,
+    _guard: RwLockReadGuard<'a, Inner<T>>,
+}
+
+impl<T: Clone> Iterator for IterForward<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let value = step_forward(&mut self.front)?;
+        self.remaining -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: Clone> DoubleEndedIterator for IterForward<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let value = step_backward(&mut self.back)?;
+        self.remaining -= 1;
+        Some(value)
+    }
+}
+
+impl<T: Clone> ExactSizeIterator for IterForward<'_, T> {}
+
+/// Backward iterator: tail → head
+///
+/// Holds the list's read lock while alive.
+pub struct IterBackward<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    remaining: usize,
+    _guard: RwLockReadGuard<'a, Inner<T>>,
+}
+
+impl<T: Clone> Iterator for IterBackward<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let value = step_backward(&mut self.back)?;
+        self.remaining -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: Clone> DoubleEndedIterator for IterBackward<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let value = step_forward(&mut self.front)?;
+        self.remaining -= 1;
+        Some(value)
+    }
+}
+
+impl<T: Clone> ExactSizeIterator for IterBackward<'_, T> {}
+
+/// Owning iterator: drains the list from head to tail.
+pub struct IntoIter<T> {
+    list: DoublyLinkedList<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.list.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn assert_send_sync<S: Send + Sync>() {}
+
+    #[test]
+    fn list_is_send_and_sync_for_send_payloads() {
+        assert_send_sync::<DoublyLinkedList<i32>>();
+        assert_send_sync::<DoublyLinkedList<String>>();
+        assert_send_sync::<DoublyLinkedList<std::cell::Cell<u8>>>();
+    }
+
+    #[test]
+    fn push_pop_and_iterate_from_both_ends() {
+        let list = DoublyLinkedList::new();
+        assert!(list.is_empty());
+        assert_eq!(list.pop_front(), None::<i32>);
+        assert!(list.check_invariants());
+
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.peek_front(), Some(1));
+        assert_eq!(list.peek_back(), Some(3));
+        assert_eq!(list.iter_forward().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(list.iter_backward().collect::<Vec<_>>(), vec![3, 2, 1]);
+        assert_eq!(list.iter_forward().rev().collect::<Vec<_>>(), vec![3, 2, 1]);
+        assert!(list.check_invariants());
+
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_back(), Some(3));
+        assert!(list.check_invariants()); // head == tail
+        assert_eq!(list.pop_back(), Some(2));
+        assert!(list.is_empty());
+        assert!(list.check_invariants());
+    }
+
+    #[test]
+    fn positional_ops_match_safe_list() {
+        let list: DoublyLinkedList<i32> = (0..6).collect();
+
+        assert_eq!(list.get(4), Ok(4));
+        assert_eq!(
+            list.get(6),
+            Err(ListError::IndexOutOfBounds { index: 6, len: 6 })
+        );
+        list.get_mut(1, |v| *v = 10).unwrap();
+        list.insert_at(6, 6).unwrap();
+        list.insert_at(3, 30).unwrap();
+        assert_eq!(list.remove_at(0), Ok(0));
+        list.swap(0, 6).unwrap();
+        assert!(list.check_invariants());
+        assert_eq!(
+            list.iter_forward().collect::<Vec<_>>(),
+            vec![6, 2, 30, 3, 4, 5, 10]
+        );
+
+        list.truncate(2);
+        assert_eq!(list.iter_forward().collect::<Vec<_>>(), vec![6, 2]);
+        list.truncate(0);
+        assert!(list.is_empty());
+        assert!(list.check_invariants());
+    }
+
+    #[test]
+    fn cursor_edits_split_and_splice() {
+        let list: DoublyLinkedList<i32> = (0..5).collect();
+        let rest = {
+            let mut cur = list.cursor_front_mut();
+            cur.move_next();
+            *cur.current_mut().unwrap() = 100;
+            cur.insert_after(150);
+            cur.insert_before(50);
+            assert_eq!(cur.index(), Some(2));
+            cur.move_next();
+            cur.move_next();
+            assert_eq!(cur.remove_current(), Some(2));
+            cur.split_after()
+        };
+        assert!(list.check_invariants());
+        assert!(rest.check_invariants());
+        assert_eq!(
+            list.iter_forward().collect::<Vec<_>>(),
+            vec![0, 50, 100, 150, 3]
+        );
+        assert_eq!(rest.iter_forward().collect::<Vec<_>>(), vec![4]);
+
+        {
+            let mut cur = list.cursor_back_mut();
+            cur.move_next(); // ghost
+            cur.splice_after(rest);
+            assert_eq!(cur.current(), None);
+            cur.move_next();
+            let seen = cur.current();
+            assert_eq!(seen, Some(4));
+            // `current` hands out a copy: changing the node and moving on
+            // leaves it as it was.
+            *cur.current_mut().unwrap() *= 10;
+            cur.move_next();
+            assert_eq!(seen, Some(4));
+            assert_eq!(cur.current(), Some(0));
+        }
+        assert!(list.check_invariants());
+        assert_eq!(
+            list.iter_forward().collect::<Vec<_>>(),
+            vec![40, 0, 50, 100, 150, 3]
+        );
+    }
+
+    #[test]
+    fn std_traits() {
+        let mut a: DoublyLinkedList<i32> = (1..=3).collect();
+        a.extend([4]);
+        let b = a.clone();
+
+        assert_eq!(a, b);
+        assert_eq!(a, a);
+        assert_eq!(format!("{:?}", b), "[1, 2, 3, 4]");
+        assert_eq!(b.into_iter().rev().collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+        assert_eq!((&a).into_iter().len(), 4);
+    }
+
+    #[test]
+    fn concurrent_opposite_order_comparisons_do_not_deadlock() {
+        // Short lists so every comparison contends on the same node pairs.
+        let a: Arc<DoublyLinkedList<u32>> = Arc::new((0..4).collect());
+        let b: Arc<DoublyLinkedList<u32>> = Arc::new((0..4).collect());
+
+        let handles: Vec<_> = (0..8)
+            .map(|t| {
+                let (a, b) = (Arc::clone(&a), Arc::clone(&b));
+                thread::spawn(move || {
+                    for _ in 0..20_000 {
+                        if t % 2 == 0 {
+                            assert!(*a == *b);
+                        } else {
+                            assert!(*b == *a);
+                        }
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn concurrent_push_pop_stress_keeps_invariants() {
+        const THREADS: usize = 8;
+        const OPS: usize = 5_000;
+
+        let list = Arc::new(DoublyLinkedList::new());
+        let handles: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let list = Arc::clone(&list);
+                thread::spawn(move || {
+                    let mut popped = 0usize;
+                    for i in 0..OPS {
+                        let v = t * OPS + i;
+                        match i % 4 {
+                            0 => list.push_front(v),
+                            1 => list.push_back(v),
+                            2 => popped += list.pop_front().is_some() as usize,
+                            _ => popped += list.pop_back().is_some() as usize,
+                        }
+                        if i % 500 == 0 {
+                            // Readers run alongside writers.
+                            let n = list.iter_forward().count();
+                            assert!(n <= THREADS * OPS);
+                        }
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        let popped: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+        let pushed = THREADS * OPS / 2;
+
+        assert!(list.check_invariants());
+        assert_eq!(list.len(), pushed - popped);
+        assert_eq!(list.iter_backward().count(), list.len());
+    }
+
+    #[test]
+    fn concurrent_cursor_edits_are_atomic() {
+        let list = Arc::new(DoublyLinkedList::<u32>::new());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let list = Arc::clone(&list);
+                thread::spawn(move || {
+                    for _ in 0..1_000 {
+                        // Insert a matching pair under one lock.
+                        let mut cur = list.cursor_back_mut();
+                        cur.insert_after(1);
+                        cur.move_next();
+                        cur.insert_after(2);
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        assert!(list.check_invariants());
+        let values = list.iter_forward().collect::<Vec<_>>();
+        assert_eq!(values.len(), 8_000);
+        assert!(values.chunks(2).all(|pair| pair == [1, 2]));
+    }
+
+    #[test]
+    fn dropping_a_long_list_does_not_overflow_the_stack() {
+        let list: DoublyLinkedList<u8> = std::iter::repeat_n(0, 1_000_000).collect();
+        assert_eq!(list.len(), 1_000_000);
+        drop(list);
+    }
+}
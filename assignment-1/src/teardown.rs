@@ -0,0 +1,24 @@
+// =======================
+// Iterative Chain Teardown
+// =======================
+//
+// Dropping a chain of owned nodes (`Box` or `Rc` links) the default way is
+// recursive: dropping node 0 drops its `next`, which drops node 1's `next`,
+// and so on. Each level uses a stack frame, so a long enough chain overflows
+// the stack.
+//
+// `drop_chain` turns that recursion into a loop. The caller supplies an
+// `unlink` step that detaches one node's successor before the node itself
+// is dropped, so every drop sees an empty `next` and stays shallow.
+
+/// Drop a chain of nodes starting at `head`, one node per iteration.
+///
+/// `unlink` takes ownership of a node, detaches and returns its successor,
+/// and lets the node drop. Returning `None` ends the walk early, e.g. when
+/// the successor is still shared and must not be torn down here.
+pub fn drop_chain<N>(head: Option<N>, mut unlink: impl FnMut(N) -> Option<N>) {
+    let mut cur = head;
+    while let Some(node) = cur {
+        cur = unlink(node);
+    }
+}
@@ -4,8 +4,8 @@
 //
 // Purpose:
 // - This file implements a doubly linked list using raw pointers.
-// - List logic (rewiring prev/next, head/tail) is fully implemented.
-// - Your job is to correctly implement:
+// - List logic (rewiring prev/next, head/tail) is fully implemented, and
+//   so is the memory management underneath it:
 //     1) allocation
 //     2) deallocation
 //     3) cleanup in Drop
@@ -13,52 +13,118 @@
 // This file intentionally uses unsafe Rust to expose the risks of
 // manual memory management.
 //
+// The list is generic over its values, so freeing a node and dropping the
+// value it held are separate steps. Pops and removals move the value out,
+// give the node back and only then hand the value to the caller (or drop
+// it), so every value is dropped exactly once. The list is consistent
+// again before any value's destructor runs. If one of those destructors
+// panics during `clear` or `Drop`, the remaining values are still dropped
+// and every node is still freed.
+//
 // ---------------------------------
-// What you MUST implement
+// What is implemented here
 // ---------------------------------
 //
-// TODOs:
-//   - alloc_node
-//   - dealloc_node
-//   - Drop for DoublyLinkedList
+//   - alloc_node: takes a pooled node or a fresh one from the NodeAlloc
+//   - dealloc_node / release_node: pool the node or give it back
+//   - Drop for DoublyLinkedList: frees every node, then the pool
 //
-// Constraints:
-// - You must use std::alloc::{alloc, dealloc}
-// - You must use Layout::new::<Node>()
-// - Each node must be freed EXACTLY ONCE
+// Invariants the code keeps:
+// - All node memory goes through the list's NodeAlloc
+// - Always with Layout::new::<Node<T>>()
+// - Each node is freed EXACTLY ONCE
 // - No memory leaks
 // - No double frees
 //
+// Node memory comes from the list's allocator (`crate::node_alloc`), the
+// global one unless the list was made with `new_in`:
+//
+//   let arena = Bump::new();
+//   let mut list = DoublyLinkedList::new_in(&arena);
+//
+// Node pool: a list can keep up to `pool_limit` freed nodes on a free list
+// (linked through `next`) and reuse them for later pushes, so a queue that
+// pushes and pops millions of times stops paying for `alloc`/`dealloc` per
+// element. The limit is 0 (free at once) unless set with `with_capacity` or
+// `set_pool_limit`; `shrink_to_fit` gives the pooled nodes back.
+//
+// The tests stay small enough to run under Miri as they are, which checks
+// every pointer this file follows for use-after-free, leaks and aliasing:
+//
+//   rustup +nightly component add miri
+//   cargo +nightly miri test --lib unsafe_list::
+//
 
-use std::alloc::{alloc, dealloc, Layout};
-use std::ptr::null_mut;
+use std::alloc::{handle_alloc_error, Layout};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ptr::{self, null_mut, NonNull};
 
-/// The value stored in the list.
-///
-/// In the safe version, this becomes an enum.
-/// Here, we keep it as i64 to focus on memory safety.
-pub type Value = i64;
+use crate::error::ListError;
+use crate::node_alloc::{Global, NodeAlloc};
 
 #[repr(C)]
-struct Node {
-    value: Value,
-    prev: *mut Node,
-    next: *mut Node,
+struct Node<T> {
+    value: T,
+    prev: *mut Node<T>,
+    next: *mut Node<T>,
 }
 
-pub struct DoublyLinkedList {
-    head: *mut Node,
-    tail: *mut Node,
+pub struct DoublyLinkedList<T, A: NodeAlloc = Global> {
+    head: *mut Node<T>,
+    tail: *mut Node<T>,
     len: usize,
+    /// Freed nodes kept for reuse, linked through `next`.
+    pool: *mut Node<T>,
+    pool_len: usize,
+    /// Most nodes kept in `pool`; 0 frees every node at once.
+    pool_limit: usize,
+    alloc: A,
 }
 
-impl DoublyLinkedList {
+impl<T> DoublyLinkedList<T> {
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    /// An empty list with `capacity` nodes allocated up front and kept
+    /// for reuse (`pool_limit == capacity`).
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+}
+
+impl<T, A: NodeAlloc> DoublyLinkedList<T, A> {
+    /// An empty list whose nodes come from `alloc`.
+    pub fn new_in(alloc: A) -> Self {
         Self {
             head: null_mut(),
             tail: null_mut(),
             len: 0,
+            pool: null_mut(),
+            pool_len: 0,
+            pool_limit: 0,
+            alloc,
+        }
+    }
+
+    /// Like `with_capacity`, with nodes from `alloc`.
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        let mut list = Self::new_in(alloc);
+        list.pool_limit = capacity;
+        for _ in 0..capacity {
+            let n = list
+                .fresh_node()
+                .unwrap_or_else(|_| handle_alloc_error(Layout::new::<Node<T>>()));
+            unsafe { list.release_node(n) };
         }
+        list
+    }
+
+    /// The allocator the nodes come from.
+    pub fn allocator(&self) -> &A {
+        &self.alloc
     }
 
     pub fn len(&self) -> usize {
@@ -69,39 +135,129 @@ impl DoublyLinkedList {
         self.len == 0
     }
 
+    /// Nodes held: in the list plus pooled for reuse.
+    pub fn capacity(&self) -> usize {
+        self.len + self.pool_len
+    }
+
+    /// Freed nodes currently pooled for reuse.
+    pub fn pooled(&self) -> usize {
+        self.pool_len
+    }
+
+    /// Most freed nodes the list keeps for reuse.
+    pub fn pool_limit(&self) -> usize {
+        self.pool_limit
+    }
+
+    /// Keep up to `limit` freed nodes for reuse, freeing any pooled nodes
+    /// beyond it now.
+    pub fn set_pool_limit(&mut self, limit: usize) {
+        self.pool_limit = limit;
+        while self.pool_len > limit {
+            unsafe {
+                let n = self.pool;
+                self.pool = (*n).next;
+                self.pool_len -= 1;
+                self.dealloc_node(n);
+            }
+        }
+    }
+
+    /// Free every pooled node. The pool limit stays, so later pops fill
+    /// the pool again.
+    pub fn shrink_to_fit(&mut self) {
+        let limit = self.pool_limit;
+        self.set_pool_limit(0);
+        self.pool_limit = limit;
+    }
+
     // --------------------------------------------------
-    // TODO #1: Allocate and initialize a Node
+    // Node allocation
     // --------------------------------------------------
     //
-    // Requirements:
-    // - Allocate memory using alloc(Layout::new::<Node>())
-    // - Panic or abort if allocation fails
-    // - Initialize:
-    //     value
-    //     prev = null
-    //     next = null
-    // - Return a *mut Node
+    // `alloc_node` pops a node off the pool if there is one and only asks
+    // the NodeAlloc (`fresh_node`) otherwise. Either way the memory is
+    // written as a whole `Node { value, prev: null, next: null }` before
+    // the pointer is returned. A failed allocation comes back as
+    // `ListError::AllocFailed`: the try_* operations return it, the others
+    // abort with `handle_alloc_error`.
     //
-    unsafe fn alloc_node(value: Value) -> *mut Node {
-        todo!("Allocate Node using alloc and initialize its fields");
+    unsafe fn alloc_node(&mut self, value: T) -> Result<*mut Node<T>, ListError> {
+        let ptr = if self.pool.is_null() {
+            self.fresh_node()?
+        } else {
+            let n = self.pool;
+            self.pool = unsafe { (*n).next };
+            self.pool_len -= 1;
+            n
+        };
+
+        unsafe {
+            ptr.write(Node {
+                value,
+                prev: null_mut(),
+                next: null_mut(),
+            });
+        }
+        Ok(ptr)
+    }
+
+    /// Uninitialized memory for one node, straight from the allocator.
+    fn fresh_node(&self) -> Result<*mut Node<T>, ListError> {
+        let layout = Layout::new::<Node<T>>();
+        match self.alloc.allocate(layout) {
+            Ok(ptr) => Ok(ptr.cast::<Node<T>>().as_ptr()),
+            Err(_) => Err(ListError::AllocFailed(layout)),
+        }
     }
 
     // --------------------------------------------------
-    // TODO #2: Deallocate a Node
+    // Node deallocation
     // --------------------------------------------------
     //
-    // Requirements:
-    // - Free memory allocated by alloc_node
-    // - Use dealloc with the SAME Layout
-    // - Node must not be freed more than once
+    // Callers move the value out first; these only handle the memory.
+    // `release_node` keeps the node on the pool while it is under
+    // `pool_limit`, and `dealloc_node` hands it back to the same NodeAlloc
+    // with the same Layout it was allocated with. A node is unlinked
+    // before either is called, so nothing can reach it to free it twice.
     //
-    unsafe fn dealloc_node(node: *mut Node) {
-        todo!("Deallocate Node using dealloc with Layout::new::<Node>()");
+    unsafe fn dealloc_node(&self, node: *mut Node<T>) {
+        let node = NonNull::new(node).expect("dealloc_node: null node");
+        unsafe { self.alloc.deallocate(node.cast(), Layout::new::<Node<T>>()) };
     }
 
-    pub fn push_front(&mut self, value: Value) {
+    /// Done with `node`: pool it if there is room, free it otherwise.
+    unsafe fn release_node(&mut self, node: *mut Node<T>) {
+        if self.pool_len < self.pool_limit {
+            unsafe {
+                (*node).prev = null_mut();
+                (*node).next = self.pool;
+            }
+            self.pool = node;
+            self.pool_len += 1;
+        } else {
+            unsafe { self.dealloc_node(node) };
+        }
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        if let Err(ListError::AllocFailed(layout)) = self.try_push_front(value) {
+            handle_alloc_error(layout);
+        }
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        if let Err(ListError::AllocFailed(layout)) = self.try_push_back(value) {
+            handle_alloc_error(layout);
+        }
+    }
+
+    /// Like `push_front`, but a failed allocation is returned as
+    /// `ListError::AllocFailed` and leaves the list unchanged.
+    pub fn try_push_front(&mut self, value: T) -> Result<(), ListError> {
         unsafe {
-            let n = Self::alloc_node(value);
+            let n = self.alloc_node(value)?;
 
             if self.len == 0 {
                 self.head = n;
@@ -114,11 +270,14 @@ impl DoublyLinkedList {
 
             self.len += 1;
         }
+        Ok(())
     }
 
-    pub fn push_back(&mut self, value: Value) {
+    /// Like `push_back`, but a failed allocation is returned as
+    /// `ListError::AllocFailed` and leaves the list unchanged.
+    pub fn try_push_back(&mut self, value: T) -> Result<(), ListError> {
         unsafe {
-            let n = Self::alloc_node(value);
+            let n = self.alloc_node(value)?;
 
             if self.len == 0 {
                 self.head = n;
@@ -131,16 +290,17 @@ impl DoublyLinkedList {
 
             self.len += 1;
         }
+        Ok(())
     }
 
-    pub fn pop_front(&mut self) -> Option<Value> {
+    pub fn pop_front(&mut self) -> Option<T> {
         if self.len == 0 {
             return None;
         }
 
         unsafe {
             let n = self.head;
-            let out = (*n).value;
+            let out = ptr::read(&(*n).value);
 
             let next = (*n).next;
             if next.is_null() {
@@ -157,19 +317,19 @@ impl DoublyLinkedList {
             (*n).prev = null_mut();
             (*n).next = null_mut();
 
-            Self::dealloc_node(n);
+            self.release_node(n);
             Some(out)
         }
     }
 
-    pub fn pop_back(&mut self) -> Option<Value> {
+    pub fn pop_back(&mut self) -> Option<T> {
         if self.len == 0 {
             return None;
         }
 
         unsafe {
             let n = self.tail;
-            let out = (*n).value;
+            let out = ptr::read(&(*n).value);
 
             let prev = (*n).prev;
             if prev.is_null() {
@@ -185,30 +345,206 @@ impl DoublyLinkedList {
             (*n).prev = null_mut();
             (*n).next = null_mut();
 
-            Self::dealloc_node(n);
+            self.release_node(n);
             Some(out)
         }
     }
 
+    // --------------------------------------------------
+    // Positional operations
+    // --------------------------------------------------
+    //
+    // Every index is bounds-checked before any pointer is followed, and the
+    // walk starts from whichever end of the list is closer.
+
+    /// Return the node at `index`.
+    fn node_at(&self, index: usize) -> Result<*mut Node<T>, ListError> {
+        if index >= self.len {
+            return Err(ListError::IndexOutOfBounds {
+                index,
+                len: self.len,
+            });
+        }
+
+        unsafe {
+            let mut cur;
+            if index < self.len / 2 {
+                cur = self.head;
+                for _ in 0..index {
+                    cur = (*cur).next;
+                }
+            } else {
+                cur = self.tail;
+                for _ in 0..(self.len - 1 - index) {
+                    cur = (*cur).prev;
+                }
+            }
+            Ok(cur)
+        }
+    }
+
+    /// Borrow the value at `index`.
+    pub fn get(&self, index: usize) -> Result<&T, ListError> {
+        let n = self.node_at(index)?;
+        unsafe { Ok(&(*n).value) }
+    }
+
+    /// Mutably borrow the value at `index`.
+    pub fn get_mut(&mut self, index: usize) -> Result<&mut T, ListError> {
+        let n = self.node_at(index)?;
+        unsafe { Ok(&mut (*n).value) }
+    }
+
+    /// Insert `value` so that it ends up at position `index`.
+    ///
+    /// `index == len` appends; anything larger is an error.
+    pub fn insert_at(&mut self, index: usize, value: T) -> Result<(), ListError> {
+        match self.try_insert_at(index, value) {
+            Err(ListError::AllocFailed(layout)) => handle_alloc_error(layout),
+            result => result,
+        }
+    }
+
+    /// Like `insert_at`, but a failed allocation is returned as
+    /// `ListError::AllocFailed` and leaves the list unchanged.
+    pub fn try_insert_at(&mut self, index: usize, value: T) -> Result<(), ListError> {
+        if index == 0 {
+            return self.try_push_front(value);
+        }
+        if index == self.len {
+            return self.try_push_back(value);
+        }
+
+        // 0 < index < len: the new node goes between `prev` and `next`.
+        // Nothing is rewired until the node exists.
+        let next = self.node_at(index)?;
+        unsafe {
+            let prev = (*next).prev;
+            let n = self.alloc_node(value)?;
+
+            (*n).prev = prev;
+            (*n).next = next;
+            (*prev).next = n;
+            (*next).prev = n;
+        }
+
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Remove and return the value at `index`.
+    pub fn remove_at(&mut self, index: usize) -> Result<T, ListError> {
+        let n = self.node_at(index)?;
+
+        unsafe {
+            let prev = (*n).prev;
+            let next = (*n).next;
+
+            if prev.is_null() {
+                self.head = next;
+            } else {
+                (*prev).next = next;
+            }
+            if next.is_null() {
+                self.tail = prev;
+            } else {
+                (*next).prev = prev;
+            }
+
+            self.len -= 1;
+
+            let out = ptr::read(&(*n).value);
+            self.release_node(n);
+            Ok(out)
+        }
+    }
+
+    /// Swap the values at positions `i` and `j`. The nodes stay in place.
+    pub fn swap(&mut self, i: usize, j: usize) -> Result<(), ListError> {
+        let a = self.node_at(i)?;
+        let b = self.node_at(j)?;
+        if a != b {
+            unsafe { std::ptr::swap(&mut (*a).value, &mut (*b).value) };
+        }
+        Ok(())
+    }
+
+    /// Keep the first `len` elements and free the rest.
+    ///
+    /// Does nothing if the list is already `len` elements or shorter.
+    pub fn truncate(&mut self, len: usize) {
+        while self.len > len {
+            self.pop_back();
+        }
+    }
+
+    /// Remove and drop every value. Freed nodes go to the pool as usual.
+    ///
+    /// If a value's destructor panics, the values after it stay in the
+    /// list, which is still valid.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    /// The values head to tail, by reference.
+    fn values(&self) -> impl Iterator<Item = &T> {
+        let mut cur = self.head;
+        std::iter::from_fn(move || {
+            if cur.is_null() {
+                return None;
+            }
+            unsafe {
+                let v = &(*cur).value;
+                cur = (*cur).next;
+                Some(v)
+            }
+        })
+    }
+
     /// Forward iterator (head → tail)
-    pub fn iter_forward(&self) -> IterForward {
+    pub fn iter_forward(&self) -> IterForward<'_, T>
+    where
+        T: Clone,
+    {
         IterForward {
-            cur: self.head,
+            front: self.head,
+            back: self.tail,
             remaining: self.len,
+            _list: PhantomData,
         }
     }
 
     /// Backward iterator (tail → head)
-    pub fn iter_backward(&self) -> IterBackward {
+    pub fn iter_backward(&self) -> IterBackward<'_, T>
+    where
+        T: Clone,
+    {
         IterBackward {
-            cur: self.tail,
+            front: self.head,
+            back: self.tail,
             remaining: self.len,
+            _list: PhantomData,
         }
     }
 
     /// Optional invariant checker (useful for debugging)
     pub fn check_invariants(&self) -> bool {
         unsafe {
+            // The pool is a separate, null-terminated chain of `pool_len`
+            // nodes.
+            let mut pooled = 0usize;
+            let mut cur = self.pool;
+            while !cur.is_null() {
+                pooled += 1;
+                if pooled > self.pool_len {
+                    return false;
+                }
+                cur = (*cur).next;
+            }
+            if pooled != self.pool_len || pooled > self.pool_limit {
+                return false;
+            }
+
             if self.len == 0 {
                 return self.head.is_null() && self.tail.is_null();
             }
@@ -243,75 +579,259 @@ impl DoublyLinkedList {
     }
 }
 
+impl<T> Default for DoublyLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug, A: NodeAlloc> fmt::Debug for DoublyLinkedList<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.values()).finish()
+    }
+}
+
+impl<T: Clone, A: NodeAlloc + Clone> Clone for DoublyLinkedList<T, A> {
+    /// Copy the values into a new list sharing a clone of the allocator.
+    /// The copy has the same pool limit but starts with an empty pool.
+    fn clone(&self) -> Self {
+        let mut list = Self::new_in(self.alloc.clone());
+        list.pool_limit = self.pool_limit;
+        list.extend(self.values().cloned());
+        list
+    }
+}
+
+impl<T: PartialEq, A: NodeAlloc> PartialEq for DoublyLinkedList<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.values().eq(other.values())
+    }
+}
+
+impl<T: Eq, A: NodeAlloc> Eq for DoublyLinkedList<T, A> {}
+
+impl<T: Hash, A: NodeAlloc> Hash for DoublyLinkedList<T, A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for v in self.values() {
+            v.hash(state);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for DoublyLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T, A: NodeAlloc> Extend<T> for DoublyLinkedList<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for v in iter {
+            self.push_back(v);
+        }
+    }
+}
+
+impl<T, A: NodeAlloc> IntoIterator for DoublyLinkedList<T, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
+
+    /// Consume the list, yielding its values from head to tail.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T: Clone, A: NodeAlloc> IntoIterator for &'a DoublyLinkedList<T, A> {
+    type Item = T;
+    type IntoIter = IterForward<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_forward()
+    }
+}
+
 /// Forward iterator (unsafe list)
-pub struct IterForward {
-    cur: *mut Node,
+///
+/// Borrows the list so no node can be freed while iterating.
+pub struct IterForward<'a, T> {
+    front: *mut Node<T>,
+    back: *mut Node<T>,
     remaining: usize,
+    _list: PhantomData<&'a Node<T>>,
 }
 
-impl Iterator for IterForward {
-    type Item = Value;
+impl<T: Clone> Iterator for IterForward<'_, T> {
+    type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.remaining == 0 || self.cur.is_null() {
+        if self.remaining == 0 || self.front.is_null() {
+            return None;
+        }
+        unsafe {
+            let v = (*self.front).value.clone();
+            self.front = (*self.front).next;
+            self.remaining -= 1;
+            Some(v)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: Clone> DoubleEndedIterator for IterForward<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 || self.back.is_null() {
             return None;
         }
         unsafe {
-            let v = (*self.cur).value;
-            self.cur = (*self.cur).next;
+            let v = (*self.back).value.clone();
+            self.back = (*self.back).prev;
             self.remaining -= 1;
             Some(v)
         }
     }
 }
 
+impl<T: Clone> ExactSizeIterator for IterForward<'_, T> {}
+
 /// Backward iterator (unsafe list)
-pub struct IterBackward {
-    cur: *mut Node,
+///
+/// Borrows the list so no node can be freed while iterating.
+pub struct IterBackward<'a, T> {
+    front: *mut Node<T>,
+    back: *mut Node<T>,
     remaining: usize,
+    _list: PhantomData<&'a Node<T>>,
 }
 
-impl Iterator for IterBackward {
-    type Item = Value;
+impl<T: Clone> Iterator for IterBackward<'_, T> {
+    type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.remaining == 0 || self.cur.is_null() {
+        if self.remaining == 0 || self.back.is_null() {
+            return None;
+        }
+        unsafe {
+            let v = (*self.back).value.clone();
+            self.back = (*self.back).prev;
+            self.remaining -= 1;
+            Some(v)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: Clone> DoubleEndedIterator for IterBackward<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 || self.front.is_null() {
             return None;
         }
         unsafe {
-            let v = (*self.cur).value;
-            self.cur = (*self.cur).prev;
+            let v = (*self.front).value.clone();
+            self.front = (*self.front).next;
             self.remaining -= 1;
             Some(v)
         }
     }
 }
 
+impl<T: Clone> ExactSizeIterator for IterBackward<'_, T> {}
+
+/// Owning iterator (unsafe list): drains from head to tail.
+///
+/// Any values not yielded are freed when the iterator is dropped.
+pub struct IntoIter<T, A: NodeAlloc = Global> {
+    list: DoublyLinkedList<T, A>,
+}
+
+impl<T, A: NodeAlloc> Iterator for IntoIter<T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T, A: NodeAlloc> DoubleEndedIterator for IntoIter<T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T, A: NodeAlloc> ExactSizeIterator for IntoIter<T, A> {}
+
 // --------------------------------------------------
-// TODO #3: Drop implementation
+// Drop
 // --------------------------------------------------
 //
-// When the list is dropped, all remaining nodes must be freed.
-//
-// Requirements:
-// - Walk from head → tail
-// - Store next pointer BEFORE freeing current node
-// - Call dealloc_node exactly once per node
-// - After Drop completes, no memory should be leaked
+// `free_nodes` walks head → tail, advancing `head` past each node before
+// freeing it, so the list only ever holds nodes that are still live. Each
+// value is read out, its node freed with `dealloc_node`, and only then is
+// the value dropped. If that destructor panics, the guard runs
+// `free_nodes` again while unwinding and it picks up at the next node.
+// The pooled nodes are freed last.
 //
-impl Drop for DoublyLinkedList {
+impl<T, A: NodeAlloc> Drop for DoublyLinkedList<T, A> {
     fn drop(&mut self) {
-        todo!("Walk list and deallocate all nodes");
+        // Finishes the teardown while unwinding if a value's destructor
+        // panics. A second panic then aborts, as it would anywhere else.
+        struct Guard<'a, T, A: NodeAlloc>(&'a mut DoublyLinkedList<T, A>);
+
+        impl<T, A: NodeAlloc> Drop for Guard<'_, T, A> {
+            fn drop(&mut self) {
+                self.0.free_nodes();
+            }
+        }
+
+        let guard = Guard(self);
+        guard.0.free_nodes();
+    }
+}
+
+impl<T, A: NodeAlloc> DoublyLinkedList<T, A> {
+    /// Free every node, dropping the values in order, then the pool.
+    fn free_nodes(&mut self) {
+        while !self.head.is_null() {
+            unsafe {
+                let n = self.head;
+                self.head = (*n).next;
+                self.len -= 1;
+
+                let value = ptr::read(&(*n).value);
+                self.dealloc_node(n);
+                drop(value);
+            }
+        }
+
+        self.tail = null_mut();
+        self.set_pool_limit(0);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::node_alloc::{Bump, Failing, Slab};
+    use std::cell::Cell;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::rc::Rc;
 
     #[test]
     fn empty_list_basics() {
-        let mut list = DoublyLinkedList::new();
+        let mut list = DoublyLinkedList::<i64>::new();
 
         assert_eq!(list.len(), 0);
         assert!(list.is_empty());
@@ -479,4 +999,440 @@ mod tests {
         assert_eq!(bwd.len(), list.len());
         assert!(list.check_invariants());
     }
+
+    #[test]
+    fn get_and_get_mut_walk_from_either_end() {
+        let mut list: DoublyLinkedList<i64> = (0..9).collect();
+
+        for i in 0..9 {
+            assert_eq!(list.get(i), Ok(&(i as i64)));
+        }
+        assert_eq!(
+            list.get(9),
+            Err(ListError::IndexOutOfBounds { index: 9, len: 9 })
+        );
+
+        *list.get_mut(6).unwrap() = 60;
+        assert_eq!(list.get(6), Ok(&60));
+        assert!(list.get_mut(9).is_err());
+        assert!(list.check_invariants());
+    }
+
+    #[test]
+    fn insert_at_and_remove_at_cover_ends_and_middle() {
+        let mut list = DoublyLinkedList::new();
+        list.insert_at(0, 2).unwrap(); // [2]
+        list.insert_at(0, 1).unwrap(); // [1,2]
+        list.insert_at(2, 4).unwrap(); // [1,2,4]
+        list.insert_at(2, 3).unwrap(); // [1,2,3,4]
+        assert_eq!(
+            list.insert_at(5, 9),
+            Err(ListError::IndexOutOfBounds { index: 5, len: 4 })
+        );
+        assert!(list.check_invariants());
+        assert_eq!(list.iter_forward().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+
+        assert_eq!(list.remove_at(2), Ok(3)); // middle
+        assert_eq!(list.remove_at(2), Ok(4)); // tail
+        assert_eq!(list.remove_at(0), Ok(1)); // head
+        assert_eq!(
+            list.remove_at(1),
+            Err(ListError::IndexOutOfBounds { index: 1, len: 1 })
+        );
+        assert_eq!(list.remove_at(0), Ok(2)); // last one
+        assert!(list.is_empty());
+        assert!(list.check_invariants());
+    }
+
+    #[test]
+    fn swap_and_truncate() {
+        let mut list: DoublyLinkedList<i64> = (0..5).collect();
+        list.swap(0, 4).unwrap();
+        list.swap(2, 2).unwrap();
+        list.swap(3, 1).unwrap();
+        assert_eq!(
+            list.swap(5, 0),
+            Err(ListError::IndexOutOfBounds { index: 5, len: 5 })
+        );
+        assert_eq!(list.iter_forward().collect::<Vec<_>>(), vec![4, 3, 2, 1, 0]);
+
+        list.truncate(7);
+        assert_eq!(list.len(), 5);
+        list.truncate(2);
+        assert_eq!(list.iter_backward().collect::<Vec<_>>(), vec![3, 4]);
+        list.truncate(0);
+        assert!(list.is_empty());
+        assert!(list.check_invariants());
+    }
+
+    fn hash_of<H: Hash>(x: &H) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+
+        let mut h = DefaultHasher::new();
+        x.hash(&mut h);
+        h.finish()
+    }
+
+    #[test]
+    fn collect_extend_clone_and_equality() {
+        let mut list: DoublyLinkedList<i64> = (1..=3).collect();
+        list.extend([4, 5]);
+        assert!(list.check_invariants());
+
+        let copy = list.clone();
+        assert_eq!(list, copy);
+        assert_eq!(hash_of(&list), hash_of(&copy));
+
+        list.pop_front();
+        assert_ne!(list, copy);
+        assert_eq!(copy.len(), 5);
+        assert!(copy.check_invariants());
+
+        assert_eq!(format!("{:?}", copy), "[1, 2, 3, 4, 5]");
+        assert_eq!(format!("{:?}", DoublyLinkedList::<i64>::default()), "[]");
+    }
+
+    #[test]
+    fn owned_into_iter_drains_and_frees_the_rest() {
+        let list: DoublyLinkedList<i64> = (0..5).collect();
+        let mut it = list.into_iter();
+
+        assert_eq!(it.len(), 5);
+        assert_eq!(it.next(), Some(0));
+        assert_eq!(it.next_back(), Some(4));
+        assert_eq!(it.len(), 3);
+        // Dropping the iterator here must free nodes 1..=3.
+    }
+
+    #[test]
+    fn borrowed_iterators_are_double_ended_and_exact_size() {
+        let list: DoublyLinkedList<i64> = (0..6).collect();
+
+        let mut fwd = list.iter_forward();
+        assert_eq!(fwd.len(), 6);
+        assert_eq!(fwd.next_back(), Some(5));
+        assert_eq!(fwd.next(), Some(0));
+        assert_eq!(fwd.rev().collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+
+        let mut bwd = list.iter_backward();
+        assert_eq!(bwd.next_back(), Some(0));
+        assert_eq!(bwd.len(), 5);
+        assert_eq!(bwd.collect::<Vec<_>>(), vec![5, 4, 3, 2, 1]);
+
+        assert_eq!((&list).into_iter().sum::<i64>(), 15);
+    }
+
+    #[test]
+    fn every_node_goes_back_to_the_allocator() {
+        let counting = Failing::after(usize::MAX);
+        {
+            let mut list = DoublyLinkedList::new_in(&counting);
+            list.extend(0..10);
+            list.insert_at(5, 50).unwrap();
+            assert_eq!(counting.live(), 11);
+
+            list.remove_at(0).unwrap();
+            list.pop_back();
+            list.truncate(6);
+            assert_eq!(counting.live(), 6);
+
+            // The clone borrows the same allocator.
+            let copy = list.clone();
+            assert_eq!(counting.live(), 12);
+            let mut it = copy.into_iter();
+            it.next();
+            assert_eq!(counting.live(), 11);
+        }
+        assert_eq!(counting.live(), 0);
+        assert_eq!(counting.failures(), 0);
+    }
+
+    #[test]
+    fn lists_can_share_an_arena() {
+        let arena = Bump::with_chunk_size(1024);
+        let mut a = DoublyLinkedList::new_in(&arena);
+        let mut b = DoublyLinkedList::new_in(&arena);
+        for i in 0..20 {
+            a.push_back(i);
+            b.push_front(i);
+        }
+        assert_eq!(arena.chunks(), 1);
+        assert!(a.check_invariants() && b.check_invariants());
+        assert_eq!(
+            a.iter_forward().collect::<Vec<_>>(),
+            b.iter_backward().collect::<Vec<_>>()
+        );
+
+        // Popping does not give memory back to an arena; dropping it does.
+        while a.pop_front().is_some() {}
+        a.extend(0..20);
+        assert_eq!(arena.chunks(), 2);
+    }
+
+    #[test]
+    fn slab_list_reuses_slots_up_to_capacity() {
+        let mut list = DoublyLinkedList::new_in(Slab::new(4));
+        for round in 0..100 {
+            list.push_back(round);
+            list.push_front(-round);
+            if list.len() > 3 {
+                list.pop_front();
+                list.pop_back();
+            }
+            assert!(list.check_invariants());
+            assert!(list.allocator().live() <= 4);
+        }
+        assert_eq!(list.allocator().live(), list.len());
+    }
+
+    #[test]
+    fn pool_recycles_freed_nodes_up_to_its_limit() {
+        let counting = Failing::after(usize::MAX);
+        {
+            let mut list = DoublyLinkedList::with_capacity_in(4, &counting);
+            assert_eq!((list.len(), list.pooled(), list.capacity()), (0, 4, 4));
+            assert_eq!(counting.live(), 4);
+            assert!(list.check_invariants());
+
+            // Pushes use the pooled nodes before allocating.
+            list.extend(0..4);
+            assert_eq!((list.pooled(), counting.live()), (0, 4));
+            list.push_back(4);
+            assert_eq!(counting.live(), 5);
+
+            // Pops refill the pool; the node past the limit is freed.
+            list.truncate(0);
+            assert_eq!((list.pooled(), counting.live()), (4, 4));
+            assert!(list.check_invariants());
+
+            // A queue at steady depth never allocates again.
+            for i in 0..100 {
+                list.push_back(i);
+                if list.len() > 3 {
+                    assert_eq!(list.pop_front(), Some(i - 3));
+                }
+                assert!(list.check_invariants());
+            }
+            assert_eq!(counting.live(), 4);
+
+            list.insert_at(1, 10).unwrap();
+            list.remove_at(2).unwrap();
+            assert_eq!(list.iter_forward().collect::<Vec<_>>(), vec![97, 10, 99]);
+            assert_eq!(list.capacity(), 4);
+        }
+        // Drop frees the pool as well as the list.
+        assert_eq!(counting.live(), 0);
+    }
+
+    #[test]
+    fn shrink_to_fit_and_set_pool_limit_release_pooled_nodes() {
+        let counting = Failing::after(usize::MAX);
+        let mut list = DoublyLinkedList::with_capacity_in(8, &counting);
+        list.extend(0..6);
+        list.shrink_to_fit();
+        assert_eq!((list.pooled(), counting.live()), (0, 6));
+
+        // The limit survives shrinking.
+        list.truncate(1);
+        assert_eq!((list.pooled(), list.pool_limit()), (5, 8));
+
+        list.set_pool_limit(2);
+        assert_eq!((list.pooled(), counting.live()), (2, 3));
+        list.pop_back();
+        assert_eq!((list.pooled(), counting.live()), (2, 2));
+        assert!(list.check_invariants());
+
+        // Clones keep the limit, not the spare nodes; IntoIter pools what
+        // it pops and frees it all on drop.
+        list.extend(0..4);
+        let copy = list.clone();
+        assert_eq!((copy.pool_limit(), copy.pooled()), (2, 0));
+        copy.into_iter().take(3).for_each(drop);
+        assert_eq!(counting.live(), 4);
+
+        assert_eq!(DoublyLinkedList::<i64>::new().pool_limit(), 0);
+        drop(list);
+        assert_eq!(counting.live(), 0);
+    }
+
+    #[test]
+    fn failed_allocations_leave_the_list_unchanged() {
+        let failing = Failing::after(3);
+        let oom = Err(ListError::AllocFailed(Layout::new::<Node<i64>>()));
+        {
+            let mut list = DoublyLinkedList::new_in(&failing);
+            list.try_push_back(1).unwrap();
+            list.try_push_front(0).unwrap();
+            list.try_insert_at(2, 3).unwrap();
+
+            assert_eq!(list.try_push_front(-1), oom);
+            assert_eq!(list.try_push_back(4), oom);
+            assert_eq!(list.try_insert_at(2, 2), oom);
+            assert_eq!(list.try_insert_at(0, -1), oom);
+            // A bad index is reported before anything is allocated.
+            assert_eq!(
+                list.try_insert_at(9, 9),
+                Err(ListError::IndexOutOfBounds { index: 9, len: 3 })
+            );
+            assert_eq!(failing.failures(), 4);
+
+            assert!(list.check_invariants());
+            assert_eq!(list.iter_forward().collect::<Vec<_>>(), vec![0, 1, 3]);
+            assert_eq!(list.iter_backward().collect::<Vec<_>>(), vec![3, 1, 0]);
+            assert_eq!(failing.live(), 3);
+
+            // Once memory is available again, so is the list.
+            failing.set_budget(1);
+            list.try_insert_at(2, 2).unwrap();
+            assert_eq!(list.iter_forward().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+        }
+        assert_eq!(failing.live(), 0);
+    }
+
+    #[test]
+    fn pooled_nodes_are_used_before_the_allocator_fails() {
+        let failing = Failing::after(2);
+        let mut list = DoublyLinkedList::with_capacity_in(2, &failing);
+        list.try_push_back(1).unwrap();
+        list.try_push_back(2).unwrap();
+        assert!(matches!(
+            list.try_push_back(3),
+            Err(ListError::AllocFailed(_))
+        ));
+
+        list.pop_front();
+        list.try_push_back(3).unwrap();
+        assert_eq!(list.iter_forward().collect::<Vec<_>>(), vec![2, 3]);
+        assert!(list.check_invariants());
+    }
+
+    /// Counts its drops in a shared counter. One made with `panicking`
+    /// panics when dropped (after counting).
+    struct DropCounter {
+        drops: Rc<Cell<usize>>,
+        panics: bool,
+    }
+
+    impl DropCounter {
+        fn new(drops: &Rc<Cell<usize>>) -> Self {
+            Self {
+                drops: Rc::clone(drops),
+                panics: false,
+            }
+        }
+
+        fn panicking(drops: &Rc<Cell<usize>>) -> Self {
+            Self {
+                drops: Rc::clone(drops),
+                panics: true,
+            }
+        }
+    }
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+            if self.panics {
+                panic!("DropCounter dropped");
+            }
+        }
+    }
+
+    #[test]
+    fn every_value_is_dropped_exactly_once() {
+        let drops = Rc::new(Cell::new(0));
+        let counting = Failing::after(usize::MAX);
+        let mut list = DoublyLinkedList::with_capacity_in(2, &counting);
+        for _ in 0..12 {
+            list.push_back(DropCounter::new(&drops));
+        }
+
+        // Moved out, not dropped, until the caller lets go.
+        let kept = list.pop_front().unwrap();
+        let removed = list.remove_at(3).unwrap();
+        assert_eq!(drops.get(), 0);
+        drop((kept, removed));
+        assert_eq!(drops.get(), 2);
+
+        list.pop_back();
+        list.truncate(6);
+        assert_eq!(drops.get(), 6);
+
+        let mut it = list.into_iter();
+        it.next();
+        assert_eq!(drops.get(), 7);
+        drop(it);
+        assert_eq!(drops.get(), 12);
+        assert_eq!(counting.live(), 0);
+
+        // `clear` drops the values and pools the nodes.
+        let mut list = DoublyLinkedList::with_capacity_in(4, &counting);
+        list.extend((0..4).map(|_| DropCounter::new(&drops)));
+        list.clear();
+        assert_eq!(drops.get(), 16);
+        assert_eq!((list.len(), list.pooled()), (0, 4));
+        assert!(list.check_invariants());
+
+        // A value that never made it into the list is dropped too.
+        counting.set_budget(0);
+        list.shrink_to_fit();
+        assert!(list.try_push_back(DropCounter::new(&drops)).is_err());
+        assert_eq!(drops.get(), 17);
+        drop(list);
+        assert_eq!(counting.live(), 0);
+    }
+
+    #[test]
+    fn a_panicking_destructor_does_not_stop_drop() {
+        let drops = Rc::new(Cell::new(0));
+        let counting = Failing::after(usize::MAX);
+        let mut list = DoublyLinkedList::with_capacity_in(3, &counting);
+        for i in 0..6 {
+            if i == 2 {
+                list.push_back(DropCounter::panicking(&drops));
+            } else {
+                list.push_back(DropCounter::new(&drops));
+            }
+        }
+        list.pop_back();
+        assert_eq!(drops.get(), 1);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| drop(list)));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 6);
+        assert_eq!(counting.live(), 0);
+    }
+
+    #[test]
+    fn a_panicking_destructor_leaves_clear_with_a_valid_list() {
+        let drops = Rc::new(Cell::new(0));
+        let counting = Failing::after(usize::MAX);
+        let mut list = DoublyLinkedList::new_in(&counting);
+        list.push_back(DropCounter::new(&drops));
+        list.push_back(DropCounter::panicking(&drops));
+        list.push_back(DropCounter::new(&drops));
+        list.push_back(DropCounter::new(&drops));
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| list.clear()));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 2);
+        assert_eq!(list.len(), 2);
+        assert!(list.check_invariants());
+        assert_eq!(counting.live(), 2);
+
+        drop(list);
+        assert_eq!(drops.get(), 4);
+        assert_eq!(counting.live(), 0);
+    }
+
+    #[test]
+    fn holds_owned_values() {
+        let mut list: DoublyLinkedList<String> = ["b", "c"].iter().map(|s| s.to_string()).collect();
+        list.push_front("a".to_string());
+        list.get_mut(2).unwrap().push('!');
+        assert_eq!(list.iter_forward().collect::<Vec<_>>(), ["a", "b", "c!"]);
+        assert_eq!(format!("{:?}", list), r#"["a", "b", "c!"]"#);
+        assert_eq!(list.clone(), list);
+        assert_eq!(list.remove_at(1).as_deref(), Ok("b"));
+    }
 }
@@ -42,7 +42,10 @@ impl Value {
     /// - `Some(i64)` if this value is an integer
     /// - `None` if this value is a float
     pub fn as_int(&self) -> Option<i64> {
-        todo!("Return Some(i64) if Value::Int, otherwise None");
+        match self {
+            Value::Int(x) => Some(*x),
+            Value::Float(_) => None,
+        }
     }
 
     /// Attempt to extract the value as an `f64`.
@@ -51,7 +54,10 @@ impl Value {
     /// - `Some(f64)` if this value is a float
     /// - `None` if this value is an integer
     pub fn as_float(&self) -> Option<f64> {
-        todo!("Return Some(f64) if Value::Float, otherwise None");
+        match self {
+            Value::Float(x) => Some(*x),
+            Value::Int(_) => None,
+        }
     }
 }
 
@@ -94,7 +100,7 @@ mod tests {
             assert_eq!(v.as_float(), None);
         }
 
-        let floats = [0.0, 1.5, -2.25, 3.141592653589793];
+        let floats = [0.0, 1.5, -2.25, std::f64::consts::PI];
         for &x in &floats {
             let v = Value::float(x);
             assert_eq!(v.as_float(), Some(x));
@@ -0,0 +1,517 @@
+// Command-line and config-file parameters for the PoW search.
+//
+// The parsing (defaults < `--config FILE` < flags, `key = value` files,
+// `--resume`) lives in `pow_core::config`. This file adds assignment-2's
+// own parameters on top of `pow_core::SearchConfig`: how the range is
+// scheduled, block headers and the job server.
+//
+//   chunk_size = 10_000   # or `auto`
+//   mode       = stealing
+//
+// `--block-header` hashes a Bitcoin-style block header instead of
+// `prefix:nonce` (see `pow_core::header`); setting any header field turns it
+// on. Hashes are given in block-explorer (display) order:
+//
+//   block_header = true
+//   prev_hash    = 000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f
+//   timestamp    = 1231469665
+//   nonce_width  = 32          # or 64; 32 rolls over into an extra nonce
+//
+// `bits` defaults to the difficulty's compact target. A resumed run also
+// takes its header from the checkpoint; threads, chunk size and mode may
+// change between runs.
+
+use std::str::FromStr;
+
+use pow_core::config::{
+    self, ConfigError, Options, SearchConfig, VerifyArgs, flag_value, parse_num, parse_value,
+    split_flag,
+};
+use pow_core::header::parse_display_hash;
+use pow_core::{BlockHeader, ChunkSize};
+
+pub const USAGE: &str = "\
+Usage: assignment-2 [OPTIONS]
+       assignment-2 verify [FILE|-] [--valid-only] [--threads N]
+       assignment-2 serve [--listen ADDR] [--chunk-size N]
+       assignment-2 work [--server ADDR] [--threads N]
+
+Options:
+  --config <FILE>       read `key = value` parameters from FILE first
+  --prefix <STR>        candidate prefix            [default: cmkl-pow]
+  --difficulty <D>      N hex zeros, Nb zero bits,  [default: 6]
+                        0xNBITS or 64-hex target
+  --algorithm <H>       sha256 | sha256d | sha512-256 | blake2b |
+                        blake2s | blake3            [default: sha256]
+  --k <N>               solutions to collect        [default: 10]
+  --start <N>           first nonce (inclusive)     [default: 0]
+  --end <N>             last nonce (exclusive)      [default: 50_000_000_000]
+  --threads <N>         worker threads              [default: 8]
+  --chunk-size <N>      nonces per work item, or `auto` to size chunks
+                        from the measured hashrate  [default: auto]
+                        (stealing mode: auto means 50_000)
+  --mode <MODE>         central | stealing          [default: central]
+  --block-header        hash a block header instead of `prefix:nonce`
+  --header-version <N>  header version              [default: 1]
+  --prev-hash <HEX>     previous block hash         [default: 0]
+  --merkle-root <HEX>   merkle root (extra nonce 0) [default: 0]
+  --timestamp <SECS>    header time                 [default: 0]
+  --bits <0xNBITS>      header nBits        [default: the difficulty's]
+  --nonce-width <W>     32 (with extra nonce) | 64  [default: 32]
+  --smallest            report exactly the k smallest valid nonces
+                        (keep searching below the k-th best found)
+  --json <FILE>         also write the solutions to FILE as JSON
+  --timeout <SECS>      stop after SECS seconds, 0 for no limit
+                        (exit code 124)             [default: 0]
+  --max-hashes <N>      stop after about N hashes, 0 for no limit
+                        (exit code 124)             [default: 0]
+  --checkpoint <FILE>   save progress to FILE periodically and on Ctrl-C
+  --checkpoint-interval <SECS>
+                        seconds between checkpoints [default: 30]
+  --resume <FILE>       continue the search saved in checkpoint FILE
+  --progress <SECS>     print progress to stderr every SECS seconds,
+                        0 to disable                [default: 5]
+  --progress-format <F> text | json (one object per line) [default: text]
+  --server <ADDR>       submit the search to a `serve` process instead
+                        of running it here
+  -h, --help            print this help
+
+verify: re-check a run's printed output or JSON file (stdin if FILE is
+omitted or `-`): every hash, the difficulty, and that the list is the k
+smallest valid nonces in the range. Exits 1 on any discrepancy.
+  --valid-only          skip the k-smallest rescan
+  --threads <N>         threads for the rescan      [default: all cores]
+
+serve: accept searches from `--server` runs and hand their chunks to
+`work` processes (line-delimited JSON over TCP).
+  --listen <ADDR>       address to listen on        [default: 127.0.0.1:7878]
+  --chunk-size <N>      nonces per chunk            [default: 1_000_000]
+
+work: hash chunks for a `serve` process until it shuts down.
+  --server <ADDR>       server to connect to        [default: 127.0.0.1:7878]
+  --threads <N>         connections, one thread each [default: all cores]";
+
+/// Where `serve` listens and `work` connects by default.
+pub const DEFAULT_SERVER: &str = "127.0.0.1:7878";
+
+/// How workers get their chunks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueueMode {
+    /// One shared cursor over the range that every worker claims chunks from.
+    Central,
+    /// One lock-free deque per worker; idle workers steal from the others.
+    Stealing,
+}
+
+impl QueueMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            QueueMode::Central => "central",
+            QueueMode::Stealing => "stealing",
+        }
+    }
+}
+
+impl FromStr for QueueMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "central" => Ok(QueueMode::Central),
+            "stealing" => Ok(QueueMode::Stealing),
+            _ => Err("expected `central` or `stealing`".to_string()),
+        }
+    }
+}
+
+/// All parameters of one search run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// What to search for, threads, budgets, checkpoints and progress.
+    pub search: SearchConfig,
+    pub chunk_size: ChunkSize,
+    pub mode: QueueMode,
+    /// Hash `header` instead of `prefix:nonce`.
+    pub block_header: bool,
+    /// The header's fields; `bits` is only used if set explicitly.
+    pub header: BlockHeader,
+    /// Header nBits; the difficulty's compact target if unset.
+    pub bits: Option<u32>,
+    /// Job server to run the search on, instead of locally.
+    pub server: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            // The values this assignment always used.
+            search: SearchConfig {
+                prefix: "cmkl-pow".to_string(),
+                difficulty: pow_core::Difficulty::hex_digits(6),
+                k: 10,
+                end: 50_000_000_000,
+                ..SearchConfig::default()
+            },
+            chunk_size: ChunkSize::Adaptive,
+            mode: QueueMode::Central,
+            block_header: false,
+            header: BlockHeader::new(0),
+            bits: None,
+            server: None,
+        }
+    }
+}
+
+/// Parameters of the `serve` subcommand.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServeArgs {
+    pub listen: String,
+    /// Nonces per chunk handed to a worker.
+    pub chunk_size: u64,
+}
+
+/// Parameters of the `work` subcommand.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkArgs {
+    pub server: String,
+    /// Connections to the server, each hashing on its own thread.
+    pub threads: usize,
+}
+
+/// What the command line asked for.
+// Built once per process, so the size of `Run` does not matter.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Run(Config),
+    Verify(VerifyArgs),
+    Serve(ServeArgs),
+    Work(WorkArgs),
+    Help,
+}
+
+impl Options for Config {
+    const SWITCHES: &'static [&'static str] = &["smallest", "block-header"];
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        match key.replace('-', "_").as_str() {
+            "server" => self.server = Some(value.to_string()),
+            "block_header" => self.block_header = parse_value(key, value)?,
+            "header_version" | "prev_hash" | "merkle_root" | "timestamp" | "bits"
+            | "nonce_width" => {
+                self.set_header_field(key, value)?;
+                self.block_header = true;
+            }
+            "chunk_size" => {
+                self.chunk_size = match value {
+                    "auto" => ChunkSize::Adaptive,
+                    _ => ChunkSize::Fixed(parse_num(key, value)?),
+                }
+            }
+            "mode" => self.mode = parse_value(key, value)?,
+            _ => return self.search.set(key, value),
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        self.search.validate()?;
+        if self.chunk_size == ChunkSize::Fixed(0) {
+            return Err(ConfigError::Invalid(
+                "chunk_size must be at least 1".to_string(),
+            ));
+        }
+        let search = &self.search;
+        if self.server.is_some() && (search.checkpoint.is_some() || search.resume.is_some()) {
+            return Err(ConfigError::Invalid(
+                "--server cannot be combined with --checkpoint or --resume".to_string(),
+            ));
+        }
+        if self.server.is_some() && (search.timeout > 0 || search.max_hashes > 0) {
+            return Err(ConfigError::Invalid(
+                "--server cannot be combined with --timeout or --max-hashes".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn search_mut(&mut self) -> &mut SearchConfig {
+        &mut self.search
+    }
+}
+
+impl Config {
+    /// Set one block-header field.
+    fn set_header_field(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        let invalid = |reason: String| ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            reason,
+        };
+        match key.replace('-', "_").as_str() {
+            "header_version" => self.header.version = parse_num(key, value)?,
+            "prev_hash" => {
+                self.header.prev_hash =
+                    parse_display_hash(value).map_err(|e| invalid(e.to_string()))?
+            }
+            "merkle_root" => {
+                self.header.merkle_root =
+                    parse_display_hash(value).map_err(|e| invalid(e.to_string()))?
+            }
+            "timestamp" => self.header.time = parse_num(key, value)?,
+            "bits" => {
+                let digits = value.strip_prefix("0x").unwrap_or(value);
+                let bits = u32::from_str_radix(digits, 16).map_err(|e| invalid(e.to_string()))?;
+                self.bits = Some(bits);
+            }
+            "nonce_width" => self.header.nonce_width = value.parse().map_err(invalid)?,
+            _ => unreachable!("not a header field: {}", key),
+        }
+        Ok(())
+    }
+
+    /// The block header to hash, if this is a header search.
+    pub fn block_header(&self) -> Option<BlockHeader> {
+        self.block_header.then(|| BlockHeader {
+            bits: self
+                .bits
+                .unwrap_or_else(|| self.search.difficulty.compact_bits()),
+            ..self.header
+        })
+    }
+}
+
+/// Parse the command line (without the program name).
+pub fn parse_args<I>(args: I) -> Result<Command, ConfigError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter().peekable();
+    match args.peek().map(String::as_str) {
+        Some("verify") => {
+            args.next();
+            Ok(config::parse_verify_args(args)?.map_or(Command::Help, Command::Verify))
+        }
+        Some("serve") => {
+            args.next();
+            parse_serve_args(args)
+        }
+        Some("work") => {
+            args.next();
+            parse_work_args(args)
+        }
+        _ => {
+            Ok(config::parse_options(Config::default(), args)?.map_or(Command::Help, Command::Run))
+        }
+    }
+}
+
+/// Parse the arguments after `serve`.
+fn parse_serve_args<I>(args: I) -> Result<Command, ConfigError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut serve = ServeArgs {
+        listen: DEFAULT_SERVER.to_string(),
+        chunk_size: 1_000_000,
+    };
+
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        let (flag, inline) = split_flag(&arg);
+        if flag == "-h" || flag == "--help" {
+            return Ok(Command::Help);
+        }
+        let value = flag_value(&arg, inline, &mut args)?;
+        match flag {
+            "--listen" => serve.listen = value,
+            "--chunk-size" => {
+                serve.chunk_size = parse_num("chunk_size", &value)?;
+                if serve.chunk_size == 0 {
+                    return Err(ConfigError::Invalid(
+                        "chunk_size must be at least 1".to_string(),
+                    ));
+                }
+            }
+            _ => return Err(ConfigError::UnknownKey(arg)),
+        }
+    }
+    Ok(Command::Serve(serve))
+}
+
+/// Parse the arguments after `work`.
+fn parse_work_args<I>(args: I) -> Result<Command, ConfigError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut work = WorkArgs {
+        server: DEFAULT_SERVER.to_string(),
+        threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        let (flag, inline) = split_flag(&arg);
+        if flag == "-h" || flag == "--help" {
+            return Ok(Command::Help);
+        }
+        let value = flag_value(&arg, inline, &mut args)?;
+        match flag {
+            "--server" => work.server = value,
+            "--threads" => {
+                work.threads = parse_num("threads", &value)?;
+                if work.threads == 0 {
+                    return Err(ConfigError::Invalid(
+                        "threads must be at least 1".to_string(),
+                    ));
+                }
+            }
+            _ => return Err(ConfigError::UnknownKey(arg)),
+        }
+    }
+    Ok(Command::Work(work))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pow_core::Difficulty;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    fn run(s: &str) -> Result<Config, ConfigError> {
+        match parse_args(args(s))? {
+            Command::Run(cfg) => Ok(cfg),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn no_flags_gives_the_defaults() {
+        assert_eq!(run(""), Ok(Config::default()));
+        assert_eq!(run("").unwrap().search.prefix, "cmkl-pow");
+        assert_eq!(parse_args(args("--threads 2 --help")), Ok(Command::Help));
+    }
+
+    #[test]
+    fn flags_override_defaults() {
+        let cfg = run("--difficulty 3 --k=2 --end 1_000 --chunk-size 10 --mode stealing").unwrap();
+        assert_eq!(cfg.search.difficulty, Difficulty::LeadingZeroBits(12));
+        assert_eq!(cfg.search.k, 2);
+        assert_eq!(cfg.search.end, 1000);
+        assert_eq!(cfg.chunk_size, ChunkSize::Fixed(10));
+        assert_eq!(cfg.mode, QueueMode::Stealing);
+    }
+
+    #[test]
+    fn config_file_sets_schedule_options() {
+        let mut cfg = Config::default();
+        config::apply_file(
+            &mut cfg,
+            "test.conf",
+            "threads = 3\nchunk-size = 7\nmode = stealing\n",
+        )
+        .unwrap();
+        assert_eq!(cfg.search.threads, 3);
+        assert_eq!(cfg.chunk_size, ChunkSize::Fixed(7));
+        assert_eq!(cfg.mode, QueueMode::Stealing);
+        config::apply_file(&mut cfg, "test.conf", "chunk_size = auto").unwrap();
+        assert_eq!(cfg.chunk_size, ChunkSize::Adaptive);
+    }
+
+    #[test]
+    fn impossible_parameters_are_rejected() {
+        for bad in ["--threads 0", "--chunk-size 0", "--k 0"] {
+            assert!(
+                matches!(run(bad), Err(ConfigError::Invalid(_))),
+                "{bad} should be rejected"
+            );
+        }
+        assert!(matches!(
+            run("--mode fast"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            run("--server 127.0.0.1:9000 --timeout 5"),
+            Err(ConfigError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn verify_subcommand() {
+        let Ok(Command::Verify(v)) = parse_args(args("verify out.txt --valid-only --threads=3"))
+        else {
+            panic!("expected verify");
+        };
+        assert_eq!(v.input.as_deref(), Some("out.txt"));
+        assert!(v.valid_only);
+        assert_eq!(v.threads, 3);
+        assert_eq!(parse_args(args("verify --help")), Ok(Command::Help));
+        assert!(parse_args(args("verify --chunk-size 3")).is_err());
+    }
+
+    #[test]
+    fn block_header_options() {
+        assert_eq!(run("").unwrap().block_header(), None);
+
+        let cfg = run("--block-header --difficulty 0x1d00ffff").unwrap();
+        assert_eq!(cfg.block_header(), Some(BlockHeader::new(0x1d00ffff)));
+        // The bits follow the difficulty unless set.
+        let cfg = run("--block-header --difficulty 8").unwrap();
+        assert_eq!(cfg.block_header().unwrap().bits, 0x1d01_0000);
+
+        // Any header field implies --block-header.
+        let cfg = run("--timestamp 1231006505 --bits 0x1d00ffff --nonce-width 64 \
+             --merkle-root 4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b")
+        .unwrap();
+        let header = cfg.block_header().unwrap();
+        assert_eq!(header.time, 1_231_006_505);
+        assert_eq!(header.bits, 0x1d00ffff);
+        assert_eq!(header.nonce_width, pow_core::NonceWidth::U64);
+        // Stored in header byte order.
+        assert_eq!(header.merkle_root[0], 0x3b);
+
+        for bad in ["--prev-hash 1234", "--bits zz", "--nonce-width 16"] {
+            assert!(
+                matches!(run(bad), Err(ConfigError::InvalidValue { .. })),
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn serve_and_work_subcommands() {
+        assert_eq!(
+            parse_args(args("serve --listen 0.0.0.0:9000 --chunk-size 50_000")),
+            Ok(Command::Serve(ServeArgs {
+                listen: "0.0.0.0:9000".to_string(),
+                chunk_size: 50_000,
+            }))
+        );
+        let Ok(Command::Work(w)) = parse_args(args("work --threads 2")) else {
+            panic!("expected work");
+        };
+        assert_eq!(w.server, DEFAULT_SERVER);
+        assert_eq!(w.threads, 2);
+        assert_eq!(
+            parse_args(args("work --server=10.0.0.1:9000 --threads=3")),
+            Ok(Command::Work(WorkArgs {
+                server: "10.0.0.1:9000".to_string(),
+                threads: 3,
+            }))
+        );
+
+        assert!(parse_args(args("serve --chunk-size 0")).is_err());
+        assert!(parse_args(args("work --threads")).is_err());
+        assert!(parse_args(args("work --k 3")).is_err());
+
+        let cfg = run("--server 127.0.0.1:9000 --difficulty 3").unwrap();
+        assert_eq!(cfg.server.as_deref(), Some("127.0.0.1:9000"));
+        assert!(matches!(
+            run("--server 127.0.0.1:9000 --checkpoint run.ckpt"),
+            Err(ConfigError::Invalid(_))
+        ));
+    }
+}
@@ -16,7 +16,7 @@
 //    - Use the Receiver<Solution> in main to collect results.
 //
 // 3) Coordinate shared mutable state safely using Arc, Mutex, and atomics.
-//    - Global work queue: Arc<Mutex<VecDeque<Work>>>
+//    - Global work queue: a shared AtomicU64 cursor over the nonce range
 //    - Cooperative termination: Arc<AtomicBool>
 //    - Shared hash counter: Arc<AtomicU64>
 //
@@ -26,11 +26,18 @@
 // You will search for nonces such that:
 //
 //   candidate = prefix + ":" + nonce
-//   hash_hex  = SHA256(candidate)
+//   hash_hex  = HASH(candidate)      (SHA256 unless `--algorithm` says otherwise)
 //
 // A nonce is VALID if `hash_hex` begins with `difficulty` leading '0' hex characters.
 // Example: difficulty=4 => hash begins with "0000".
 //
+// For finer control, difficulty can also be given as leading zero BITS (`--difficulty
+// 22b`; 4 bits per hex zero) or as a 256-bit target `hash < target`, written in
+// Bitcoin's compact nBits form (`--difficulty 0x1e00ffff`) or as 64 hex digits.
+//
+// The hot loop never builds `hash_hex`: it checks the difficulty on the raw 32-byte
+// digest and only hex-encodes solutions for printing (see the `pow-core` crate).
+//
 // This is a toy model of Proof-of-Work used in blockchains. The purpose of PoW is to
 // make it computationally expensive to produce a valid "proof", which helps limit spam
 // and makes certain attacks costly. Here we focus on the compute + concurrency aspects.
@@ -38,257 +45,427 @@
 // =====================================================================================
 // Concurrency model
 // =====================================================================================
-// We use a SINGLE global work queue:
+// We use a SINGLE global work queue, generated lazily from an atomic cursor:
 //
-//   Arc<Mutex<VecDeque<Work>>> work_queue
+//   next: AtomicU64      // first nonce nobody has claimed yet
 //
 // A Work item represents a small CHUNK of nonces [start, start+len).
 //
+// Instead of splitting the whole range into a million chunks up front, a
+// worker claims the next chunk with a compare-and-swap that advances the
+// cursor by the chunk length, so "popping" never takes a lock. With
+// `--chunk-size auto` (the default) each worker sizes its chunks from how
+// long its last one took: small at startup, ~20 ms of hashing in steady
+// state, and a shrinking share of what is left near the end. When another
+// worker moves the cursor first the CAS fails and is retried from the new
+// position; the report counts these as `cursor_retries`.
+//
 // Each worker repeatedly:
 //   1) Checks the stop flag. If set, exits promptly.
-//   2) Takes ONE Work chunk from the global queue (short critical section).
-//   3) If the queue is empty, exits.
+//   2) Claims ONE Work chunk by advancing the cursor (a single CAS, retried
+//      if another worker got there first).
+//   3) If the cursor has reached the end of the range, exits.
 //   4) Processes the chunk locally (no locks while hashing):
 //       - periodically checks stop flag
 //       - computes hashes
 //       - increments the global hash counter
 //       - sends any found solutions back to main via a channel
 //
+// Optional: work-stealing mode
+//
+// Instead of one global queue, each worker owns a Chase-Lev deque
+// (crossbeam_deque::Worker). The initial chunks are dealt round-robin
+// across the deques, so every worker still walks roughly ascending nonces.
+// A worker pops from its own deque without locking; when it runs dry, it
+// steals a batch from another worker's deque. Select it with:
+//
+//   cargo run --release -- --mode stealing
+//
+// Both modes report queue statistics (cursor races / steals) so they can be
+// compared on the same parameters.
+//
+// Optional: k-smallest mode (`--smallest`)
+//
+// By default the collector stops everyone as soon as k solutions arrive, so a
+// worker that happens to finish a later chunk first can push out a smaller
+// nonce that was still being searched. In k-smallest mode the collector keeps
+// the k best so far and publishes the k-th best nonce as a shared `bound`.
+// Workers skip chunks that start at or above it and abandon the rest of a
+// chunk once they pass it, and the run ends when the queue drains. Every
+// nonce below the final bound is hashed, so the answer is exactly the k
+// smallest valid nonces in [start, end), whatever the thread count.
+//
+// Optional: checkpoint / resume (`--checkpoint FILE`, `--resume FILE`)
+//
+// After each chunk a worker records the nonces it actually searched (the
+// whole chunk, or the part before it stopped) together with the solutions
+// it found there in a shared `Progress`. The collector saves a snapshot of
+// it every `--checkpoint-interval` seconds and once more when the workers
+// have exited. Ctrl-C sets `stop`, so workers drop out mid-chunk, record
+// what they finished and the final checkpoint is written as usual.
+// `--resume` rebuilds the work queue from the ranges not yet searched:
+//
+//   cargo run --release -- --smallest --checkpoint run.ckpt
+//   ^C
+//   cargo run --release -- --resume run.ckpt
+//
+// Progress: while the search runs, a reporter thread prints the current
+// hashrate, per-worker hash counts, chunks left in the queue, solutions so
+// far and an ETA for the range to stderr every `--progress` seconds (or
+// JSON lines with `--progress-format json`). The final report adds a
+// per-worker breakdown to show how evenly the queue spread the work.
+//
+// Budgets: `--timeout SECS` and `--max-hashes N` set `stop` once the wall
+// clock or the hash count runs out, and SIGINT/SIGTERM do the same (a second
+// signal quits at once). The run then prints what it has, a `status=` line
+// saying whether it finished, timed out, hit the hash limit or was
+// interrupted, and how much of the range was searched. An unfinished run
+// exits with 124 (budget) or 130 (signal); with `--checkpoint` it can be
+// continued with `--resume`.
+//
 // The main thread acts as the collector:
 //   - Receives solutions from workers.
 //   - Once K solutions are collected, sets stop=true.
 //   - Joins all workers.
 //   - Prints deterministic output + performance stats.
 //
+// The queue, workers and collector live in the `pow-core` crate
+// (`pow_core::search`, schedules `CentralQueue` and `WorkStealing`), where
+// lab-3's static range split and other tools can share them. This program
+// turns its configuration into a `PowSearch` and prints the `PowReport`.
+//
+// =====================================================================================
+// Configuration
+// =====================================================================================
+// All parameters (prefix, difficulty, k, nonce range, threads, chunk size,
+// queue mode) can be set from the command line or a `key = value` config
+// file; see `config.rs` or run with `--help`. For example:
+//
+//   cargo run --release -- --difficulty 4 --threads 4 --mode stealing
+//   cargo run --release -- --config fast.conf --k 3
+//
+// `--block-header` swaps `prefix:nonce` for a Bitcoin-style block header
+// with the nonce (and, past 2^32, an extra nonce) filled in:
+//
+//   cargo run --release -- --block-header --algorithm sha256d --difficulty 0x1f00ffff
+//
+// A run's printed output (or `--json FILE`) can be re-checked independently:
+//
+//   cargo run --release -- --difficulty 4 > out.txt
+//   cargo run --release -- verify out.txt
+//
+// The same queue can be spread over processes (`pow_core::net`): `serve`
+// runs a job server, `work` connects hashing threads to it, and
+// `--server ADDR` submits the search there instead of running it here:
+//
+//   cargo run --release -- serve &
+//   cargo run --release -- work --threads 4 &
+//   cargo run --release -- --server 127.0.0.1:7878 --difficulty 5 --smallest
+//
 // =====================================================================================
 // Correctness requirements
 // =====================================================================================
-// - Never hold the queue lock while hashing.
+// - Never hash a chunk before its claim on the cursor has succeeded.
 // - Never mutate shared structures without synchronization.
 // - Stop should cause workers to exit promptly.
 // - Output must be deterministic: sort by nonce, truncate to K.
 //
 // =====================================================================================
 
-use std::collections::VecDeque;
+mod config;
+
+use std::fs;
+use std::path::Path;
+use std::process;
 use std::sync::{
-    atomic::{AtomicBool, AtomicU64, Ordering},
-    mpsc,
-    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+    Arc,
 };
 use std::thread;
-use std::time::Instant;
 
-use sha2::{Digest, Sha256};
+use config::{Command, Config, QueueMode, ServeArgs, WorkArgs};
+use pow_core::net::{self, Job};
+use pow_core::telemetry;
+use pow_core::{
+    BlockHeader, Checkpoint, ChunkSize, Options, PowReport, PowSearch, ReportedSolution, Schedule,
+    SearchConfig, SolutionSet, Status,
+};
 
-#[derive(Clone, Debug)]
-struct Solution {
-    nonce: u64,
-    hash_hex: String,
+/// `serve` subcommand: run a job server until the process is killed.
+fn run_serve(args: &ServeArgs) -> i32 {
+    let server = match net::Server::bind(&args.listen, args.chunk_size) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("error: cannot listen on `{}`: {}", args.listen, e);
+            return 2;
+        }
+    };
+    if let Ok(addr) = server.local_addr() {
+        eprintln!("listening on {} (chunk_size={})", addr, args.chunk_size);
+    }
+    match server.run() {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
 }
 
-/// A work item is a chunk of nonces: [start, start+len).
-#[derive(Clone, Copy, Debug)]
-struct Work {
-    start: u64,
-    len: u64,
-}
+/// `work` subcommand: hash chunks for a server on `args.threads`
+/// connections until it shuts down.
+fn run_work(args: &WorkArgs) -> i32 {
+    let workers: Vec<_> = (0..args.threads)
+        .map(|_| {
+            let server = args.server.clone();
+            thread::spawn(move || net::work(server))
+        })
+        .collect();
 
-impl Work {
-    fn end_exclusive(self) -> u64 {
-        self.start.saturating_add(self.len)
+    let (mut chunks, mut hashes, mut failed) = (0, 0, false);
+    for worker in workers {
+        match worker.join().expect("worker thread panicked") {
+            Ok(report) => {
+                chunks += report.chunks;
+                hashes += report.hashes;
+            }
+            Err(e) => {
+                eprintln!("error: `{}`: {}", args.server, e);
+                failed = true;
+            }
+        }
     }
+    println!("server={} chunks={} hashes={}", args.server, chunks, hashes);
+    if failed { 1 } else { 0 }
 }
 
-/// Compute SHA-256(prefix + ":" + nonce) and return lowercase hex.
-fn sha256_hex(prefix: &str, nonce: u64) -> String {
-    let mut h = Sha256::new();
-    h.update(prefix.as_bytes());
-    h.update(b":");
-    h.update(nonce.to_string().as_bytes());
-    hex::encode(h.finalize())
-}
+/// Run the search `cfg` describes on the job server at `addr`, printing
+/// the same report as a local run minus the per-worker statistics.
+fn run_remote(cfg: &Config, addr: &str) -> i32 {
+    let search = &cfg.search;
+    let job = Job {
+        prefix: search.prefix.clone(),
+        difficulty: search.difficulty,
+        algorithm: search.algorithm,
+        header: cfg.block_header(),
+        k: search.k,
+        start: search.start,
+        end: search.end,
+        smallest: search.smallest,
+    };
+    let result = match net::submit(addr, &job) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("error: `{}`: {}", addr, e);
+            return 1;
+        }
+    };
+
+    let hashrate = if result.elapsed_ms > 0 {
+        result.hashes as f64 * 1000.0 / result.elapsed_ms as f64
+    } else {
+        0.0
+    };
+    println!(
+        "prefix={} threads={} difficulty={} target={} algorithm={}",
+        search.prefix, search.threads, search.difficulty, search.k, search.algorithm
+    );
+    println!("range={}..{}", search.start, search.end);
+    if let Some(header) = &job.header {
+        println!("header={}", header);
+    }
+    println!(
+        "server={} job={} smallest={} hashes={} time_ms={} hashrate={:.0}/s",
+        addr, result.job_id, search.smallest, result.hashes, result.elapsed_ms, hashrate
+    );
+    println!("solutions:");
+    for s in &result.solutions {
+        print_solution(s.nonce, &s.hash, job.header.as_ref());
+    }
+    if result.solutions.len() < search.k {
+        println!(
+            "WARNING: only got {} solutions; lower difficulty or increase end range.",
+            result.solutions.len()
+        );
+    }
 
-/// Difficulty check: hash hex begins with `difficulty` leading '0' characters.
-fn meets_difficulty(hash_hex: &str, difficulty: usize) -> bool {
-    hash_hex
-        .as_bytes()
-        .iter()
-        .take(difficulty)
-        .all(|&c| c == b'0')
+    if let Some(path) = &search.json {
+        let set = SolutionSet {
+            prefix: search.prefix.clone(),
+            difficulty: search.difficulty,
+            algorithm: search.algorithm,
+            header: job.header,
+            k: search.k,
+            start: search.start,
+            end: search.end,
+            solutions: result.solutions,
+        };
+        if let Err(e) = fs::write(path, set.to_json()) {
+            eprintln!("error: cannot write `{}`: {}", path, e);
+            return 1;
+        }
+    }
+    0
 }
 
-/// Split [start, end) into many small chunks to populate the work queue.
-/// Smaller chunk_size => better load balance, more queue contention.
-/// Larger chunk_size => less contention, worse early-stop responsiveness.
-fn make_initial_work(start: u64, end: u64, chunk_size: u64) -> VecDeque<Work> {
-    let mut q = VecDeque::new();
-    let mut cur = start;
-    while cur < end {
-        let remaining = end - cur;
-        let len = remaining.min(chunk_size);
-        q.push_back(Work { start: cur, len });
-        cur += len;
+/// One `solutions:` line. Header searches also show how the nonce splits
+/// into the extra nonce and the header's own nonce field.
+fn print_solution(nonce: u64, hash: &str, header: Option<&BlockHeader>) {
+    match header {
+        Some(header) => {
+            let (extra, header_nonce) = header.split_nonce(nonce);
+            println!(
+                "nonce={} hash={} extra_nonce={} header_nonce={}",
+                nonce, hash, extra, header_nonce
+            );
+        }
+        None => println!("nonce={} hash={}", nonce, hash),
     }
-    q
 }
 
-/// Worker logic: pull work from the global queue, process, report solutions.
-///
-/// You must implement the protocol described in the comments.
-/// Do not use unsafe code.
-fn worker_loop(
-    id: usize,
-    prefix: String,
-    difficulty: usize,
-    work_queue: Arc<Mutex<VecDeque<Work>>>,
-    sol_tx: mpsc::Sender<Solution>,
-    stop: Arc<AtomicBool>,
-    hashes: Arc<AtomicU64>,
-) {
-    // ---------------------------------------------------------------------------------
-    // Worker protocol checklist
-    // ---------------------------------------------------------------------------------
-    // Repeat:
-    //
-    //   A) If stop flag is set, exit the worker.
-    //
-    //   B) Take ONE Work chunk from the global queue:
-    //        - lock the queue
-    //        - pop a chunk (front)
-    //        - unlock immediately
-    //
-    //   C) If no work remains (pop returned None), exit the worker.
-    //
-    //   D) Process the chunk locally:
-    //        - iterate through the nonce range
-    //        - occasionally check stop flag, exit early if needed
-    //        - compute hash for each nonce
-    //        - increment shared hash counter
-    //        - if solution found, send it to collector (sol_tx)
-    //
-    //   E) Loop back to get another chunk.
-    //
-    // Notes:
-    // - The lock around the queue must be held only briefly.
-    // - The channel send is how you report results.
-    // - Atomics are used only for stop/counter; results are not stored in shared memory.
-    // ---------------------------------------------------------------------------------
-
-    // TODO: Implement the worker protocol described above.
-    //
-    // Remove the panic once implemented.
-    panic!("TODO: worker_loop not implemented");
+/// Stealing-mode chunk length for `--chunk-size auto`.
+const STEALING_CHUNK_SIZE: u64 = 50_000;
+
+/// The search `cfg` describes.
+fn pow_search(cfg: &Config) -> PowSearch {
+    let chunk_size = cfg.chunk_size;
+    let schedule = match (cfg.mode, chunk_size) {
+        (QueueMode::Central, _) => Schedule::CentralQueue { chunk_size },
+        // Stealing deals out every chunk up front, so it needs a size.
+        (QueueMode::Stealing, ChunkSize::Fixed(n)) => Schedule::WorkStealing { chunk_size: n },
+        (QueueMode::Stealing, ChunkSize::Adaptive) => Schedule::WorkStealing {
+            chunk_size: STEALING_CHUNK_SIZE,
+        },
+    };
+    let search = cfg.search.pow_search(schedule);
+    match cfg.block_header() {
+        Some(header) => search.header(header),
+        None => search,
+    }
 }
 
 fn main() {
     // =================================================================================
-    // Parameters (adjustable)
+    // Parameters (command line / config file)
     // =================================================================================
     // If difficulty is too high, you may not find k solutions in [start, end).
-    // For early testing, set difficulty to 3 or 4.
-    let prefix = "cmkl-pow".to_string();
-    let difficulty: usize = 6;
-    let k: usize = 10;
+    // For early testing, pass `--difficulty 3` or `--difficulty 4`.
+    let mut cfg = match config::parse_args(std::env::args().skip(1)) {
+        Ok(Command::Run(cfg)) => cfg,
+        Ok(Command::Verify(args)) => process::exit(pow_core::run_verify(&args)),
+        Ok(Command::Serve(args)) => process::exit(run_serve(&args)),
+        Ok(Command::Work(args)) => process::exit(run_work(&args)),
+        Ok(Command::Help) => {
+            println!("{}", config::USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, config::USAGE);
+            process::exit(2);
+        }
+    };
 
-    let start: u64 = 0;
-    let end: u64 = 50_000_000_000;
+    if let Some(addr) = &cfg.server {
+        process::exit(run_remote(&cfg, addr));
+    }
 
-    let threads: usize = 8;
-    let chunk_size: u64 = 50_000;
+    // Resuming: the checkpoint decides what is being searched.
+    let resume = cfg.search.resume.clone().map(|path| {
+        let ckpt = Checkpoint::load(Path::new(&path)).unwrap_or_else(|e| {
+            eprintln!("error: `{}`: {}", path, e);
+            process::exit(2);
+        });
+        cfg.search.resume_from(&ckpt);
+        cfg.block_header = ckpt.header.is_some();
+        if let Some(header) = ckpt.header {
+            cfg.header = header;
+            cfg.bits = Some(header.bits);
+        }
+        if let Err(e) = cfg.validate() {
+            eprintln!("error: `{}`: {}", path, e);
+            process::exit(2);
+        }
+        eprintln!(
+            "resuming `{}`: {} of {} nonces searched, {} solution(s) so far",
+            path,
+            ckpt.completed.count(),
+            ckpt.end - ckpt.start,
+            ckpt.solutions.len()
+        );
+        ckpt
+    });
 
-    // =================================================================================
-    // Shared state
-    // =================================================================================
+    // Ctrl-C (SIGINT) and SIGTERM stop the workers through `stop`, so the
+    // solutions so far are still reported and the final checkpoint is
+    // still written. A second Ctrl-C quits at once.
     let stop = Arc::new(AtomicBool::new(false));
-    let hashes = Arc::new(AtomicU64::new(0));
-
-    let initial_queue = make_initial_work(start, end, chunk_size);
-    let work_queue = Arc::new(Mutex::new(initial_queue));
-
-    let t0 = Instant::now();
-
-    // =================================================================================
-    // TODO 1: Channel setup
-    // =================================================================================
-    // Create a channel for Solution messages.
-    //
-    // Requirements:
-    // - The main thread must own the Receiver<Solution>.
-    // - Each worker thread must have a Sender<Solution>.
-    // - The Sender must be cloned for each worker.
-    //
-    // Hint: use std::sync::mpsc (already imported).
-    //
-    // TODO: create (sol_tx, sol_rx)
-    //
-    // let (sol_tx, sol_rx) = ...
-    panic!("TODO 1: create solution channel");
+    {
+        let stop = Arc::clone(&stop);
+        let handler = move || {
+            if stop.swap(true, Ordering::Relaxed) {
+                process::exit(130);
+            }
+            eprintln!("stopping; press Ctrl-C again to quit at once");
+        };
+        if let Err(e) = ctrlc::set_handler(handler) {
+            eprintln!("warning: cannot install Ctrl-C handler: {}", e);
+        }
+    }
 
     // =================================================================================
-    // TODO 2: Spawn and manage worker threads
+    // Search (shared state, workers and collector live in `pow_core::search`)
     // =================================================================================
-    // Spawn `threads` worker threads and store JoinHandles in a Vec.
-    //
-    // Requirements:
-    // - Each worker gets:
-    //     * an id
-    //     * prefix.clone()
-    //     * difficulty
-    //     * Arc clones of work_queue, stop, hashes
-    //     * a cloned sender endpoint sol_tx.clone()
-    //
-    // - You MUST store the JoinHandle so you can join later.
-    //
-    // - After spawning workers, the main thread should drop its copy of sol_tx.
-    //   This ensures the receiver can observe channel closure when workers exit.
-    //
-    // TODO: create a Vec<JoinHandle<()>> and spawn threads
-    //
-    // let mut handles = Vec::new();
-    // for id in 0..threads { ... }
-    // drop(sol_tx);
-    panic!("TODO 2: spawn workers");
+    let mut search = pow_search(&cfg).stop_flag(stop);
+    if let Some(ckpt) = resume {
+        search = search.resume(ckpt);
+    }
+    let report = search.run();
+    let header = cfg.block_header();
+    let queue_stats = report.totals();
+    let per_worker = report.per_thread_hashes();
+    let PowReport {
+        solutions,
+        hashes: total_hashes,
+        elapsed,
+        searched,
+        status,
+        ..
+    } = report;
+    let Config {
+        search:
+            SearchConfig {
+                prefix,
+                difficulty,
+                algorithm,
+                k,
+                start,
+                end,
+                threads,
+                smallest,
+                json,
+                timeout,
+                max_hashes,
+                checkpoint,
+                checkpoint_interval: _,
+                resume: _,
+                progress: _,
+                progress_format: _,
+            },
+        chunk_size,
+        mode,
+        server: _,
+        block_header: _,
+        header: _,
+        bits: _,
+    } = cfg;
 
     // =================================================================================
-    // TODO 3: Collector logic (main thread)
-    // =================================================================================
-    // In the main thread:
-    //
-    // A) Receive solutions until you have collected k solutions (or channel closes).
-    //
-    // B) Once you have k solutions:
-    //    - Set stop=true (Relaxed is fine here).
-    //
-    // C) Join all worker threads.
-    //
-    // D) Use the provided final reporting code below.
-    //
-    // TODO: implement collector logic and joining.
-    //
-    let mut solutions: Vec<Solution> = Vec::new();
-
-    panic!("TODO 3: collector logic");
-    
-    // =================================================================================
-    // Final reporting (provided)
+    // Final reporting
     // =================================================================================
-    // NOTE: You should reach this point only after:
-    // - solutions is collected
-    // - stop is set
-    // - all workers joined
-    //
-    // Sort solutions by nonce to make output deterministic
-    solutions.sort_by_key(|s| s.nonce);
-
-    // Truncate in case more than k solutions arrived concurrently
-    if solutions.len() > k {
-        solutions.truncate(k);
-    }
+    // `run` returns once every worker has been joined, with the solutions
+    // already sorted by nonce and truncated to k.
 
     // Performance statistics
-    let total_hashes = hashes.load(Ordering::Relaxed);
-    let elapsed = t0.elapsed();
     let secs = elapsed.as_secs_f64();
     let hashrate = if secs > 0.0 {
         (total_hashes as f64) / secs
@@ -297,9 +474,13 @@ fn main() {
     };
 
     println!(
-        "prefix={} threads={} difficulty={} target={}",
-        prefix, threads, difficulty, k
+        "prefix={} threads={} difficulty={} target={} algorithm={}",
+        prefix, threads, difficulty, k, algorithm
     );
+    println!("range={}..{}", start, end);
+    if let Some(header) = &header {
+        println!("header={}", header);
+    }
 
     println!(
         "chunk_size={} hashes={} time_ms={} hashrate={:.0}/s",
@@ -309,15 +490,276 @@ fn main() {
         hashrate
     );
 
+    println!(
+        "mode={} smallest={} chunks={} skipped={} cursor_retries={} steals={}",
+        mode.name(),
+        smallest,
+        queue_stats.chunks,
+        queue_stats.skipped,
+        queue_stats.cursor_retries,
+        queue_stats.steals
+    );
+    // Per-worker hashes, to spot load imbalance.
+    println!("{}", telemetry::breakdown(&per_worker));
+    println!("status={} searched={}", status, searched);
+
     println!("solutions:");
     for s in solutions.iter() {
-        println!("nonce={} hash={}", s.nonce, s.hash_hex);
+        print_solution(s.nonce, &hex::encode(s.hash), header.as_ref());
     }
 
+    if status != Status::Finished {
+        // Not an answer yet, so no JSON copy either.
+        let why = match status {
+            Status::TimedOut => format!("TIMED OUT after {}s", timeout),
+            Status::HashLimit => format!("HASH LIMIT of {} reached", max_hashes),
+            _ => "INTERRUPTED".to_string(),
+        };
+        let next = match &checkpoint {
+            Some(path) => format!("continue with --resume {}", path),
+            None => "pass --checkpoint FILE to be able to continue".to_string(),
+        };
+        println!(
+            "{}: searched {} of {} nonces, {} solution(s) so far; {}",
+            why,
+            searched,
+            end - start,
+            solutions.len(),
+            next
+        );
+        process::exit(if status == Status::Interrupted { 130 } else { 124 });
+    }
     if solutions.len() < k {
         println!(
             "WARNING: only got {} solutions; lower difficulty or increase end range.",
             solutions.len()
         );
     }
+
+    // Machine-readable copy for `verify` and other tools.
+    if let Some(path) = json {
+        let set = SolutionSet {
+            prefix,
+            difficulty,
+            algorithm,
+            header,
+            k,
+            start,
+            end,
+            solutions: solutions
+                .iter()
+                .map(|s| ReportedSolution::new(s.nonce, &s.hash))
+                .collect(),
+        };
+        if let Err(e) = fs::write(&path, set.to_json()) {
+            eprintln!("error: cannot write `{}`: {}", path, e);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pow_core::{Algorithm, Difficulty, NonceHasher, PrefixHasher, RangeSet};
+    use std::time::Duration;
+
+    fn search(cfg: &Config, stop: Arc<AtomicBool>, resume: Option<&Checkpoint>) -> PowReport {
+        let mut search = pow_search(cfg).stop_flag(stop);
+        if let Some(ckpt) = resume {
+            search = search.resume(ckpt.clone());
+        }
+        search.run()
+    }
+
+    fn small_run(threads: usize, mode: QueueMode) -> Config {
+        Config {
+            search: SearchConfig {
+                difficulty: Difficulty::hex_digits(3),
+                k: 8,
+                end: 1_000_000,
+                threads,
+                smallest: true,
+                ..Config::default().search
+            },
+            chunk_size: ChunkSize::Fixed(500),
+            mode,
+            ..Config::default()
+        }
+    }
+
+    fn nonces(outcome: &PowReport) -> Vec<u64> {
+        outcome.solutions.iter().map(|s| s.nonce).collect()
+    }
+
+    #[test]
+    fn smallest_mode_is_independent_of_thread_count() {
+        let cfg = small_run(1, QueueMode::Central).search;
+        let hasher = PrefixHasher::new(&cfg.prefix);
+        let expected: Vec<u64> = (cfg.start..cfg.end)
+            .filter(|&n| cfg.difficulty.meets(&hasher.digest(n)))
+            .take(cfg.k)
+            .collect();
+
+        for mode in [QueueMode::Central, QueueMode::Stealing] {
+            for threads in [1, 16] {
+                let outcome = search(&small_run(threads, mode), Default::default(), None);
+                assert_eq!(nonces(&outcome), expected, "{mode:?} threads={threads}");
+                for s in &outcome.solutions {
+                    assert_eq!(s.hash, hasher.digest(s.nonce));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn default_mode_still_returns_k_valid_solutions() {
+        let mut cfg = small_run(4, QueueMode::Central);
+        cfg.search.smallest = false;
+        let outcome = search(&cfg, Default::default(), None);
+        assert_eq!(outcome.solutions.len(), cfg.search.k);
+        assert_eq!(outcome.threads.len(), cfg.search.threads);
+        assert_eq!(outcome.totals().hashes, outcome.hashes);
+        assert!(
+            outcome
+                .solutions
+                .iter()
+                .all(|s| cfg.search.difficulty.meets(&s.hash))
+        );
+        assert!(
+            outcome
+                .solutions
+                .windows(2)
+                .all(|w| w[0].nonce < w[1].nonce)
+        );
+    }
+
+    fn expected(cfg: &Config) -> Vec<u64> {
+        let header = cfg.block_header();
+        let cfg = &cfg.search;
+        let hasher = NonceHasher::new(cfg.algorithm, &cfg.prefix, header.as_ref());
+        (cfg.start..cfg.end)
+            .filter(|&n| cfg.difficulty.meets(&hasher.digest(n)))
+            .take(cfg.k)
+            .collect()
+    }
+
+    #[test]
+    fn every_algorithm_finds_the_same_nonces_as_brute_force() {
+        for algorithm in Algorithm::ALL {
+            let mut cfg = small_run(3, QueueMode::Stealing);
+            cfg.search.algorithm = algorithm;
+            cfg.search.end = 200_000;
+            let outcome = search(&cfg, Default::default(), None);
+            assert_eq!(nonces(&outcome), expected(&cfg), "{algorithm}");
+        }
+    }
+
+    #[test]
+    fn auto_chunk_size_works_in_both_modes() {
+        for mode in [QueueMode::Central, QueueMode::Stealing] {
+            let cfg = Config {
+                chunk_size: ChunkSize::Adaptive,
+                ..small_run(4, mode)
+            };
+            let outcome = search(&cfg, Default::default(), None);
+            assert_eq!(nonces(&outcome), expected(&cfg), "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn block_header_mode_rolls_over_in_both_modes() {
+        for mode in [QueueMode::Central, QueueMode::Stealing] {
+            let mut cfg = Config {
+                block_header: true,
+                ..small_run(4, mode)
+            };
+            cfg.search.algorithm = Algorithm::DoubleSha256;
+            cfg.search.start = (1 << 32) - 10_000;
+            cfg.search.end = (1 << 32) + 1_000_000;
+            let want = expected(&cfg);
+            assert!(want[0] < 1 << 32 && want[7] >= 1 << 32, "{want:?}");
+            let outcome = search(&cfg, Default::default(), None);
+            assert_eq!(nonces(&outcome), want, "{mode:?}");
+        }
+    }
+
+    fn temp_checkpoint(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("a2-{}-{}.ckpt", name, process::id()));
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn resume_searches_only_the_gaps() {
+        let run = small_run(4, QueueMode::Stealing);
+        let want = expected(&run);
+        let cfg = &run.search;
+        let hasher = PrefixHasher::new(&cfg.prefix);
+
+        // Pretend an earlier run finished [0, 20_000) and one stray chunk.
+        let mut completed = RangeSet::new();
+        completed.insert(0, 20_000);
+        completed.insert(50_000, 50_500);
+        let ckpt = Checkpoint {
+            prefix: cfg.prefix.clone(),
+            difficulty: cfg.difficulty,
+            algorithm: cfg.algorithm,
+            header: None,
+            k: cfg.k,
+            start: cfg.start,
+            end: cfg.end,
+            smallest: true,
+            solutions: want
+                .iter()
+                .filter(|&&n| completed.contains(n))
+                .map(|&n| ReportedSolution::new(n, &hasher.digest(n)))
+                .collect(),
+            completed,
+            hashes: 20_500,
+        };
+        assert!(!ckpt.solutions.is_empty() && ckpt.solutions.len() < cfg.k);
+
+        let outcome = search(&run, Default::default(), Some(&ckpt));
+        assert_eq!(nonces(&outcome), want);
+        assert!(outcome.finished());
+        // Nothing in the completed ranges is hashed again.
+        assert!(outcome.hashes <= outcome.searched - 20_500);
+    }
+
+    #[test]
+    fn interrupted_run_resumes_to_the_same_answer() {
+        let path = temp_checkpoint("interrupt");
+        let mut cfg = small_run(2, QueueMode::Central);
+        cfg.search.checkpoint = Some(path.clone());
+        let want = expected(&cfg);
+
+        // Already stopped: the workers exit at once and the checkpoint
+        // records no progress.
+        let outcome = search(&cfg, Arc::new(AtomicBool::new(true)), None);
+        assert_eq!(outcome.status, Status::Interrupted);
+        let ckpt = Checkpoint::load(Path::new(&path)).unwrap();
+        assert_eq!(ckpt.remaining(), vec![(cfg.search.start, cfg.search.end)]);
+
+        // Stop part-way through, possibly mid-chunk.
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopper = {
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(5));
+                stop.store(true, Ordering::Relaxed);
+            })
+        };
+        search(&cfg, stop, None);
+        stopper.join().unwrap();
+
+        let ckpt = Checkpoint::load(Path::new(&path)).unwrap();
+        let outcome = search(&cfg, Default::default(), Some(&ckpt));
+        assert_eq!(nonces(&outcome), want);
+        assert!(outcome.finished());
+
+        let done = Checkpoint::load(Path::new(&path)).unwrap();
+        assert_eq!(done.solutions.len(), cfg.search.k);
+        assert!(done.hashes >= ckpt.hashes);
+        fs::remove_file(&path).unwrap();
+    }
 }
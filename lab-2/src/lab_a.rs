@@ -27,6 +27,17 @@ struct BoxNode {
     next: Option<Box<BoxNode>>,
 }
 
+impl Drop for BoxNode {
+    /// Drop the rest of the chain iteratively so long chains cannot
+    /// overflow the stack.
+    fn drop(&mut self) {
+        let mut cur = self.next.take();
+        while let Some(mut node) = cur {
+            cur = node.next.take();
+        }
+    }
+}
+
 fn demo_box_nodes_and_mutation() {
     println!("\n-- Lab A1: Box nodes (exclusive ownership) + mutation --");
 
@@ -81,6 +92,17 @@ struct RcNode {
     next: Option<Rc<RcNode>>,
 }
 
+impl Drop for RcNode {
+    /// Drop the rest of the chain iteratively, stopping at the first node
+    /// that someone else still holds (it stays alive for them).
+    fn drop(&mut self) {
+        let mut cur = self.next.take();
+        while let Some(node) = cur {
+            cur = Rc::try_unwrap(node).ok().and_then(|mut n| n.next.take());
+        }
+    }
+}
+
 fn demo_rc_nodes_refcount_outlives_scope() {
     println!("\n-- Lab A2: Rc nodes (shared ownership) + strong_count --");
 
@@ -145,3 +167,53 @@ fn clone_b_and_print_counts(node_b: &Rc<RcNode>) -> Rc<RcNode> {
     );
     external
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LONG_CHAIN: usize = 5_000_000;
+
+    #[test]
+    fn dropping_a_long_box_chain_does_not_overflow_the_stack() {
+        let mut head: Option<Box<BoxNode>> = None;
+        for _ in 0..LONG_CHAIN {
+            head = Some(Box::new(BoxNode {
+                value: String::new(),
+                next: head,
+            }));
+        }
+        drop(head);
+    }
+
+    #[test]
+    fn dropping_a_long_rc_chain_does_not_overflow_the_stack() {
+        let mut head: Option<Rc<RcNode>> = None;
+        for _ in 0..LONG_CHAIN {
+            head = Some(Rc::new(RcNode {
+                value: String::new(),
+                next: head,
+            }));
+        }
+        drop(head);
+    }
+
+    #[test]
+    fn dropping_an_rc_chain_keeps_shared_tail_alive() {
+        let tail = Rc::new(RcNode {
+            value: "C".to_string(),
+            next: None,
+        });
+        let head = Rc::new(RcNode {
+            value: "A".to_string(),
+            next: Some(Rc::new(RcNode {
+                value: "B".to_string(),
+                next: Some(tail.clone()),
+            })),
+        });
+
+        drop(head);
+        assert_eq!(Rc::strong_count(&tail), 1);
+        assert_eq!(tail.value, "C");
+    }
+}
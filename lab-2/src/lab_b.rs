@@ -26,6 +26,19 @@ struct Node {
     next: Option<NodeRef>,
 }
 
+impl Drop for Node {
+    /// Drop the rest of the chain iteratively, stopping at the first node
+    /// that is still shared.
+    fn drop(&mut self) {
+        let mut cur = self.next.take();
+        while let Some(node) = cur {
+            cur = Rc::try_unwrap(node)
+                .ok()
+                .and_then(|mut cell| cell.get_mut().next.take());
+        }
+    }
+}
+
 fn build_chain() -> (NodeRef, NodeRef, NodeRef) {
     // Build A -> B -> C
     let c = Rc::new(RefCell::new(Node {
@@ -120,3 +133,31 @@ fn double_borrow_demo(node: &NodeRef) {
         println!("No panic occurred.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropping_a_long_refcell_chain_does_not_overflow_the_stack() {
+        let mut head: Option<NodeRef> = None;
+        for _ in 0..5_000_000 {
+            head = Some(Rc::new(RefCell::new(Node {
+                value: String::new(),
+                next: head,
+            })));
+        }
+        drop(head);
+    }
+
+    #[test]
+    fn dropping_the_head_keeps_externally_held_nodes_alive() {
+        let (a, b, c) = build_chain();
+        drop(a);
+        assert_eq!(Rc::strong_count(&b), 1);
+        assert_eq!(
+            b.borrow().next.as_ref().map(Rc::as_ptr),
+            Some(Rc::as_ptr(&c))
+        );
+    }
+}
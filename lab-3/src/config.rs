@@ -0,0 +1,123 @@
+// Command-line and config-file parameters for the PoW search.
+//
+// The parsing (defaults < `--config FILE` < flags, `key = value` files,
+// `--resume`) lives in `pow_core::config`; this file only holds lab-3's
+// defaults and help text. lab-3 has no parameters beyond
+// `pow_core::SearchConfig`, so that is its configuration as is.
+
+use pow_core::Difficulty;
+use pow_core::config::{self, ConfigError, SearchConfig, VerifyArgs};
+
+pub const USAGE: &str = "\
+Usage: lab-3 [OPTIONS]
+       lab-3 verify [FILE|-] [--valid-only] [--threads N]
+
+Options:
+  --config <FILE>       read `key = value` parameters from FILE first
+  --prefix <STR>        candidate prefix            [default: cmkl-lab]
+  --difficulty <D>      N hex zeros, Nb zero bits,  [default: 7]
+                        0xNBITS or 64-hex target
+  --algorithm <H>       sha256 | sha256d | sha512-256 | blake2b |
+                        blake2s | blake3            [default: sha256]
+  --k <N>               solutions to collect        [default: 3]
+  --start <N>           first nonce (inclusive)     [default: 0]
+  --end <N>             last nonce (exclusive)      [default: 100_000_000_000]
+  --threads <N>         worker threads              [default: 8]
+  --smallest            report exactly the k smallest valid nonces
+                        (keep searching below the k-th best found)
+  --json <FILE>         also write the solutions to FILE as JSON
+  --timeout <SECS>      stop after SECS seconds, 0 for no limit
+                        (exit code 124)             [default: 0]
+  --max-hashes <N>      stop after about N hashes, 0 for no limit
+                        (exit code 124)             [default: 0]
+  --checkpoint <FILE>   save progress to FILE periodically and on Ctrl-C
+  --checkpoint-interval <SECS>
+                        seconds between checkpoints [default: 30]
+  --resume <FILE>       continue the search saved in checkpoint FILE
+  --progress <SECS>     print progress to stderr every SECS seconds,
+                        0 to disable                [default: 5]
+  --progress-format <F> text | json (one object per line) [default: text]
+  -h, --help            print this help
+
+verify: re-check a run's printed output or JSON file (stdin if FILE is
+omitted or `-`): every hash, the difficulty, and that the list is the k
+smallest valid nonces in the range. Exits 1 on any discrepancy.
+  --valid-only          skip the k-smallest rescan
+  --threads <N>         threads for the rescan      [default: all cores]";
+
+/// The values this lab always used.
+pub fn defaults() -> SearchConfig {
+    SearchConfig {
+        prefix: "cmkl-lab".to_string(),
+        difficulty: Difficulty::hex_digits(7),
+        k: 3,
+        end: 100_000_000_000,
+        ..SearchConfig::default()
+    }
+}
+
+/// What the command line asked for.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Run(SearchConfig),
+    Verify(VerifyArgs),
+    Help,
+}
+
+/// Parse the command line (without the program name).
+pub fn parse_args<I>(args: I) -> Result<Command, ConfigError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter().peekable();
+    if args.peek().is_some_and(|a| a == "verify") {
+        args.next();
+        return Ok(config::parse_verify_args(args)?.map_or(Command::Help, Command::Verify));
+    }
+    Ok(config::parse_options(defaults(), args)?.map_or(Command::Help, Command::Run))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn no_flags_gives_the_lab_defaults() {
+        let Ok(Command::Run(cfg)) = parse_args(args("")) else {
+            panic!("expected a run");
+        };
+        assert_eq!(cfg, defaults());
+        assert_eq!(cfg.prefix, "cmkl-lab");
+        assert_eq!(cfg.k, 3);
+        assert_eq!(parse_args(args("--threads 2 --help")), Ok(Command::Help));
+
+        let Ok(Command::Run(cfg)) = parse_args(args("--k=2 --threads 4")) else {
+            panic!("expected a run");
+        };
+        assert_eq!((cfg.k, cfg.threads), (2, 4));
+        assert_eq!(cfg.difficulty, Difficulty::hex_digits(7));
+    }
+
+    #[test]
+    fn verify_subcommand() {
+        let Ok(Command::Verify(v)) = parse_args(args("verify out.txt --valid-only --threads=3"))
+        else {
+            panic!("expected verify");
+        };
+        assert_eq!(v.input.as_deref(), Some("out.txt"));
+        assert!(v.valid_only);
+        assert_eq!(v.threads, 3);
+
+        assert_eq!(parse_args(args("verify --help")), Ok(Command::Help));
+        assert!(parse_args(args("verify --difficulty 3")).is_err());
+        // Only the run flags know about modes this lab does not have.
+        assert_eq!(
+            parse_args(args("--mode stealing")),
+            Err(ConfigError::UnknownKey("mode".to_string()))
+        );
+    }
+}
@@ -7,12 +7,47 @@
 //
 //   hash_hex = SHA256(prefix + ":" + nonce)
 //
+// (or another hash chosen with `--algorithm`, e.g. sha256d or blake3)
+//
 // is "good enough" under a simple difficulty rule:
 //   - the hex string begins with `d` leading '0' characters.
 //
+// `d` may also be given in leading zero bits (`--difficulty 22b`) or as a
+// 256-bit target `hash < target` (`--difficulty 0x1e00ffff`, Bitcoin's
+// compact nBits form), for steps finer than 16x per hex digit.
+//
+// The loop checks this on the raw 32-byte digest instead of building
+// `hash_hex`, and only hex-encodes solutions for printing (see the
+// `pow-core` crate).
+//
+// With `--smallest`, a shared `bound` holds the k-th best nonce found so far
+// and threads stop once they pass it instead of all stopping at the first k
+// solutions, so the output is exactly the k smallest valid nonces in
+// [start, end) for any thread count. With static range splitting only the
+// thread holding the lowest slice does useful work until the bound drops
+// into its slice, so this mode trades away most of the parallel speedup.
+//
 // You will do this in parallel using `threads` OS threads.
 // Each thread searches a disjoint slice of the nonce range [start, end).
 //
+// While the threads run, a reporter prints the current hashrate, per-thread
+// hash counts, solutions so far and an ETA to stderr every `--progress`
+// seconds (JSON lines with `--progress-format json`); the final report adds
+// a per-thread breakdown, which shows how uneven a static split can get.
+//
+// With `--checkpoint FILE`, each thread adds what it has searched to a shared
+// set of completed ranges every `PROGRESS_INTERVAL` nonces (after publishing
+// any solution in them), and the main thread saves that set with the
+// solutions so far every `--checkpoint-interval` seconds and at the end.
+// Ctrl-C sets `stop`, so the final checkpoint is still written. `--resume
+// FILE` splits only the ranges not yet searched between the threads.
+//
+// Threads check `stop` once per nonce, so `--timeout SECS`, `--max-hashes N`
+// and SIGTERM end a run just as quickly: each sets `stop`, and the solutions
+// so far are printed with a `status=` line saying whether the run finished,
+// timed out, hit the hash limit or was interrupted. An unfinished run exits
+// with 124 (budget) or 130 (signal).
+//
 // =======================
 // Concurrency primitives
 // =======================
@@ -30,21 +65,28 @@
 //     - Must be protected by a Mutex because Vec is not safe to mutate concurrently.
 //
 // =======================
-// Your tasks (TODOs)
+// What this program does
 // =======================
-// You will implement ONLY these three TODOs inside the worker loop:
+// The worker loop lives in the `pow-core` crate (`pow_core::search`,
+// schedule `StaticSplit`), next to assignment-2's work queue. Each thread
+// checks `stop` before every nonce, counts every hash in `hashes`, and
+// pushes solutions into `results` under its lock; in the default mode the
+// run stops once there are k of them. This program reads the configuration, installs the
+// Ctrl-C handler, loads a checkpoint to resume from if asked, runs the
+// `PowSearch` and prints the `PowReport`.
 //
-// TODO 1: Early stop check
-//   - If stop is true, break out of the loop.
+// =======================
+// Configuration
+// =======================
+// Every parameter below can be set on the command line or in a
+// `key = value` config file (see `pow_core::config`, or run with `--help`):
 //
-// TODO 2: Count hashes
-//   - Increment `hashes` for every nonce tested.
+//   cargo run --release -- --difficulty 4 --threads 4
+//   cargo run --release -- --config fast.conf --k 5
+//   cargo run --release -- --checkpoint run.ckpt   (Ctrl-C, then --resume run.ckpt)
 //
-// TODO 3: Publish solutions safely
-//   - If a hash meets the difficulty requirement:
-//     - Lock `results`
-//     - If fewer than k solutions exist, push a new Solution
-//     - If you reach k solutions, set stop=true so other threads stop early
+// The printed output (or `--json FILE`) can be re-checked independently
+// with `cargo run --release -- verify out.txt`.
 //
 // =======================
 // Correctness requirements
@@ -54,34 +96,26 @@
 // - Output should be deterministic:
 //     - We sort solutions by nonce and truncate to k at the end.
 
+mod config;
+
+use std::fs;
+use std::path::Path;
+use std::process;
 use std::sync::{
-    Arc, Mutex,
-    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+    atomic::{AtomicBool, Ordering},
 };
-use std::thread;
-use std::time::Instant;
-
-use sha2::{Digest, Sha256};
-
-#[derive(Clone, Debug)]
-struct Solution {
-    nonce: u64,
-    hash_hex: String,
-}
 
-/// Compute SHA-256(prefix + ":" + nonce) and return hex string.
-fn sha256_hex(prefix: &str, nonce: u64) -> String {
-    let mut h = Sha256::new();
-    h.update(prefix.as_bytes());
-    h.update(b":");
-    h.update(nonce.to_string().as_bytes());
-    let digest = h.finalize();
-    hex::encode(digest)
-}
+use config::Command;
+use pow_core::telemetry;
+use pow_core::{
+    Checkpoint, Options, PowReport, PowSearch, ReportedSolution, Schedule, SearchConfig,
+    SolutionSet, Status,
+};
 
-/// Difficulty check: hash hex begins with `d` leading '0' characters.
-fn meets_difficulty(hash_hex: &str, d: usize) -> bool {
-    hash_hex.as_bytes().iter().take(d).all(|&c| c == b'0')
+/// The search `cfg` describes: a static split of the range over the threads.
+fn pow_search(cfg: &SearchConfig) -> PowSearch {
+    cfg.pow_search(Schedule::StaticSplit)
 }
 
 fn main() {
@@ -90,91 +124,93 @@ fn main() {
     // =======================
     // Feel free to change these when testing.
     // Start with smaller difficulty if your machine is slow:
-    //   d=3 or d=4 should produce results quickly.
-    let prefix = "cmkl-lab".to_string();
-    let d: usize = 7; // leading hex zeros
-    let k: usize = 3; // number of solutions to find
+    //   --difficulty 3 or 4 should produce results quickly.
+    let mut cfg = match config::parse_args(std::env::args().skip(1)) {
+        Ok(Command::Run(cfg)) => cfg,
+        Ok(Command::Verify(args)) => process::exit(pow_core::run_verify(&args)),
+        Ok(Command::Help) => {
+            println!("{}", config::USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, config::USAGE);
+            process::exit(2);
+        }
+    };
 
-    let start: u64 = 0;
-    let end: u64 = 100_000_000_000;
-    let threads: u64 = 8;
+    // Resuming: the checkpoint decides what is being searched.
+    let resume = cfg.resume.clone().map(|path| {
+        let ckpt = Checkpoint::load(Path::new(&path)).unwrap_or_else(|e| {
+            eprintln!("error: `{}`: {}", path, e);
+            process::exit(2);
+        });
+        cfg.resume_from(&ckpt);
+        if let Err(e) = cfg.validate() {
+            eprintln!("error: `{}`: {}", path, e);
+            process::exit(2);
+        }
+        eprintln!(
+            "resuming `{}`: {} of {} nonces searched, {} solution(s) so far",
+            path,
+            ckpt.completed.count(),
+            ckpt.end - ckpt.start,
+            ckpt.solutions.len()
+        );
+        ckpt
+    });
 
-    // =======================
-    // Shared state (Arc)
-    // =======================
+    // Ctrl-C (SIGINT) and SIGTERM stop the threads through `stop`, so the
+    // solutions so far are still reported and the final checkpoint is still
+    // written. A second Ctrl-C quits at once.
     let stop = Arc::new(AtomicBool::new(false));
-    let hashes = Arc::new(AtomicU64::new(0));
-    let results = Arc::new(Mutex::new(Vec::<Solution>::new()));
-
-    // =======================
-    // Range splitting
-    // =======================
-    // Each thread i searches:
-    //   [start + i*chunk, min(start + (i+1)*chunk, end))
-    let n = end - start;
-    let chunk = n.div_ceil(threads);
-
-    let t0 = Instant::now();
-    let mut handles = Vec::new();
-
-    for i in 0..threads {
-        let lo = start + i * chunk;
-        let hi = (lo + chunk).min(end);
-
-        let prefix = prefix.clone();
+    {
         let stop = Arc::clone(&stop);
-        let hashes = Arc::clone(&hashes);
-        let results = Arc::clone(&results);
-
-        handles.push(thread::spawn(move || {
-            for nonce in lo..hi {
-                // ---------------------------------------
-                // 1: Early stop check
-                // ---------------------------------------
-                if stop.load(Ordering::Relaxed) {
-                    break;
-                }
-
-                let hash_hex = sha256_hex(&prefix, nonce);
-
-                // ---------------------------------------
-                // 2: Count hashes
-                // ---------------------------------------
-                hashes.fetch_add(1, Ordering::Relaxed);
-
-                if meets_difficulty(&hash_hex, d) {
-                    // ---------------------------------------
-                    // 3: Publish solution safely
-                    // ---------------------------------------
-                    let mut res = results.lock().unwrap();
-
-                    if res.len() < k {
-                        res.push(Solution { nonce, hash_hex });
-                        if res.len() >= k {
-                            stop.store(true, Ordering::Relaxed);
-                        }
-                    }
-                }
+        let handler = move || {
+            if stop.swap(true, Ordering::Relaxed) {
+                process::exit(130);
             }
-        }));
+            eprintln!("stopping; press Ctrl-C again to quit at once");
+        };
+        if let Err(e) = ctrlc::set_handler(handler) {
+            eprintln!("warning: cannot install Ctrl-C handler: {}", e);
+        }
     }
 
-    // Wait for workers to finish
-    for h in handles {
-        h.join().unwrap();
-    }
-
-    let elapsed = t0.elapsed();
-
-    // Deterministic output: sort by nonce then truncate to k
-    let mut sols = results.lock().unwrap();
-    sols.sort_by_key(|s| s.nonce);
-    if sols.len() > k {
-        sols.truncate(k);
+    // The threads, range split and checkpoints live in `pow_core::search`.
+    let mut search = pow_search(&cfg).stop_flag(stop);
+    if let Some(ckpt) = resume {
+        search = search.resume(ckpt);
     }
+    let report = search.run();
+    let per_worker = report.per_thread_hashes();
+    let PowReport {
+        solutions: sols,
+        hashes: total_hashes,
+        elapsed,
+        searched,
+        status,
+        ..
+    } = report;
+    let SearchConfig {
+        prefix,
+        difficulty: d,
+        algorithm,
+        k,
+        start,
+        end,
+        threads,
+        smallest: _,
+        json,
+        timeout,
+        max_hashes,
+        checkpoint,
+        checkpoint_interval: _,
+        resume: _,
+        progress: _,
+        progress_format: _,
+    } = cfg;
 
     // Performance stats
-    let total_hashes = hashes.load(Ordering::Relaxed);
     let secs = elapsed.as_secs_f64();
     let hashrate = if secs > 0.0 {
         (total_hashes as f64) / secs
@@ -183,18 +219,172 @@ fn main() {
     };
 
     println!(
-        "prefix={} threads={} difficulty={} target={}",
-        prefix, threads, d, k
+        "prefix={} threads={} difficulty={} target={} algorithm={}",
+        prefix, threads, d, k, algorithm
     );
+    println!("range={}..{}", start, end);
     println!(
         "hashes={} time_ms={} hashrate={:.0}/s",
         total_hashes,
         elapsed.as_millis(),
         hashrate
     );
+    // Per-thread hashes, to spot load imbalance.
+    println!("{}", telemetry::breakdown(&per_worker));
+    println!("status={} searched={}", status, searched);
 
     println!("solutions:");
     for s in sols.iter() {
-        println!("nonce={} hash={}", s.nonce, s.hash_hex);
+        println!("nonce={} hash={}", s.nonce, hex::encode(s.hash));
+    }
+
+    if status != Status::Finished {
+        // Not an answer yet, so no JSON copy either.
+        let why = match status {
+            Status::TimedOut => format!("TIMED OUT after {}s", timeout),
+            Status::HashLimit => format!("HASH LIMIT of {} reached", max_hashes),
+            _ => "INTERRUPTED".to_string(),
+        };
+        let next = match &checkpoint {
+            Some(path) => format!("continue with --resume {}", path),
+            None => "pass --checkpoint FILE to be able to continue".to_string(),
+        };
+        println!(
+            "{}: searched {} of {} nonces, {} solution(s) so far; {}",
+            why,
+            searched,
+            end - start,
+            sols.len(),
+            next
+        );
+        process::exit(if status == Status::Interrupted {
+            130
+        } else {
+            124
+        });
+    }
+
+    // Machine-readable copy for `verify` and other tools.
+    if let Some(path) = json {
+        let set = SolutionSet {
+            prefix,
+            difficulty: d,
+            algorithm,
+            header: None,
+            k,
+            start,
+            end,
+            solutions: sols
+                .iter()
+                .map(|s| ReportedSolution::new(s.nonce, &s.hash))
+                .collect(),
+        };
+        if let Err(e) = fs::write(&path, set.to_json()) {
+            eprintln!("error: cannot write `{}`: {}", path, e);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pow_core::{Algorithm, PrefixHasher};
+    use std::thread;
+    use std::time::Duration;
+
+    fn search(cfg: &SearchConfig, stop: Arc<AtomicBool>, resume: Option<&Checkpoint>) -> PowReport {
+        let mut search = pow_search(cfg).stop_flag(stop);
+        if let Some(ckpt) = resume {
+            search = search.resume(ckpt.clone());
+        }
+        search.run()
+    }
+
+    fn small_run(threads: usize) -> SearchConfig {
+        SearchConfig {
+            difficulty: pow_core::Difficulty::hex_digits(3),
+            k: 6,
+            end: 60_000,
+            threads,
+            smallest: true,
+            ..config::defaults()
+        }
+    }
+
+    #[test]
+    fn smallest_mode_is_independent_of_thread_count() {
+        let cfg = small_run(1);
+        let hasher = PrefixHasher::new(&cfg.prefix);
+        let expected: Vec<u64> = (cfg.start..cfg.end)
+            .filter(|&n| cfg.difficulty.meets(&hasher.digest(n)))
+            .take(cfg.k)
+            .collect();
+        assert_eq!(expected.len(), cfg.k);
+
+        for threads in [1, 16] {
+            let outcome = search(&small_run(threads), Default::default(), None);
+            let nonces: Vec<u64> = outcome.solutions.iter().map(|s| s.nonce).collect();
+            assert_eq!(nonces, expected, "threads={threads}");
+        }
+    }
+
+    #[test]
+    fn every_algorithm_finds_the_same_nonces_as_brute_force() {
+        for algorithm in Algorithm::ALL {
+            let cfg = SearchConfig {
+                algorithm,
+                ..small_run(3)
+            };
+            let hasher = PrefixHasher::with_algorithm(algorithm, &cfg.prefix);
+            let expected: Vec<u64> = (cfg.start..cfg.end)
+                .filter(|&n| cfg.difficulty.meets(&hasher.digest(n)))
+                .take(cfg.k)
+                .collect();
+            let outcome = search(&cfg, Default::default(), None);
+            let nonces: Vec<u64> = outcome.solutions.iter().map(|s| s.nonce).collect();
+            assert_eq!(nonces, expected, "{algorithm}");
+        }
+    }
+
+    #[test]
+    fn interrupted_run_resumes_to_the_same_answer() {
+        let path = std::env::temp_dir().join(format!("lab3-interrupt-{}.ckpt", process::id()));
+        let cfg = SearchConfig {
+            checkpoint: Some(path.to_string_lossy().into_owned()),
+            ..small_run(4)
+        };
+        let hasher = PrefixHasher::new(&cfg.prefix);
+        let expected: Vec<u64> = (cfg.start..cfg.end)
+            .filter(|&n| cfg.difficulty.meets(&hasher.digest(n)))
+            .take(cfg.k)
+            .collect();
+
+        // Already stopped: nothing is searched.
+        let outcome = search(&cfg, Arc::new(AtomicBool::new(true)), None);
+        assert_eq!(outcome.status, Status::Interrupted);
+        assert_eq!(outcome.searched, 0);
+
+        // Stop part-way through, then resume (possibly more than once).
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopper = {
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(5));
+                stop.store(true, Ordering::Relaxed);
+            })
+        };
+        search(&cfg, stop, None);
+        stopper.join().unwrap();
+
+        let ckpt = Checkpoint::load(&path).unwrap();
+        let outcome = search(&cfg, Default::default(), Some(&ckpt));
+        let nonces: Vec<u64> = outcome.solutions.iter().map(|s| s.nonce).collect();
+        assert_eq!(nonces, expected);
+        assert!(outcome.finished());
+        assert_eq!(outcome.totals().hashes, outcome.hashes);
+        // Nothing searched before is searched again.
+        assert_eq!(outcome.searched, ckpt.completed.count() + outcome.hashes);
+        fs::remove_file(&path).unwrap();
     }
 }
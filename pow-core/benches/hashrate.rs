@@ -0,0 +1,125 @@
+// Single-thread hashrate of the PoW hot loop, before and after the
+// byte-level difficulty check:
+//
+//   before: SHA256 of `format!` input, `nonce.to_string()` + `hex::encode`
+//           per hash, difficulty checked on the hex string
+//   after:  `pow_core::PrefixHasher` + `Difficulty::meets` (no allocation)
+//
+// followed by the same allocation-free loop for every `--algorithm`, to
+// compare hash throughput.
+//
+// Run with:
+//
+//   cargo bench --bench hashrate [-- NONCES]
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use pow_core::{Algorithm, Difficulty, Hash, PrefixHasher};
+use sha2::{Digest, Sha256};
+
+const PREFIX: &str = "cmkl-pow";
+const DIFFICULTY: usize = 4;
+const ROUNDS: usize = 3;
+
+/// The original hot path, kept here as the baseline.
+fn sha256_hex(prefix: &str, nonce: u64) -> String {
+    let mut h = Sha256::new();
+    h.update(prefix.as_bytes());
+    h.update(b":");
+    h.update(nonce.to_string().as_bytes());
+    hex::encode(h.finalize())
+}
+
+fn meets_difficulty_hex(hash_hex: &str, difficulty: usize) -> bool {
+    hash_hex
+        .as_bytes()
+        .iter()
+        .take(difficulty)
+        .all(|&c| c == b'0')
+}
+
+fn search_hex(nonces: u64) -> u64 {
+    let mut found = 0;
+    for nonce in 0..nonces {
+        let hash_hex = sha256_hex(PREFIX, black_box(nonce));
+        if meets_difficulty_hex(&hash_hex, DIFFICULTY) {
+            found += 1;
+        }
+    }
+    found
+}
+
+fn search_bytes(nonces: u64) -> u64 {
+    search_with(Algorithm::Sha256, nonces)
+}
+
+fn search_with(algorithm: Algorithm, nonces: u64) -> u64 {
+    let hasher = PrefixHasher::with_algorithm(algorithm, PREFIX);
+    let difficulty = Difficulty::hex_digits(DIFFICULTY as u32);
+    let mut found = 0;
+    for nonce in 0..nonces {
+        let hash: Hash = hasher.digest(black_box(nonce));
+        if difficulty.meets(&hash) {
+            found += 1;
+        }
+    }
+    found
+}
+
+/// Best of `ROUNDS` runs, plus the number of solutions found.
+fn measure(search: impl Fn(u64) -> u64, nonces: u64) -> (Duration, u64) {
+    let mut best = Duration::MAX;
+    let mut found = 0;
+    for _ in 0..ROUNDS {
+        let t0 = Instant::now();
+        found = black_box(search(nonces));
+        best = best.min(t0.elapsed());
+    }
+    (best, found)
+}
+
+fn main() {
+    // `cargo bench` passes `--bench`; take the first numeric argument.
+    let nonces: u64 = std::env::args()
+        .skip(1)
+        .find_map(|a| a.replace('_', "").parse().ok())
+        .unwrap_or(1_000_000);
+
+    let (hex_time, hex_found) = measure(search_hex, nonces);
+    let (byte_time, byte_found) = measure(search_bytes, nonces);
+    assert_eq!(
+        hex_found, byte_found,
+        "both checks must accept the same nonces"
+    );
+
+    let rate = |t: Duration| nonces as f64 / t.as_secs_f64();
+    println!(
+        "nonces={} difficulty={} solutions={}",
+        nonces, DIFFICULTY, byte_found
+    );
+    println!(
+        "hex   time_ms={} hashrate={:.0}/s",
+        hex_time.as_millis(),
+        rate(hex_time)
+    );
+    println!(
+        "bytes time_ms={} hashrate={:.0}/s",
+        byte_time.as_millis(),
+        rate(byte_time)
+    );
+    println!(
+        "speedup={:.2}x",
+        hex_time.as_secs_f64() / byte_time.as_secs_f64()
+    );
+
+    for algorithm in Algorithm::ALL {
+        let (time, _) = measure(|n| search_with(algorithm, n), nonces);
+        println!(
+            "algorithm={:<10} time_ms={} hashrate={:.0}/s",
+            algorithm.name(),
+            time.as_millis(),
+            rate(time)
+        );
+    }
+}
@@ -0,0 +1,283 @@
+// Checkpoint / resume for long searches.
+//
+// A checkpoint records which parts of [start, end) have been fully
+// searched, the solutions found in them, and the hashes spent. Resuming
+// searches only the gaps:
+//
+//   {"prefix": "cmkl-pow", "difficulty": "6", "algorithm": "sha256", "k": 10,
+//    "start": 0, "end": 50000000000, "smallest": false,
+//    "completed": [[0, 1250000], [1300000, 1350000]],
+//    "solutions": [{"nonce": 4598276, "hash": "000000..."}],
+//    "hashes": 1300000}
+//
+// For the checkpoint to be consistent, a range may only be added to
+// `completed` together with (or after) every solution found in it.
+//
+// Checkpoints are written to `<path>.tmp` and renamed over `<path>`, so a
+// crash mid-write leaves the previous checkpoint intact.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::difficulty::Difficulty;
+use crate::hash::Algorithm;
+use crate::header::BlockHeader;
+use crate::verify::ReportedSolution;
+
+/// A set of nonces stored as sorted, disjoint, non-adjacent half-open
+/// ranges `[start, end)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RangeSet {
+    ranges: Vec<(u64, u64)>,
+}
+
+impl RangeSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The ranges, sorted and merged.
+    pub fn ranges(&self) -> &[(u64, u64)] {
+        &self.ranges
+    }
+
+    /// Add `[start, end)`, merging with any range it overlaps or touches.
+    pub fn insert(&mut self, start: u64, end: u64) {
+        if start >= end {
+            return;
+        }
+        // First range that ends at or after `start` (could merge with us).
+        let first = self.ranges.partition_point(|&(_, e)| e < start);
+        // First range that starts after `end` (cannot merge with us).
+        let last = self.ranges.partition_point(|&(s, _)| s <= end);
+
+        let (mut lo, mut hi) = (start, end);
+        if first < last {
+            lo = lo.min(self.ranges[first].0);
+            hi = hi.max(self.ranges[last - 1].1);
+        }
+        self.ranges.splice(first..last, [(lo, hi)]);
+    }
+
+    pub fn contains(&self, n: u64) -> bool {
+        let i = self.ranges.partition_point(|&(_, e)| e <= n);
+        self.ranges.get(i).is_some_and(|&(s, _)| s <= n)
+    }
+
+    /// Number of nonces in the set.
+    pub fn count(&self) -> u64 {
+        self.ranges.iter().map(|&(s, e)| e - s).sum()
+    }
+
+    /// The parts of `[start, end)` not in the set.
+    pub fn gaps(&self, start: u64, end: u64) -> Vec<(u64, u64)> {
+        let mut gaps = Vec::new();
+        let mut cur = start;
+        for &(s, e) in &self.ranges {
+            if e <= cur {
+                continue;
+            }
+            if s >= end {
+                break;
+            }
+            if s > cur {
+                gaps.push((cur, s));
+            }
+            cur = e;
+        }
+        if cur < end {
+            gaps.push((cur, end));
+        }
+        gaps
+    }
+}
+
+/// Split `ranges` into `parts` lists of ranges of (nearly) equal total size,
+/// keeping nonces in ascending order across the lists.
+pub fn split_even(ranges: &[(u64, u64)], parts: usize) -> Vec<Vec<(u64, u64)>> {
+    let parts = parts.max(1);
+    let total: u64 = ranges.iter().map(|&(s, e)| e - s).sum();
+    let share = total.div_ceil(parts as u64).max(1);
+
+    let mut out = vec![Vec::new(); parts];
+    let mut part = 0;
+    let mut room = share;
+    for &(mut s, e) in ranges {
+        while s < e {
+            let take = (e - s).min(room);
+            out[part].push((s, s + take));
+            s += take;
+            room -= take;
+            if room == 0 && part + 1 < parts {
+                part += 1;
+                room = share;
+            }
+        }
+    }
+    out
+}
+
+/// Saved progress of one search.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub prefix: String,
+    pub difficulty: Difficulty,
+    /// Missing in checkpoints from before `--algorithm`: SHA-256.
+    #[serde(default)]
+    pub algorithm: Algorithm,
+    /// The header hashed instead of `prefix:nonce`, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<BlockHeader>,
+    pub k: usize,
+    pub start: u64,
+    pub end: u64,
+    pub smallest: bool,
+    /// Nonces already searched.
+    pub completed: RangeSet,
+    /// Solutions found in `completed`, ascending by nonce.
+    pub solutions: Vec<ReportedSolution>,
+    /// Hashes computed so far, across all resumed runs.
+    pub hashes: u64,
+}
+
+/// Why a checkpoint could not be loaded.
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckpointError::Io(e) => write!(f, "cannot read checkpoint: {}", e),
+            CheckpointError::Json(e) => write!(f, "invalid checkpoint: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {}
+
+impl Checkpoint {
+    /// The parts of `[start, end)` still to search.
+    pub fn remaining(&self) -> Vec<(u64, u64)> {
+        self.completed.gaps(self.start, self.end)
+    }
+
+    /// Atomically replace the checkpoint at `path`.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut tmp = PathBuf::from(path);
+        tmp.as_mut_os_string().push(".tmp");
+        let json = serde_json::to_string_pretty(self).expect("a Checkpoint always serializes");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, CheckpointError> {
+        let text = fs::read_to_string(path).map_err(CheckpointError::Io)?;
+        serde_json::from_str(&text).map_err(CheckpointError::Json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[(u64, u64)]) -> RangeSet {
+        let mut s = RangeSet::new();
+        for &(a, b) in ranges {
+            s.insert(a, b);
+        }
+        s
+    }
+
+    #[test]
+    fn insert_merges_overlapping_and_adjacent_ranges() {
+        assert_eq!(set(&[(10, 20), (30, 40)]).ranges(), &[(10, 20), (30, 40)]);
+        assert_eq!(set(&[(10, 20), (20, 30)]).ranges(), &[(10, 30)]);
+        assert_eq!(set(&[(30, 40), (10, 20), (15, 35)]).ranges(), &[(10, 40)]);
+        assert_eq!(
+            set(&[(0, 5), (10, 15), (20, 25), (4, 21)]).ranges(),
+            &[(0, 25)]
+        );
+        assert_eq!(set(&[(10, 20), (12, 14), (5, 5)]).ranges(), &[(10, 20)]);
+
+        // Out-of-order chunk completions collapse into one range.
+        let mut s = RangeSet::new();
+        for i in [3, 0, 2, 5, 1, 4] {
+            s.insert(i * 100, (i + 1) * 100);
+        }
+        assert_eq!(s.ranges(), &[(0, 600)]);
+        assert_eq!(s.count(), 600);
+    }
+
+    #[test]
+    fn gaps_and_contains() {
+        let s = set(&[(10, 20), (30, 40)]);
+        assert_eq!(s.gaps(0, 50), vec![(0, 10), (20, 30), (40, 50)]);
+        assert_eq!(s.gaps(15, 35), vec![(20, 30)]);
+        assert_eq!(s.gaps(10, 20), vec![]);
+        assert_eq!(RangeSet::new().gaps(3, 7), vec![(3, 7)]);
+        assert!(s.contains(10) && s.contains(19) && s.contains(35));
+        assert!(!s.contains(9) && !s.contains(20) && !s.contains(40));
+    }
+
+    #[test]
+    fn split_even_covers_everything_in_order() {
+        let ranges = [(0, 10), (20, 25), (40, 55)];
+        for parts in 1..8 {
+            let split = split_even(&ranges, parts);
+            assert_eq!(split.len(), parts);
+            let flat: Vec<(u64, u64)> = split.iter().flatten().copied().collect();
+            let mut merged = RangeSet::new();
+            for &(a, b) in &flat {
+                merged.insert(a, b);
+            }
+            assert_eq!(merged.ranges(), &ranges);
+            assert!(flat.windows(2).all(|w| w[0].1 <= w[1].0));
+            let sizes: Vec<u64> = split
+                .iter()
+                .map(|p| p.iter().map(|&(a, b)| b - a).sum())
+                .collect();
+            assert!(
+                sizes.iter().max().unwrap() - sizes.iter().min().unwrap() <= 30 / parts as u64 + 1
+            );
+        }
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = std::env::temp_dir().join(format!("pow-core-ckpt-{}.json", std::process::id()));
+        let ckpt = Checkpoint {
+            prefix: "cmkl-pow".to_string(),
+            difficulty: Difficulty::LeadingZeroBits(21),
+            algorithm: Algorithm::Blake3,
+            header: Some(BlockHeader::new(0x1d00ffff)),
+            k: 3,
+            start: 0,
+            end: 1000,
+            smallest: true,
+            completed: set(&[(0, 400), (500, 600)]),
+            solutions: vec![ReportedSolution {
+                nonce: 7,
+                hash: "00".repeat(32),
+            }],
+            hashes: 500,
+        };
+        ckpt.save(&path).unwrap();
+        let loaded = Checkpoint::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, ckpt);
+        assert_eq!(loaded.remaining(), vec![(400, 500), (600, 1000)]);
+        assert!(matches!(
+            Checkpoint::load(&path),
+            Err(CheckpointError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,681 @@
+// Command-line and config-file parameters shared by the PoW binaries.
+//
+// Every parameter has a default, can be set in a config file, and can be
+// overridden by a flag:
+//
+//   defaults  <  --config FILE  <  --flags
+//
+// The config file is plain `key = value` lines; `#` starts a comment and
+// blank lines are ignored. Keys match the flag names, with `_` or `-`:
+//
+//   # fast local run
+//   difficulty = 4
+//   threads    = 4
+//
+// Numbers may use `_` separators (e.g. `end = 50_000_000_000`). Flags are
+// `--key value` or `--key=value`; switches such as `--smallest` take no
+// value.
+//
+// `SearchConfig` holds the parameters every binary has (what to search for,
+// threads, budgets, checkpoints, progress). A binary with more parameters
+// wraps it in its own type and implements `Options` for that, handling its
+// own keys and passing the rest on to `SearchConfig`.
+//
+// `--resume FILE` takes the search parameters (prefix, difficulty, k, range,
+// smallest) from the checkpoint instead (see `SearchConfig::resume_from`).
+// It keeps checkpointing to FILE unless `--checkpoint` names another file.
+
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::checkpoint::Checkpoint;
+use crate::difficulty::Difficulty;
+use crate::hash::Algorithm;
+use crate::search::{PowSearch, Schedule};
+use crate::telemetry::ProgressFormat;
+
+/// Why the parameters were rejected.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// A flag or config key this program does not know.
+    UnknownKey(String),
+    /// A flag given as the last argument, with no value after it.
+    MissingValue(String),
+    /// A value that does not parse for its key.
+    InvalidValue {
+        key: String,
+        value: String,
+        reason: String,
+    },
+    /// The config file could not be read.
+    Io { path: String, reason: String },
+    /// A config file line that is not `key = value`.
+    Syntax { path: String, line: usize },
+    /// Parameters that parse but cannot describe a valid run.
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::UnknownKey(key) => write!(f, "unknown option `{}`", key),
+            ConfigError::MissingValue(key) => write!(f, "option `{}` needs a value", key),
+            ConfigError::InvalidValue { key, value, reason } => {
+                write!(f, "invalid value `{}` for `{}`: {}", value, key, reason)
+            }
+            ConfigError::Io { path, reason } => {
+                write!(f, "cannot read config file `{}`: {}", path, reason)
+            }
+            ConfigError::Syntax { path, line } => {
+                write!(f, "{}:{}: expected `key = value`", path, line)
+            }
+            ConfigError::Invalid(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Parse a value with its `FromStr` impl, naming `key` in the error.
+pub fn parse_value<T: FromStr>(key: &str, value: &str) -> Result<T, ConfigError>
+where
+    T::Err: fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e: T::Err| ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            reason: e.to_string(),
+        })
+}
+
+/// Parse a number, allowing `_` separators.
+pub fn parse_num<N: FromStr>(key: &str, value: &str) -> Result<N, ConfigError>
+where
+    N::Err: fmt::Display,
+{
+    parse_value(key, &value.replace('_', "")).map_err(|e| match e {
+        ConfigError::InvalidValue { key, reason, .. } => ConfigError::InvalidValue {
+            key,
+            value: value.to_string(),
+            reason,
+        },
+        e => e,
+    })
+}
+
+/// Split `--key=value` into the flag and its inline value.
+pub fn split_flag(arg: &str) -> (&str, Option<&str>) {
+    match arg.split_once('=') {
+        Some((flag, value)) if flag.starts_with("--") => (flag, Some(value)),
+        _ => (arg, None),
+    }
+}
+
+/// The value of a flag: inline after `=`, or else the next argument.
+pub fn flag_value<I>(arg: &str, inline: Option<&str>, args: &mut I) -> Result<String, ConfigError>
+where
+    I: Iterator<Item = String>,
+{
+    match inline {
+        Some(value) => Ok(value.to_string()),
+        None => args
+            .next()
+            .ok_or_else(|| ConfigError::MissingValue(arg.to_string())),
+    }
+}
+
+/// A set of parameters that `parse_options` can fill in.
+pub trait Options {
+    /// Flags that take no value on the command line (`--smallest` means
+    /// `smallest = true`).
+    const SWITCHES: &'static [&'static str];
+
+    /// Set one parameter by name. Accepts `chunk_size` and `chunk-size`.
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError>;
+
+    /// Reject parameter combinations that would panic or never finish.
+    fn validate(&self) -> Result<(), ConfigError>;
+
+    /// The parameters every binary shares.
+    fn search_mut(&mut self) -> &mut SearchConfig;
+}
+
+/// The parameters of a search that every binary shares.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchConfig {
+    pub prefix: String,
+    pub difficulty: Difficulty,
+    /// Hash applied to `prefix:nonce`.
+    pub algorithm: Algorithm,
+    pub k: usize,
+    pub start: u64,
+    pub end: u64,
+    pub threads: usize,
+    /// Keep searching until the k smallest valid nonces are known.
+    pub smallest: bool,
+    /// Where to write the solutions as JSON, if anywhere.
+    pub json: Option<String>,
+    /// Seconds before the search gives up; 0 means no limit.
+    pub timeout: u64,
+    /// Hashes before the search gives up; 0 means no limit.
+    pub max_hashes: u64,
+    /// Where to save progress, if anywhere.
+    pub checkpoint: Option<String>,
+    /// Seconds between periodic checkpoints.
+    pub checkpoint_interval: u64,
+    /// Checkpoint to continue from.
+    pub resume: Option<String>,
+    /// Seconds between progress lines; 0 disables them.
+    pub progress: u64,
+    pub progress_format: ProgressFormat,
+}
+
+impl Default for SearchConfig {
+    /// assignment-2's search; other binaries override what they search for.
+    fn default() -> Self {
+        Self {
+            prefix: "cmkl-pow".to_string(),
+            difficulty: Difficulty::hex_digits(6),
+            algorithm: Algorithm::Sha256,
+            k: 10,
+            start: 0,
+            end: 50_000_000_000,
+            threads: 8,
+            smallest: false,
+            json: None,
+            timeout: 0,
+            max_hashes: 0,
+            checkpoint: None,
+            checkpoint_interval: 30,
+            resume: None,
+            progress: 5,
+            progress_format: ProgressFormat::Text,
+        }
+    }
+}
+
+impl SearchConfig {
+    /// Take the search parameters from a checkpoint being resumed.
+    pub fn resume_from(&mut self, ckpt: &Checkpoint) {
+        self.prefix = ckpt.prefix.clone();
+        self.difficulty = ckpt.difficulty;
+        self.algorithm = ckpt.algorithm;
+        self.k = ckpt.k;
+        self.start = ckpt.start;
+        self.end = ckpt.end;
+        self.smallest = ckpt.smallest;
+    }
+
+    /// The search these parameters describe, run with `schedule`.
+    pub fn pow_search(&self, schedule: Schedule) -> PowSearch {
+        let mut search = PowSearch::new(&self.prefix)
+            .difficulty(self.difficulty)
+            .algorithm(self.algorithm)
+            .k(self.k)
+            .range(self.start, self.end)
+            .threads(self.threads)
+            .schedule(schedule)
+            .smallest(self.smallest);
+        if let Some(path) = &self.checkpoint {
+            search = search.checkpoint(path, Duration::from_secs(self.checkpoint_interval));
+        }
+        if self.timeout > 0 {
+            search = search.timeout(Duration::from_secs(self.timeout));
+        }
+        if self.max_hashes > 0 {
+            search = search.max_hashes(self.max_hashes);
+        }
+        if self.progress > 0 {
+            search = search.progress(Duration::from_secs(self.progress), self.progress_format);
+        }
+        search
+    }
+}
+
+impl Options for SearchConfig {
+    const SWITCHES: &'static [&'static str] = &["smallest"];
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        match key.replace('-', "_").as_str() {
+            "prefix" => self.prefix = value.to_string(),
+            "difficulty" => self.difficulty = parse_value(key, value)?,
+            "algorithm" => self.algorithm = parse_value(key, value)?,
+            "k" => self.k = parse_num(key, value)?,
+            "start" => self.start = parse_num(key, value)?,
+            "end" => self.end = parse_num(key, value)?,
+            "threads" => self.threads = parse_num(key, value)?,
+            "smallest" => self.smallest = parse_value(key, value)?,
+            "json" => self.json = Some(value.to_string()),
+            "timeout" => self.timeout = parse_num(key, value)?,
+            "max_hashes" => self.max_hashes = parse_num(key, value)?,
+            "checkpoint" => self.checkpoint = Some(value.to_string()),
+            "checkpoint_interval" => self.checkpoint_interval = parse_num(key, value)?,
+            "resume" => self.resume = Some(value.to_string()),
+            "progress" => self.progress = parse_num(key, value)?,
+            "progress_format" => self.progress_format = parse_value(key, value)?,
+            _ => return Err(ConfigError::UnknownKey(key.to_string())),
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.threads == 0 {
+            return Err(ConfigError::Invalid(
+                "threads must be at least 1".to_string(),
+            ));
+        }
+        if self.checkpoint_interval == 0 {
+            return Err(ConfigError::Invalid(
+                "checkpoint_interval must be at least 1 second".to_string(),
+            ));
+        }
+        if self.k == 0 {
+            return Err(ConfigError::Invalid("k must be at least 1".to_string()));
+        }
+        if self.start >= self.end {
+            return Err(ConfigError::Invalid(format!(
+                "empty nonce range: start ({}) must be less than end ({})",
+                self.start, self.end
+            )));
+        }
+        self.difficulty
+            .validate()
+            .map_err(|e| ConfigError::Invalid(e.to_string()))?;
+        Ok(())
+    }
+
+    fn search_mut(&mut self) -> &mut SearchConfig {
+        self
+    }
+}
+
+/// Apply every `key = value` line of a config file's contents.
+pub fn apply_file<O: Options>(
+    options: &mut O,
+    path: &str,
+    contents: &str,
+) -> Result<(), ConfigError> {
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(ConfigError::Syntax {
+                path: path.to_string(),
+                line: i + 1,
+            });
+        };
+        let value = value.trim().trim_matches('"');
+        options.set(key.trim(), value)?;
+    }
+    Ok(())
+}
+
+/// Parse run flags (without the program name) on top of `options`.
+///
+/// `--config` is applied before any other flag, wherever it appears, so
+/// flags always override the file. Returns `None` if `--help` was asked for.
+pub fn parse_options<O, I>(mut options: O, args: I) -> Result<Option<O>, ConfigError>
+where
+    O: Options,
+    I: IntoIterator<Item = String>,
+{
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    let mut config_path: Option<String> = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            return Ok(None);
+        }
+        let Some(flag) = arg.strip_prefix("--") else {
+            return Err(ConfigError::UnknownKey(arg));
+        };
+
+        let (key, value) = match flag.split_once('=') {
+            Some((key, value)) => (key.to_string(), value.to_string()),
+            None if O::SWITCHES.contains(&flag) => (flag.to_string(), "true".to_string()),
+            None => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| ConfigError::MissingValue(arg.clone()))?;
+                (flag.to_string(), value)
+            }
+        };
+
+        if key == "config" {
+            config_path = Some(value);
+        } else {
+            pairs.push((key, value));
+        }
+    }
+
+    if let Some(path) = config_path {
+        let contents = fs::read_to_string(&path).map_err(|e| ConfigError::Io {
+            path: path.clone(),
+            reason: e.to_string(),
+        })?;
+        apply_file(&mut options, &path, &contents)?;
+    }
+    for (key, value) in pairs {
+        options.set(&key, &value)?;
+    }
+    // A resumed run keeps saving to the file it was resumed from.
+    let search = options.search_mut();
+    if search.checkpoint.is_none() {
+        search.checkpoint = search.resume.clone();
+    }
+
+    options.validate()?;
+    Ok(Some(options))
+}
+
+/// Parameters of the `verify` subcommand.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifyArgs {
+    /// Printed output or JSON file; `None` reads stdin.
+    pub input: Option<String>,
+    /// Only check each reported solution, not that they are the k smallest.
+    pub valid_only: bool,
+    /// Threads used to rescan the range.
+    pub threads: usize,
+}
+
+/// Parse the arguments after `verify`. Returns `None` for `--help`.
+pub fn parse_verify_args<I>(args: I) -> Result<Option<VerifyArgs>, ConfigError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut verify = VerifyArgs {
+        input: None,
+        valid_only: false,
+        threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+
+    // Same flag syntax as `parse_options`: `--threads N` or `--threads=N`.
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline) = split_flag(&arg);
+        match flag {
+            "-h" | "--help" => return Ok(None),
+            "--valid-only" if inline.is_none() => verify.valid_only = true,
+            "--threads" => {
+                let value = flag_value(&arg, inline, &mut args)?;
+                verify.threads = parse_num("threads", &value)?;
+                if verify.threads == 0 {
+                    return Err(ConfigError::Invalid(
+                        "threads must be at least 1".to_string(),
+                    ));
+                }
+            }
+            _ if flag.starts_with("--") || verify.input.is_some() => {
+                return Err(ConfigError::UnknownKey(arg));
+            }
+            _ => verify.input = Some(arg),
+        }
+    }
+    Ok(Some(verify))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    fn run(s: &str) -> Result<SearchConfig, ConfigError> {
+        match parse_options(SearchConfig::default(), args(s))? {
+            Some(cfg) => Ok(cfg),
+            None => panic!("unexpected --help"),
+        }
+    }
+
+    #[test]
+    fn no_flags_gives_the_defaults() {
+        assert_eq!(run(""), Ok(SearchConfig::default()));
+        assert_eq!(
+            parse_options(SearchConfig::default(), args("--threads 2 --help")),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn flags_override_defaults() {
+        let cfg = run("--difficulty 3 --k=2 --end 1_000 --threads 4").unwrap();
+        assert_eq!(cfg.difficulty, Difficulty::LeadingZeroBits(12));
+        assert_eq!(cfg.k, 2);
+        assert_eq!(cfg.end, 1000);
+        assert_eq!(cfg.threads, 4);
+        assert_eq!(cfg.prefix, "cmkl-pow");
+    }
+
+    #[test]
+    fn config_file_is_applied_before_flags() {
+        let mut cfg = SearchConfig::default();
+        apply_file(
+            &mut cfg,
+            "test.conf",
+            "# comment\n\nprefix = \"abc\"\nthreads = 3  # trailing\nmax-hashes = 7\n",
+        )
+        .unwrap();
+        assert_eq!(cfg.prefix, "abc");
+        assert_eq!(cfg.threads, 3);
+        assert_eq!(cfg.max_hashes, 7);
+
+        assert_eq!(
+            apply_file(&mut cfg, "test.conf", "threads 3"),
+            Err(ConfigError::Syntax {
+                path: "test.conf".to_string(),
+                line: 1
+            })
+        );
+    }
+
+    #[test]
+    fn impossible_parameters_are_rejected() {
+        for bad in [
+            "--threads 0",
+            "--k 0",
+            "--start 5 --end 5",
+            "--start 6 --end 5",
+            "--difficulty 65",
+        ] {
+            assert!(
+                matches!(run(bad), Err(ConfigError::Invalid(_))),
+                "{bad} should be rejected"
+            );
+        }
+        assert!(run("--difficulty 64").is_ok());
+        assert!(run("--difficulty 256b").is_ok());
+        assert!(matches!(
+            run("--difficulty 257b"),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            run("--difficulty 0x00000000"),
+            Err(ConfigError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn difficulty_accepts_bits_and_targets() {
+        assert_eq!(
+            run("--difficulty 22b").unwrap().difficulty,
+            Difficulty::LeadingZeroBits(22)
+        );
+        let cfg = run("--difficulty 0x1d00ffff").unwrap();
+        assert_eq!(cfg.difficulty.to_string(), "0x1d00ffff");
+        assert!(matches!(
+            run("--difficulty 0x04923456"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn smallest_is_a_switch() {
+        assert!(!run("").unwrap().smallest);
+        let cfg = run("--smallest --threads 2").unwrap();
+        assert!(cfg.smallest);
+        assert_eq!(cfg.threads, 2);
+        assert!(!run("--smallest=false").unwrap().smallest);
+
+        let mut cfg = SearchConfig::default();
+        apply_file(&mut cfg, "test.conf", "smallest = true").unwrap();
+        assert!(cfg.smallest);
+        assert!(matches!(
+            apply_file(&mut cfg, "test.conf", "smallest = yes"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn verify_args() {
+        let v = parse_verify_args(args("out.txt --valid-only --threads 3"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(v.input.as_deref(), Some("out.txt"));
+        assert!(v.valid_only);
+        assert_eq!(v.threads, 3);
+
+        let v = parse_verify_args(args("")).unwrap().unwrap();
+        assert_eq!(v.input, None);
+        assert!(!v.valid_only);
+
+        let v = parse_verify_args(args("--threads=5 -")).unwrap().unwrap();
+        assert_eq!(v.threads, 5);
+        assert_eq!(v.input.as_deref(), Some("-"));
+
+        assert_eq!(parse_verify_args(args("a --help")), Ok(None));
+        assert!(parse_verify_args(args("a b")).is_err());
+        assert!(parse_verify_args(args("--threads 0")).is_err());
+        assert!(parse_verify_args(args("--threads=")).is_err());
+        assert!(parse_verify_args(args("--valid-only=1")).is_err());
+        assert!(parse_verify_args(args("--difficulty 3")).is_err());
+        assert_eq!(
+            run("--json out.json").unwrap().json.as_deref(),
+            Some("out.json")
+        );
+    }
+
+    #[test]
+    fn budget_options() {
+        let cfg = run("--timeout 30 --max-hashes 1_000_000").unwrap();
+        assert_eq!((cfg.timeout, cfg.max_hashes), (30, 1_000_000));
+        assert_eq!(
+            (
+                SearchConfig::default().timeout,
+                SearchConfig::default().max_hashes
+            ),
+            (0, 0)
+        );
+        assert!(matches!(
+            run("--timeout soon"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn checkpoint_options() {
+        let cfg = run("--checkpoint run.ckpt --checkpoint-interval 5").unwrap();
+        assert_eq!(cfg.checkpoint.as_deref(), Some("run.ckpt"));
+        assert_eq!(cfg.checkpoint_interval, 5);
+        assert_eq!(cfg.resume, None);
+
+        let cfg = run("--resume run.ckpt").unwrap();
+        assert_eq!(cfg.resume.as_deref(), Some("run.ckpt"));
+        assert_eq!(cfg.checkpoint.as_deref(), Some("run.ckpt"));
+
+        let cfg = run("--resume old.ckpt --checkpoint new.ckpt").unwrap();
+        assert_eq!(cfg.checkpoint.as_deref(), Some("new.ckpt"));
+
+        assert!(matches!(
+            run("--checkpoint-interval 0"),
+            Err(ConfigError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn resume_takes_the_search_from_the_checkpoint() {
+        let mut cfg = run("--threads 3 --k 4").unwrap();
+        let ckpt = Checkpoint {
+            prefix: "other".to_string(),
+            difficulty: Difficulty::LeadingZeroBits(9),
+            algorithm: Algorithm::Blake3,
+            header: None,
+            k: 7,
+            start: 100,
+            end: 900,
+            smallest: true,
+            completed: crate::RangeSet::new(),
+            solutions: Vec::new(),
+            hashes: 0,
+        };
+        cfg.resume_from(&ckpt);
+        assert_eq!(cfg.prefix, "other");
+        assert_eq!(cfg.difficulty, Difficulty::LeadingZeroBits(9));
+        assert_eq!(cfg.algorithm, Algorithm::Blake3);
+        assert_eq!((cfg.k, cfg.start, cfg.end), (7, 100, 900));
+        assert!(cfg.smallest);
+        assert_eq!(cfg.threads, 3);
+    }
+
+    #[test]
+    fn algorithm_is_configurable() {
+        assert_eq!(run("").unwrap().algorithm, Algorithm::Sha256);
+        assert_eq!(
+            run("--algorithm blake3").unwrap().algorithm,
+            Algorithm::Blake3
+        );
+        let mut cfg = SearchConfig::default();
+        apply_file(&mut cfg, "test.conf", "algorithm = sha512-256").unwrap();
+        assert_eq!(cfg.algorithm, Algorithm::Sha512_256);
+        assert!(matches!(
+            run("--algorithm md5"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn progress_options() {
+        let cfg = run("--progress 0 --progress-format json").unwrap();
+        assert_eq!(cfg.progress, 0);
+        assert_eq!(cfg.progress_format, ProgressFormat::Json);
+        assert!(matches!(
+            run("--progress-format xml"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn malformed_flags_are_reported() {
+        assert_eq!(
+            run("--threads"),
+            Err(ConfigError::MissingValue("--threads".to_string()))
+        );
+        assert_eq!(
+            run("--bogus 1"),
+            Err(ConfigError::UnknownKey("bogus".to_string()))
+        );
+        assert_eq!(
+            run("stealing"),
+            Err(ConfigError::UnknownKey("stealing".to_string()))
+        );
+        assert!(matches!(
+            run("--k ten"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            run("--config /nonexistent/pow.conf"),
+            Err(ConfigError::Io { .. })
+        ));
+        assert_eq!(split_flag("--threads=4"), ("--threads", Some("4")));
+        assert_eq!(split_flag("a=b"), ("a=b", None));
+    }
+}
@@ -0,0 +1,444 @@
+// Difficulty rules for the PoW search.
+//
+// The original rule ("the hex hash begins with `d` zeros") only moves in
+// steps of 16x expected work. Two finer rules are supported here:
+//
+//   LeadingZeroBits(n)  the digest begins with `n` zero bits; `d` hex zeros
+//                       is exactly `LeadingZeroBits(4 * d)`.
+//   Target(t)           the digest, read as a big-endian 256-bit integer,
+//                       is below `t` (`hash < target`), like Bitcoin.
+//
+// Targets can be written in Bitcoin's compact `nBits` form: the top byte is
+// a base-256 exponent and the low three bytes a mantissa, so
+//
+//   target = mantissa * 256^(exponent - 3)
+//
+// e.g. 0x1d00ffff is 0x00000000ffff0000...0000.
+//
+// As text (flags, config files, printed output) a difficulty is one of:
+//
+//   6                 6 leading zero hex digits (24 bits)
+//   22b               22 leading zero bits
+//   0x1d00ffff        compact nBits target
+//   00000000ffff...   full 64-hex-digit target
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+use crate::hash::Hash;
+
+/// A 256-bit target, stored big-endian.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Target(pub [u8; 32]);
+
+impl Target {
+    /// Decode a compact `nBits` value.
+    pub fn from_compact(bits: u32) -> Result<Self, DifficultyError> {
+        let size = (bits >> 24) as i32;
+        let word = bits & 0x007f_ffff;
+        if word != 0 && bits & 0x0080_0000 != 0 {
+            return Err(DifficultyError::NegativeCompact(bits));
+        }
+
+        // Mantissa byte `j` has weight 256^(size - 1 - j); the byte with
+        // weight 256^p lives at index 31 - p of the big-endian array.
+        let mut out = [0u8; 32];
+        for (j, &b) in word.to_be_bytes()[1..].iter().enumerate() {
+            let p = size - 1 - j as i32;
+            if p < 0 {
+                continue; // shifted out below the units byte
+            }
+            if p > 31 {
+                if b != 0 {
+                    return Err(DifficultyError::CompactOverflow(bits));
+                }
+                continue;
+            }
+            out[31 - p as usize] = b;
+        }
+        Ok(Target(out))
+    }
+
+    /// Encode as compact `nBits`, rounding down to 3 significant bytes.
+    pub fn to_compact(&self) -> u32 {
+        let first = self.0.iter().position(|&b| b != 0).unwrap_or(32);
+        let mut size = (32 - first) as u32;
+
+        let byte = |i: usize| self.0.get(i).copied().unwrap_or(0) as u32;
+        let mut word = (byte(first) << 16) | (byte(first + 1) << 8) | byte(first + 2);
+
+        // The mantissa is signed: keep its top bit clear.
+        if word & 0x0080_0000 != 0 {
+            word >>= 8;
+            size += 1;
+        }
+        (size << 24) | word
+    }
+
+    /// The target as a float, for work estimates.
+    fn to_f64(self) -> f64 {
+        self.0.iter().fold(0.0, |acc, &b| acc * 256.0 + b as f64)
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+/// When a hash counts as a solution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    /// The digest begins with at least this many zero bits.
+    LeadingZeroBits(u32),
+    /// The digest, as a big-endian integer, is strictly below the target.
+    Target(Target),
+}
+
+impl Difficulty {
+    /// A PoW digest has 256 bits.
+    pub const MAX_BITS: u32 = 256;
+
+    /// The original rule: `digits` leading zero hex characters.
+    pub fn hex_digits(digits: u32) -> Self {
+        Difficulty::LeadingZeroBits(digits.saturating_mul(4))
+    }
+
+    /// Does `hash` satisfy this difficulty?
+    ///
+    /// Must only be called on a difficulty that passed [`Difficulty::validate`].
+    #[inline]
+    pub fn meets(&self, hash: &Hash) -> bool {
+        match *self {
+            Difficulty::LeadingZeroBits(bits) => {
+                let full_bytes = (bits / 8) as usize;
+                let rest = bits % 8;
+                hash[..full_bytes].iter().all(|&b| b == 0)
+                    && (rest == 0 || hash[full_bytes] >> (8 - rest) == 0)
+            }
+            Difficulty::Target(target) => *hash < target.0,
+        }
+    }
+
+    /// Reject difficulties no hash can ever meet.
+    pub fn validate(&self) -> Result<(), DifficultyError> {
+        match *self {
+            Difficulty::LeadingZeroBits(bits) if bits > Self::MAX_BITS => {
+                Err(DifficultyError::TooManyBits(bits))
+            }
+            Difficulty::Target(target) if target.0 == [0; 32] => Err(DifficultyError::ZeroTarget),
+            _ => Ok(()),
+        }
+    }
+
+    /// The compact `nBits` a block header carries for this difficulty:
+    /// the target itself, or `2^(256 - n)` for `n` leading zero bits.
+    pub fn compact_bits(&self) -> u32 {
+        match *self {
+            // 2^256 does not fit; the largest target is the closest.
+            Difficulty::LeadingZeroBits(0) => Target([0xff; 32]).to_compact(),
+            Difficulty::LeadingZeroBits(bits) => {
+                let p = Self::MAX_BITS.saturating_sub(bits);
+                let mut t = [0u8; 32];
+                t[31 - (p / 8) as usize] = 1 << (p % 8);
+                Target(t).to_compact()
+            }
+            Difficulty::Target(target) => target.to_compact(),
+        }
+    }
+
+    /// Expected number of hashes per solution (`1 / P(hash meets)`).
+    pub fn expected_hashes(&self) -> f64 {
+        match *self {
+            Difficulty::LeadingZeroBits(bits) => 2f64.powi(bits as i32),
+            Difficulty::Target(target) => 2f64.powi(256) / target.to_f64(),
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Difficulty::LeadingZeroBits(bits) if bits.is_multiple_of(4) => {
+                write!(f, "{}", bits / 4)
+            }
+            Difficulty::LeadingZeroBits(bits) => write!(f, "{}b", bits),
+            Difficulty::Target(target) => {
+                let compact = target.to_compact();
+                if Target::from_compact(compact) == Ok(target) {
+                    write!(f, "0x{:08x}", compact)
+                } else {
+                    write!(f, "{}", target)
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = DifficultyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_err = || DifficultyError::Parse(s.to_string());
+        let is_hex = |t: &str| !t.is_empty() && t.bytes().all(|c| c.is_ascii_hexdigit());
+
+        let digits = s.strip_prefix("0x").unwrap_or(s);
+        if digits.len() == 64 && is_hex(digits) {
+            let mut out = [0u8; 32];
+            hex::decode_to_slice(digits, &mut out).map_err(|_| parse_err())?;
+            return Ok(Difficulty::Target(Target(out)));
+        }
+        if s.starts_with("0x") {
+            if digits.len() > 8 || !is_hex(digits) {
+                return Err(parse_err());
+            }
+            let bits = u32::from_str_radix(digits, 16).map_err(|_| parse_err())?;
+            return Target::from_compact(bits).map(Difficulty::Target);
+        }
+        if let Some(bits) = s.strip_suffix('b') {
+            let bits = bits.parse().map_err(|_| parse_err())?;
+            return Ok(Difficulty::LeadingZeroBits(bits));
+        }
+        s.parse()
+            .map(Difficulty::hex_digits)
+            .map_err(|_| parse_err())
+    }
+}
+
+/// Serialized in its text form, e.g. `"6"` or `"0x1d00ffff"`.
+impl Serialize for Difficulty {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Difficulty {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Why a difficulty was rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DifficultyError {
+    /// More leading zero bits than a digest has.
+    TooManyBits(u32),
+    /// `hash < 0` never holds.
+    ZeroTarget,
+    /// Compact encoding with the sign bit set.
+    NegativeCompact(u32),
+    /// Compact encoding whose value does not fit in 256 bits.
+    CompactOverflow(u32),
+    /// Text that is none of the accepted forms.
+    Parse(String),
+}
+
+impl fmt::Display for DifficultyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DifficultyError::TooManyBits(bits) => write!(
+                f,
+                "difficulty of {} leading zero bits is impossible: a hash has only {} bits",
+                bits,
+                Difficulty::MAX_BITS
+            ),
+            DifficultyError::ZeroTarget => f.write_str("a target of zero can never be met"),
+            DifficultyError::NegativeCompact(bits) => {
+                write!(f, "compact target 0x{:08x} is negative", bits)
+            }
+            DifficultyError::CompactOverflow(bits) => {
+                write!(f, "compact target 0x{:08x} does not fit in 256 bits", bits)
+            }
+            DifficultyError::Parse(s) => write!(
+                f,
+                "`{}` is not a difficulty (expected N hex zeros, Nb zero bits, 0xNBITS or a 64-digit hex target)",
+                s
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DifficultyError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::PrefixHasher;
+
+    /// 2^exp as a target (`exp < 256`).
+    fn pow2(exp: u32) -> Target {
+        let mut t = [0u8; 32];
+        t[31 - (exp / 8) as usize] = 1 << (exp % 8);
+        Target(t)
+    }
+
+    /// A digest with exactly `zeros` leading zero bits.
+    fn with_leading_zeros(zeros: u32) -> Hash {
+        let mut h = [0xffu8; 32];
+        if zeros == 256 {
+            return [0; 32];
+        }
+        for bit in 0..=zeros {
+            let mask = 0x80 >> (bit % 8);
+            if bit == zeros {
+                h[(bit / 8) as usize] |= mask;
+            } else {
+                h[(bit / 8) as usize] &= !mask;
+            }
+        }
+        h
+    }
+
+    fn sample_hashes() -> Vec<Hash> {
+        let mut hashes: Vec<Hash> = vec![[0; 32], [0xff; 32]];
+        // A single nonzero nibble at every position, high and low.
+        for i in 0..32 {
+            for b in [0x10, 0x01] {
+                let mut h = [0; 32];
+                h[i] = b;
+                hashes.push(h);
+            }
+        }
+        hashes.extend((0..=256).map(with_leading_zeros));
+        let hasher = PrefixHasher::new("cmkl-pow");
+        hashes.extend((0..2000).map(|n| hasher.digest(n)));
+        hashes
+    }
+
+    #[test]
+    fn bits_and_targets_agree_with_hex_rule_at_multiples_of_four() {
+        for h in &sample_hashes() {
+            let hex_hash = hex::encode(h);
+            for d in 0..=64u32 {
+                let by_hex = hex_hash.bytes().take(d as usize).all(|c| c == b'0');
+                assert_eq!(
+                    Difficulty::hex_digits(d).meets(h),
+                    by_hex,
+                    "{hex_hash} d={d}"
+                );
+                if d > 0 {
+                    // d hex zeros <=> hash < 2^(256 - 4d)
+                    let target = Difficulty::Target(pow2(256 - 4 * d));
+                    assert_eq!(target.meets(h), by_hex, "{hex_hash} target d={d}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn leading_zero_bits_are_bit_granular() {
+        for zeros in 0..=256 {
+            let h = with_leading_zeros(zeros);
+            for bits in 0..=256 {
+                assert_eq!(
+                    Difficulty::LeadingZeroBits(bits).meets(&h),
+                    bits <= zeros,
+                    "zeros={zeros} bits={bits}"
+                );
+            }
+        }
+        assert_eq!(Difficulty::LeadingZeroBits(22).expected_hashes(), 4194304.0);
+        assert_eq!(Difficulty::Target(pow2(234)).expected_hashes(), 4194304.0);
+    }
+
+    #[test]
+    fn compact_nbits_round_trips() {
+        let genesis = Target::from_compact(0x1d00ffff).unwrap();
+        assert_eq!(
+            genesis.to_string(),
+            format!("00000000ffff{}", "0".repeat(52))
+        );
+        assert_eq!(genesis.to_compact(), 0x1d00ffff);
+
+        // Vectors from Bitcoin Core's arith_uint256 tests.
+        for (bits, expect_low, compact) in [
+            (0x0000_0000, 0u32, 0x0000_0000),
+            (0x0112_3456, 0x12, 0x0112_0000),
+            (0x0200_8000, 0x80, 0x0200_8000),
+            (0x0500_9234, 0x9234_0000, 0x0500_9234),
+            (0x0412_3456, 0x1234_5600, 0x0412_3456),
+        ] {
+            let t = Target::from_compact(bits).unwrap();
+            assert_eq!(
+                u32::from_be_bytes(t.0[28..].try_into().unwrap()),
+                expect_low
+            );
+            assert_eq!(t.to_compact(), compact, "0x{bits:08x}");
+        }
+        assert_eq!(
+            Target::from_compact(0x0492_3456),
+            Err(DifficultyError::NegativeCompact(0x0492_3456))
+        );
+        assert_eq!(
+            Target::from_compact(0xff12_3456),
+            Err(DifficultyError::CompactOverflow(0xff12_3456))
+        );
+    }
+
+    #[test]
+    fn compact_bits_matches_the_rule() {
+        assert_eq!(
+            Difficulty::Target(Target::from_compact(0x1d00ffff).unwrap()).compact_bits(),
+            0x1d00ffff
+        );
+        // 32 zero bits: hash < 2^224 = 0x01 followed by 28 zero bytes.
+        assert_eq!(Difficulty::LeadingZeroBits(32).compact_bits(), 0x1d01_0000);
+        assert_eq!(Difficulty::LeadingZeroBits(1).compact_bits(), 0x2100_8000);
+        assert_eq!(Difficulty::LeadingZeroBits(0).compact_bits(), 0x2100_ffff);
+        for bits in [4, 22, 24, 100] {
+            let t = Target::from_compact(Difficulty::LeadingZeroBits(bits).compact_bits()).unwrap();
+            let mut below = t.0;
+            // The largest hash below the target has exactly `bits` zeros.
+            for b in below.iter_mut().rev() {
+                let (v, borrow) = b.overflowing_sub(1);
+                *b = v;
+                if !borrow {
+                    break;
+                }
+            }
+            assert!(Difficulty::LeadingZeroBits(bits).meets(&below), "{bits}");
+            assert!(
+                !Difficulty::LeadingZeroBits(bits + 1).meets(&below),
+                "{bits}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_and_display_round_trip() {
+        let genesis = Difficulty::Target(Target::from_compact(0x1d00ffff).unwrap());
+        let odd = Difficulty::Target(Target([0x0f; 32]));
+        for (text, d) in [
+            ("6", Difficulty::LeadingZeroBits(24)),
+            ("22b", Difficulty::LeadingZeroBits(22)),
+            ("0x1d00ffff", genesis),
+        ] {
+            assert_eq!(text.parse::<Difficulty>(), Ok(d));
+            assert_eq!(d.to_string(), text);
+        }
+        assert_eq!(odd.to_string().parse::<Difficulty>(), Ok(odd));
+        assert_eq!("24b".parse::<Difficulty>().unwrap().to_string(), "6");
+
+        for bad in ["", "six", "b", "0x", "0x123456789", "0xzz", "-1"] {
+            assert!(bad.parse::<Difficulty>().is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn impossible_difficulties_are_rejected() {
+        assert_eq!(Difficulty::hex_digits(64).validate(), Ok(()));
+        assert_eq!(
+            Difficulty::hex_digits(65).validate(),
+            Err(DifficultyError::TooManyBits(260))
+        );
+        assert_eq!(
+            Difficulty::Target(Target([0; 32])).validate(),
+            Err(DifficultyError::ZeroTarget)
+        );
+    }
+}
@@ -0,0 +1,526 @@
+// Hashing primitives for the PoW hot loop.
+//
+// Every candidate is H(prefix + ":" + nonce) for a configurable 256-bit
+// hash H (SHA-256 unless told otherwise). The naive version allocates twice
+// per hash (`nonce.to_string()` and `hex::encode`), which dominates the cost
+// once the hash itself is fast. Here instead:
+//
+//   - the hasher state after `prefix:` is computed once and cloned per nonce,
+//   - the nonce is formatted into a stack buffer,
+//
+// and the difficulty is checked directly on the 32-byte digest (see
+// `difficulty.rs`), so nothing is allocated until a solution is found.
+//
+// Each algorithm implements `PowHash`. `PrefixHasher` picks one at run time
+// (`--algorithm blake3`), so the search drivers stay the same for all of
+// them:
+//
+//   sha256      SHA-256 (the default)
+//   sha256d     SHA-256 of the SHA-256 digest, as in Bitcoin
+//   sha512-256  SHA-512/256 (FIPS 180-4, not a truncated SHA-512)
+//   blake2b     BLAKE2b with a 32-byte output
+//   blake2s     BLAKE2s-256
+//   blake3      BLAKE3
+//
+// A search can also hash a block header instead of `prefix:nonce` (see
+// `header.rs`); `NonceHasher` is whichever of the two a search uses.
+
+use std::fmt;
+use std::str::FromStr;
+
+use blake2::digest::consts::U32;
+use blake2::{Blake2b, Blake2s256};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256, Sha512_256};
+
+use crate::header::{BlockHeader, HeaderHasher};
+
+/// A 256-bit PoW digest.
+pub type Hash = [u8; 32];
+
+/// Longest decimal representation of a `u64` (`u64::MAX` has 20 digits).
+const MAX_U64_DIGITS: usize = 20;
+
+/// A 256-bit hash of `prefix:nonce` with the prefix absorbed up front.
+pub trait PowHash: Clone + Send + Sync + 'static {
+    /// The algorithm this hasher computes.
+    const ALGORITHM: Algorithm;
+
+    fn new(prefix: &str) -> Self;
+
+    /// H(prefix + ":" + nonce), without allocating.
+    fn digest(&self, nonce: u64) -> Hash;
+}
+
+/// The `Digest` state after `prefix:`.
+fn absorb_prefix<D: Digest>(prefix: &str) -> D {
+    let mut base = D::new();
+    base.update(prefix.as_bytes());
+    base.update(b":");
+    base
+}
+
+/// Finish `base` over the decimal digits of `nonce`.
+fn finish<D: Digest + Clone>(base: &D, nonce: u64) -> Hash {
+    let mut buf = [0u8; MAX_U64_DIGITS];
+    let mut h = base.clone();
+    h.update(decimal(nonce, &mut buf));
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&h.finalize());
+    out
+}
+
+/// SHA-256.
+#[derive(Clone)]
+pub struct Sha256Hasher {
+    base: Sha256,
+}
+
+impl PowHash for Sha256Hasher {
+    const ALGORITHM: Algorithm = Algorithm::Sha256;
+
+    fn new(prefix: &str) -> Self {
+        Self {
+            base: absorb_prefix(prefix),
+        }
+    }
+
+    fn digest(&self, nonce: u64) -> Hash {
+        finish(&self.base, nonce)
+    }
+}
+
+/// SHA256(SHA256(prefix:nonce)).
+#[derive(Clone)]
+pub struct DoubleSha256Hasher {
+    inner: Sha256Hasher,
+}
+
+impl PowHash for DoubleSha256Hasher {
+    const ALGORITHM: Algorithm = Algorithm::DoubleSha256;
+
+    fn new(prefix: &str) -> Self {
+        Self {
+            inner: Sha256Hasher::new(prefix),
+        }
+    }
+
+    fn digest(&self, nonce: u64) -> Hash {
+        Sha256::digest(self.inner.digest(nonce)).into()
+    }
+}
+
+/// SHA-512/256.
+#[derive(Clone)]
+pub struct Sha512_256Hasher {
+    base: Sha512_256,
+}
+
+impl PowHash for Sha512_256Hasher {
+    const ALGORITHM: Algorithm = Algorithm::Sha512_256;
+
+    fn new(prefix: &str) -> Self {
+        Self {
+            base: absorb_prefix(prefix),
+        }
+    }
+
+    fn digest(&self, nonce: u64) -> Hash {
+        finish(&self.base, nonce)
+    }
+}
+
+/// BLAKE2b with a 32-byte output (not a truncated BLAKE2b-512).
+#[derive(Clone)]
+pub struct Blake2bHasher {
+    base: Blake2b<U32>,
+}
+
+impl PowHash for Blake2bHasher {
+    const ALGORITHM: Algorithm = Algorithm::Blake2b;
+
+    fn new(prefix: &str) -> Self {
+        Self {
+            base: absorb_prefix(prefix),
+        }
+    }
+
+    fn digest(&self, nonce: u64) -> Hash {
+        finish(&self.base, nonce)
+    }
+}
+
+/// BLAKE2s-256.
+#[derive(Clone)]
+pub struct Blake2sHasher {
+    base: Blake2s256,
+}
+
+impl PowHash for Blake2sHasher {
+    const ALGORITHM: Algorithm = Algorithm::Blake2s;
+
+    fn new(prefix: &str) -> Self {
+        Self {
+            base: absorb_prefix(prefix),
+        }
+    }
+
+    fn digest(&self, nonce: u64) -> Hash {
+        finish(&self.base, nonce)
+    }
+}
+
+/// BLAKE3 with its default 32-byte output.
+#[derive(Clone)]
+pub struct Blake3Hasher {
+    base: blake3::Hasher,
+}
+
+impl PowHash for Blake3Hasher {
+    const ALGORITHM: Algorithm = Algorithm::Blake3;
+
+    fn new(prefix: &str) -> Self {
+        let mut base = blake3::Hasher::new();
+        base.update(prefix.as_bytes());
+        base.update(b":");
+        Self { base }
+    }
+
+    fn digest(&self, nonce: u64) -> Hash {
+        let mut buf = [0u8; MAX_U64_DIGITS];
+        let mut h = self.base.clone();
+        h.update(decimal(nonce, &mut buf));
+        *h.finalize().as_bytes()
+    }
+}
+
+/// The hash algorithms a search can use.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    #[default]
+    Sha256,
+    DoubleSha256,
+    Sha512_256,
+    Blake2b,
+    Blake2s,
+    Blake3,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 6] = [
+        Algorithm::Sha256,
+        Algorithm::DoubleSha256,
+        Algorithm::Sha512_256,
+        Algorithm::Blake2b,
+        Algorithm::Blake2s,
+        Algorithm::Blake3,
+    ];
+
+    /// The name used in configuration and output.
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Sha256 => "sha256",
+            Algorithm::DoubleSha256 => "sha256d",
+            Algorithm::Sha512_256 => "sha512-256",
+            Algorithm::Blake2b => "blake2b",
+            Algorithm::Blake2s => "blake2s",
+            Algorithm::Blake3 => "blake3",
+        }
+    }
+
+    /// H(data), for candidates that are not `prefix:nonce`.
+    pub fn digest(self, data: &[u8]) -> Hash {
+        match self {
+            Algorithm::Sha256 => Sha256::digest(data).into(),
+            Algorithm::DoubleSha256 => Sha256::digest(Sha256::digest(data)).into(),
+            Algorithm::Sha512_256 => Sha512_256::digest(data).into(),
+            Algorithm::Blake2b => Blake2b::<U32>::digest(data).into(),
+            Algorithm::Blake2s => Blake2s256::digest(data).into(),
+            Algorithm::Blake3 => *blake3::hash(data).as_bytes(),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Algorithm::ALL
+            .into_iter()
+            .find(|a| a.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let names: Vec<&str> = Algorithm::ALL.iter().map(|a| a.name()).collect();
+                format!("expected one of {}", names.join(", "))
+            })
+    }
+}
+
+impl Serialize for Algorithm {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Algorithm {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// A `PowHash` chosen at run time.
+///
+/// One `match` per digest is noise next to the hash itself, and it keeps
+/// the drivers free of generics. BLAKE3's state is ~2 KB, so it is boxed to
+/// keep the others small.
+#[derive(Clone)]
+pub enum PrefixHasher {
+    Sha256(Sha256Hasher),
+    DoubleSha256(DoubleSha256Hasher),
+    Sha512_256(Sha512_256Hasher),
+    Blake2b(Blake2bHasher),
+    Blake2s(Blake2sHasher),
+    Blake3(Box<Blake3Hasher>),
+}
+
+impl PrefixHasher {
+    /// SHA-256 with `prefix:` already absorbed.
+    pub fn new(prefix: &str) -> Self {
+        Self::with_algorithm(Algorithm::Sha256, prefix)
+    }
+
+    pub fn with_algorithm(algorithm: Algorithm, prefix: &str) -> Self {
+        match algorithm {
+            Algorithm::Sha256 => PrefixHasher::Sha256(PowHash::new(prefix)),
+            Algorithm::DoubleSha256 => PrefixHasher::DoubleSha256(PowHash::new(prefix)),
+            Algorithm::Sha512_256 => PrefixHasher::Sha512_256(PowHash::new(prefix)),
+            Algorithm::Blake2b => PrefixHasher::Blake2b(PowHash::new(prefix)),
+            Algorithm::Blake2s => PrefixHasher::Blake2s(PowHash::new(prefix)),
+            Algorithm::Blake3 => PrefixHasher::Blake3(Box::new(PowHash::new(prefix))),
+        }
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        match self {
+            PrefixHasher::Sha256(_) => Algorithm::Sha256,
+            PrefixHasher::DoubleSha256(_) => Algorithm::DoubleSha256,
+            PrefixHasher::Sha512_256(_) => Algorithm::Sha512_256,
+            PrefixHasher::Blake2b(_) => Algorithm::Blake2b,
+            PrefixHasher::Blake2s(_) => Algorithm::Blake2s,
+            PrefixHasher::Blake3(_) => Algorithm::Blake3,
+        }
+    }
+
+    /// H(prefix + ":" + nonce), without allocating.
+    #[inline]
+    pub fn digest(&self, nonce: u64) -> Hash {
+        match self {
+            PrefixHasher::Sha256(h) => h.digest(nonce),
+            PrefixHasher::DoubleSha256(h) => h.digest(nonce),
+            PrefixHasher::Sha512_256(h) => h.digest(nonce),
+            PrefixHasher::Blake2b(h) => h.digest(nonce),
+            PrefixHasher::Blake2s(h) => h.digest(nonce),
+            PrefixHasher::Blake3(h) => h.digest(nonce),
+        }
+    }
+}
+
+/// What a search hashes for each nonce.
+#[derive(Clone)]
+pub enum NonceHasher {
+    /// `prefix:nonce`.
+    Prefix(PrefixHasher),
+    /// A block header with the nonce (and extra nonce) filled in.
+    Header(HeaderHasher),
+}
+
+impl NonceHasher {
+    /// Hash `header` if there is one, `prefix:nonce` otherwise.
+    pub fn new(algorithm: Algorithm, prefix: &str, header: Option<&BlockHeader>) -> Self {
+        match header {
+            Some(header) => NonceHasher::Header(HeaderHasher::new(algorithm, header)),
+            None => NonceHasher::Prefix(PrefixHasher::with_algorithm(algorithm, prefix)),
+        }
+    }
+
+    #[inline]
+    pub fn digest(&self, nonce: u64) -> Hash {
+        match self {
+            NonceHasher::Prefix(h) => h.digest(nonce),
+            NonceHasher::Header(h) => h.digest(nonce),
+        }
+    }
+}
+
+/// Write `n` in decimal into the end of `buf` and return the digits.
+fn decimal(mut n: u64, buf: &mut [u8; MAX_U64_DIGITS]) -> &[u8] {
+    let mut i = buf.len();
+    loop {
+        i -= 1;
+        buf[i] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    &buf[i..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimal_matches_to_string() {
+        let mut buf = [0u8; MAX_U64_DIGITS];
+        for n in [0, 7, 10, 12_345, 50_000_000_000, u64::MAX] {
+            assert_eq!(decimal(n, &mut buf), n.to_string().as_bytes());
+        }
+    }
+
+    #[test]
+    fn digest_matches_naive_hash() {
+        let hasher = PrefixHasher::new("cmkl-pow");
+        for nonce in [0, 1, 2247, 50_000_000_000, u64::MAX] {
+            let naive: Hash = Sha256::digest(format!("cmkl-pow:{}", nonce)).into();
+            assert_eq!(hasher.digest(nonce), naive);
+        }
+    }
+
+    /// H("cmkl-pow:0") and H("cmkl-pow:12345"), as given by Python's
+    /// `hashlib` (SHA-2 and BLAKE2) and the `blake3` crate (BLAKE3, which is
+    /// itself checked against the reference vectors below).
+    #[test]
+    fn digests_are_pinned() {
+        let cases = [
+            (
+                Algorithm::Sha256,
+                0,
+                "0105708a3cd6051690d41a8e938f4853f66a306717e765a4d4de5e3054dbffcd",
+            ),
+            (
+                Algorithm::Sha256,
+                12345,
+                "e60da54df977d8bdb1a264761f19556ad76e4beaddaa02fd0add9f9abda433ef",
+            ),
+            (
+                Algorithm::DoubleSha256,
+                0,
+                "bff514f5809b5551ff521bcf507936256c3325b26ed0c8e83a269ba3a7d04ce5",
+            ),
+            (
+                Algorithm::DoubleSha256,
+                12345,
+                "89e141c4c224c5787a29fc965f6a7e59d4168edfbca2d57a5a10ca2f381c1f3a",
+            ),
+            (
+                Algorithm::Sha512_256,
+                0,
+                "9920b549c302b230818e346b6220fc98ea57571d06106f3abc348d41f807e5b3",
+            ),
+            (
+                Algorithm::Sha512_256,
+                12345,
+                "08467f3267df6011c66e3dba59234e743ad4c9d4490cdd4d2dea9a60ad3e4a6b",
+            ),
+            (
+                Algorithm::Blake2b,
+                0,
+                "67660da6183768a1bd38ae8fc2165a4041e591db4a5a6b553e46bba3dbd4f2a0",
+            ),
+            (
+                Algorithm::Blake2b,
+                12345,
+                "4834333ce23157ab4e11a35b25fcffe5ca30e9bfbb1dfe964e784f8a769ccadb",
+            ),
+            (
+                Algorithm::Blake2s,
+                0,
+                "65fe27236dc6fb5d149499669a376e5713d092a7fafb5069facede8162ed8261",
+            ),
+            (
+                Algorithm::Blake2s,
+                12345,
+                "0960d4821faf164f060b385e577be8ac401fe2e0906d878e892ac3bc325a361d",
+            ),
+            (
+                Algorithm::Blake3,
+                0,
+                "0ed9926f4de9a5ba2ab0f6c1634c467da003686719507ed8953d4f9f620c683b",
+            ),
+            (
+                Algorithm::Blake3,
+                12345,
+                "eec0ff585505610ee4412177d0f219011ff2f6d9ca313b1280ec8590ad7a734b",
+            ),
+        ];
+        for (algorithm, nonce, want) in cases {
+            let got = PrefixHasher::with_algorithm(algorithm, "cmkl-pow").digest(nonce);
+            assert_eq!(hex::encode(got), want, "{algorithm} nonce={nonce}");
+        }
+    }
+
+    #[test]
+    fn blake3_matches_reference_vectors() {
+        let hash = |input: &[u8]| hex::encode(blake3::hash(input).as_bytes());
+        assert_eq!(
+            hash(b""),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        assert_eq!(
+            hash(b"abc"),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+    }
+
+    #[test]
+    fn digesting_bytes_matches_the_prefix_hashers() {
+        for algorithm in Algorithm::ALL {
+            let hasher = PrefixHasher::with_algorithm(algorithm, "cmkl-pow");
+            assert_eq!(
+                algorithm.digest(b"cmkl-pow:12345"),
+                hasher.digest(12345),
+                "{algorithm}"
+            );
+            let nonce_hasher = NonceHasher::new(algorithm, "cmkl-pow", None);
+            assert_eq!(nonce_hasher.digest(7), hasher.digest(7));
+        }
+    }
+
+    #[test]
+    fn algorithm_names_round_trip() {
+        for algorithm in Algorithm::ALL {
+            assert_eq!(algorithm.name().parse::<Algorithm>(), Ok(algorithm));
+            let hasher = PrefixHasher::with_algorithm(algorithm, "p");
+            assert_eq!(hasher.algorithm(), algorithm);
+            let json = serde_json::to_string(&algorithm).unwrap();
+            assert_eq!(serde_json::from_str::<Algorithm>(&json).unwrap(), algorithm);
+        }
+        assert_eq!("SHA256D".parse::<Algorithm>(), Ok(Algorithm::DoubleSha256));
+        assert!("md5".parse::<Algorithm>().is_err());
+        assert_eq!(Algorithm::default(), Algorithm::Sha256);
+    }
+
+    #[test]
+    fn every_algorithm_agrees_with_its_pow_hash_impl() {
+        fn check<H: PowHash>() {
+            let direct = H::new("cmkl-pow");
+            let chosen = PrefixHasher::with_algorithm(H::ALGORITHM, "cmkl-pow");
+            for nonce in [0, 9, 10, 99_999, u64::MAX] {
+                assert_eq!(direct.digest(nonce), chosen.digest(nonce));
+            }
+        }
+        check::<Sha256Hasher>();
+        check::<DoubleSha256Hasher>();
+        check::<Sha512_256Hasher>();
+        check::<Blake2bHasher>();
+        check::<Blake2sHasher>();
+        check::<Blake3Hasher>();
+    }
+}
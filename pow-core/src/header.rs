@@ -0,0 +1,397 @@
+// Block-header candidates, for mining-style searches.
+//
+// Instead of `prefix:nonce`, a header search hashes a Bitcoin-style block
+// header, integers little-endian:
+//
+//   offset  bytes  field
+//        0      4  version
+//        4     32  previous block hash
+//       36     32  merkle root
+//       68      4  time (Unix seconds)
+//       72      4  bits (compact target, see `difficulty.rs`)
+//       76   4, 8  nonce: u32 as in Bitcoin (an 80-byte header) or u64 (84)
+//
+// Digests are reported the way block explorers show block hashes: byte-
+// reversed, i.e. as the little-endian 256-bit number Bitcoin compares with
+// the target. `Difficulty` then applies unchanged, and the genesis block
+// hashes (with sha256d) to the familiar 000000000019d6...8ce26f. The
+// previous hash and merkle root are written in that same display order on
+// the command line.
+//
+// A u32 nonce runs out after 2^32 hashes, which a fast miner gets through
+// in seconds. Miners then bump an extra nonce in the coinbase transaction,
+// which changes the merkle root, and start the nonce over. Here the
+// search's u64 nonce `n` carries both:
+//
+//   extra nonce = n >> 32          header nonce = n as u32
+//
+// and the merkle root under extra nonce `e > 0` is
+// SHA256d(merkle root || e as u64 LE), standing in for rehashing the
+// coinbase up its merkle branch. The queue still sees one flat nonce range;
+// the rollover happens wherever the range crosses a multiple of 2^32. With
+// u64 nonces the extra nonce is always 0.
+//
+// As text (printed output, JSON, checkpoints) a header is the hex of its
+// bytes with a zero nonce: 160 digits for u32 nonces, 168 for u64.
+
+use std::cell::Cell;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+
+use crate::hash::{Algorithm, Hash};
+
+/// Offset of the merkle root in the header.
+const MERKLE_OFFSET: usize = 36;
+
+/// Offset of the nonce in the header.
+const NONCE_OFFSET: usize = 76;
+
+/// Longest header: with a u64 nonce.
+const MAX_HEADER_LEN: usize = NONCE_OFFSET + 8;
+
+/// Width of the header's nonce field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NonceWidth {
+    /// Bitcoin's u32, with extra-nonce rollover.
+    #[default]
+    U32,
+    /// A u64 that covers the whole search range.
+    U64,
+}
+
+impl NonceWidth {
+    /// Bytes the nonce takes in the header.
+    pub fn bytes(self) -> usize {
+        match self {
+            NonceWidth::U32 => 4,
+            NonceWidth::U64 => 8,
+        }
+    }
+}
+
+impl fmt::Display for NonceWidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.bytes() * 8)
+    }
+}
+
+impl FromStr for NonceWidth {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "32" | "u32" => Ok(NonceWidth::U32),
+            "64" | "u64" => Ok(NonceWidth::U64),
+            _ => Err("expected `32` or `64`".to_string()),
+        }
+    }
+}
+
+/// A block header with everything but the nonce filled in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub version: u32,
+    /// In header byte order (the reverse of how it is displayed).
+    pub prev_hash: Hash,
+    /// In header byte order; the root under extra nonce 0.
+    pub merkle_root: Hash,
+    pub time: u32,
+    /// Compact target, as in `Difficulty::compact_bits`.
+    pub bits: u32,
+    pub nonce_width: NonceWidth,
+}
+
+impl BlockHeader {
+    /// A version-1 header with zero hashes and time, carrying `bits`.
+    pub fn new(bits: u32) -> Self {
+        Self {
+            version: 1,
+            prev_hash: [0; 32],
+            merkle_root: [0; 32],
+            time: 0,
+            bits,
+            nonce_width: NonceWidth::U32,
+        }
+    }
+
+    /// Header length in bytes: 80 or 84.
+    pub fn size(&self) -> usize {
+        NONCE_OFFSET + self.nonce_width.bytes()
+    }
+
+    /// Split a search nonce into (extra nonce, header nonce).
+    pub fn split_nonce(&self, n: u64) -> (u64, u64) {
+        match self.nonce_width {
+            NonceWidth::U32 => (n >> 32, n & 0xffff_ffff),
+            NonceWidth::U64 => (0, n),
+        }
+    }
+
+    /// The merkle root under extra nonce `extra`.
+    pub fn merkle_root_for(&self, extra: u64) -> Hash {
+        if extra == 0 {
+            return self.merkle_root;
+        }
+        let mut data = [0u8; 40];
+        data[..32].copy_from_slice(&self.merkle_root);
+        data[32..].copy_from_slice(&extra.to_le_bytes());
+        Sha256::digest(Sha256::digest(data)).into()
+    }
+
+    /// The serialized header for search nonce `n`.
+    pub fn to_bytes(&self, n: u64) -> Vec<u8> {
+        let (extra, nonce) = self.split_nonce(n);
+        let mut buf = self.template();
+        buf[MERKLE_OFFSET..MERKLE_OFFSET + 32].copy_from_slice(&self.merkle_root_for(extra));
+        self.put_nonce(&mut buf, nonce);
+        buf[..self.size()].to_vec()
+    }
+
+    /// The header bytes with a zero nonce, padded to `MAX_HEADER_LEN`.
+    fn template(&self) -> [u8; MAX_HEADER_LEN] {
+        let mut buf = [0u8; MAX_HEADER_LEN];
+        buf[0..4].copy_from_slice(&self.version.to_le_bytes());
+        buf[4..36].copy_from_slice(&self.prev_hash);
+        buf[MERKLE_OFFSET..MERKLE_OFFSET + 32].copy_from_slice(&self.merkle_root);
+        buf[68..72].copy_from_slice(&self.time.to_le_bytes());
+        buf[72..76].copy_from_slice(&self.bits.to_le_bytes());
+        buf
+    }
+
+    fn put_nonce(&self, buf: &mut [u8; MAX_HEADER_LEN], nonce: u64) {
+        let width = self.nonce_width.bytes();
+        buf[NONCE_OFFSET..NONCE_OFFSET + width].copy_from_slice(&nonce.to_le_bytes()[..width]);
+    }
+}
+
+/// Parse a 64-digit hash in display order (as block explorers print it)
+/// into header byte order.
+pub fn parse_display_hash(s: &str) -> Result<Hash, HeaderError> {
+    let mut hash = [0u8; 32];
+    hex::decode_to_slice(s, &mut hash).map_err(|_| HeaderError::Hash(s.to_string()))?;
+    hash.reverse();
+    Ok(hash)
+}
+
+impl fmt::Display for BlockHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(&self.template()[..self.size()]))
+    }
+}
+
+impl FromStr for BlockHeader {
+    type Err = HeaderError;
+
+    /// Any nonce in the input is ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s).map_err(|_| HeaderError::Header(s.to_string()))?;
+        let nonce_width = match bytes.len() {
+            80 => NonceWidth::U32,
+            84 => NonceWidth::U64,
+            _ => return Err(HeaderError::Header(s.to_string())),
+        };
+        let u32_at = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+        let hash_at = |i: usize| -> Hash { bytes[i..i + 32].try_into().unwrap() };
+        Ok(BlockHeader {
+            version: u32_at(0),
+            prev_hash: hash_at(4),
+            merkle_root: hash_at(MERKLE_OFFSET),
+            time: u32_at(68),
+            bits: u32_at(72),
+            nonce_width,
+        })
+    }
+}
+
+/// Serialized in its text form, the header's hex.
+impl Serialize for BlockHeader {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for BlockHeader {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Why a header or hash does not parse.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HeaderError {
+    /// Not the hex of an 80- or 84-byte header.
+    Header(String),
+    /// Not a 64-digit hex hash.
+    Hash(String),
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeaderError::Header(s) => write!(
+                f,
+                "`{}` is not a block header (expected 160 or 168 hex digits)",
+                s
+            ),
+            HeaderError::Hash(s) => write!(f, "`{}` is not a hash (expected 64 hex digits)", s),
+        }
+    }
+}
+
+impl std::error::Error for HeaderError {}
+
+/// Hashes `header` for any search nonce.
+///
+/// Each search thread owns one, so the merkle root of the latest extra
+/// nonce is cached in a `Cell`: it changes once every 2^32 nonces.
+#[derive(Clone)]
+pub struct HeaderHasher {
+    algorithm: Algorithm,
+    header: BlockHeader,
+    template: [u8; MAX_HEADER_LEN],
+    /// (extra nonce, its merkle root).
+    merkle: Cell<(u64, Hash)>,
+}
+
+impl HeaderHasher {
+    pub fn new(algorithm: Algorithm, header: &BlockHeader) -> Self {
+        Self {
+            algorithm,
+            header: *header,
+            template: header.template(),
+            merkle: Cell::new((0, header.merkle_root)),
+        }
+    }
+
+    /// H(header with nonce `n`), byte-reversed into display order.
+    #[inline]
+    pub fn digest(&self, n: u64) -> Hash {
+        let (extra, nonce) = self.header.split_nonce(n);
+        let mut buf = self.template;
+        if extra != 0 {
+            let (cached, mut root) = self.merkle.get();
+            if cached != extra {
+                root = self.header.merkle_root_for(extra);
+                self.merkle.set((extra, root));
+            }
+            buf[MERKLE_OFFSET..MERKLE_OFFSET + 32].copy_from_slice(&root);
+        }
+        self.header.put_nonce(&mut buf, nonce);
+
+        let mut hash = self.algorithm.digest(&buf[..self.header.size()]);
+        hash.reverse();
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::{Difficulty, Target};
+
+    /// Bitcoin's genesis block header, nonce included.
+    const GENESIS: &str = "01000000\
+        0000000000000000000000000000000000000000000000000000000000000000\
+        3ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a\
+        29ab5f49ffff001d1dac2b7c";
+
+    #[test]
+    fn genesis_block_hashes_like_bitcoin() {
+        let header: BlockHeader = GENESIS.parse().unwrap();
+        assert_eq!(header.version, 1);
+        assert_eq!(header.time, 1_231_006_505);
+        assert_eq!(header.bits, 0x1d00ffff);
+        assert_eq!(header.nonce_width, NonceWidth::U32);
+        assert_eq!(
+            header.merkle_root,
+            parse_display_hash("4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b")
+                .unwrap()
+        );
+
+        let hasher = HeaderHasher::new(Algorithm::DoubleSha256, &header);
+        let hash = hasher.digest(2_083_236_893);
+        assert_eq!(
+            hex::encode(hash),
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"
+        );
+        let target = Difficulty::Target(Target::from_compact(header.bits).unwrap());
+        assert!(target.meets(&hash));
+        assert_eq!(
+            header.to_bytes(2_083_236_893),
+            hex::decode(GENESIS).unwrap()
+        );
+    }
+
+    #[test]
+    fn headers_round_trip_as_hex() {
+        let mut header: BlockHeader = GENESIS.parse().unwrap();
+        // The nonce is not part of the template.
+        assert!(header.to_string().ends_with("ffff001d00000000"));
+        assert_eq!(header.to_string().parse(), Ok(header));
+
+        header.nonce_width = NonceWidth::U64;
+        assert_eq!(header.size(), 84);
+        assert_eq!(header.to_string().len(), 168);
+        let json = serde_json::to_string(&header).unwrap();
+        assert_eq!(serde_json::from_str::<BlockHeader>(&json).unwrap(), header);
+
+        assert!("00".parse::<BlockHeader>().is_err());
+        assert!(parse_display_hash("xyz").is_err());
+        assert_eq!("u64".parse(), Ok(NonceWidth::U64));
+        assert!("16".parse::<NonceWidth>().is_err());
+    }
+
+    #[test]
+    fn u32_nonces_roll_over_into_the_extra_nonce() {
+        let header = BlockHeader::new(0x1d00ffff);
+        let n = (3 << 32) | 7;
+        assert_eq!(header.split_nonce(n), (3, 7));
+
+        let (before, after) = (header.to_bytes(7), header.to_bytes(n));
+        assert_eq!(before.len(), 80);
+        // Same header nonce, different merkle root, nothing else changed.
+        assert_eq!(before[NONCE_OFFSET..], after[NONCE_OFFSET..]);
+        assert_eq!(before[..MERKLE_OFFSET], after[..MERKLE_OFFSET]);
+        assert_ne!(
+            before[MERKLE_OFFSET..MERKLE_OFFSET + 32],
+            after[MERKLE_OFFSET..MERKLE_OFFSET + 32]
+        );
+        assert_ne!(header.merkle_root_for(1), header.merkle_root_for(2));
+
+        let wide = BlockHeader {
+            nonce_width: NonceWidth::U64,
+            ..header
+        };
+        assert_eq!(wide.split_nonce(n), (0, n));
+        assert_eq!(wide.to_bytes(n)[NONCE_OFFSET..], n.to_le_bytes());
+    }
+
+    #[test]
+    fn hasher_matches_the_serialized_header() {
+        for width in [NonceWidth::U32, NonceWidth::U64] {
+            let header = BlockHeader {
+                nonce_width: width,
+                ..GENESIS.parse().unwrap()
+            };
+            let hasher = HeaderHasher::new(Algorithm::Blake3, &header);
+            // Back and forth across extra nonces, to exercise the cache.
+            for n in [
+                5,
+                1 << 32,
+                (1 << 32) + 1,
+                9,
+                (2 << 32) + 5,
+                1 << 32,
+                u64::MAX,
+            ] {
+                let mut want = Algorithm::Blake3.digest(&header.to_bytes(n));
+                want.reverse();
+                assert_eq!(hasher.digest(n), want, "{width} n={n}");
+            }
+        }
+    }
+}
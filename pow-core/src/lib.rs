@@ -0,0 +1,34 @@
+// Shared proof-of-work library behind `assignment-2` and `lab-3`.
+//
+// Both binaries search for nonces such that H(prefix + ":" + nonce)
+// satisfies a difficulty rule. This crate holds the parts that must agree
+// between them:
+//
+//   hash       - allocation-free H(prefix:nonce) for SHA-2 and BLAKE hashes
+//   header     - Bitcoin-style block headers as an alternative candidate
+//   difficulty - leading-zero-bit and 256-bit target difficulty rules
+//   verify     - reading back and independently checking reported solutions
+//   checkpoint - saving and resuming partially searched ranges
+//   telemetry  - live progress lines and per-worker hash counts
+//   search     - the parallel search itself, behind a `PowSearch` builder
+//   net        - a TCP job server sharing that search with worker processes
+//   config     - command-line and config-file parameters for the binaries
+
+pub mod checkpoint;
+pub mod config;
+pub mod difficulty;
+pub mod hash;
+pub mod header;
+pub mod net;
+pub mod search;
+pub mod telemetry;
+pub mod verify;
+
+pub use checkpoint::{Checkpoint, CheckpointError, RangeSet};
+pub use config::{ConfigError, Options, SearchConfig, VerifyArgs};
+pub use difficulty::{Difficulty, DifficultyError, Target};
+pub use hash::{Algorithm, Hash, NonceHasher, PowHash, PrefixHasher};
+pub use header::{BlockHeader, HeaderError, NonceWidth};
+pub use search::{ChunkSize, PowReport, PowSearch, Schedule, Solution, Status, ThreadStats};
+pub use telemetry::{ProgressFormat, Reporter, WorkerCounters};
+pub use verify::{Discrepancy, ReportedSolution, SolutionSet, run_verify, verify};
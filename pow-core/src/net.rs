@@ -0,0 +1,846 @@
+// Distributed search: a job server and worker processes on local TCP.
+//
+// The server owns assignment-2's work queue and hands its chunks to worker
+// processes instead of threads. Everything is std-only: blocking sockets,
+// one thread per connection, and one JSON object per line.
+//
+//   submitter -> {"type":"submit","job":{"prefix":"cmkl-pow","difficulty":"5",...}}
+//   server    -> {"type":"accepted","job_id":1}
+//   server    -> {"type":"result","job_id":1,"solutions":[...],"hashes":3000000,...}
+//
+//   worker    -> {"type":"ready"}
+//   server    -> {"type":"work","job_id":1,"prefix":"cmkl-pow",...,"start":0,"len":1000000}
+//   worker    -> {"type":"done","job_id":1,"start":0,"len":1000000,"nonces":[422140]}
+//   worker    -> {"type":"ready"}
+//   ...
+//   server    -> {"type":"shutdown"}
+//
+// A `ready` is answered as soon as some job has a chunk to give, so idle
+// workers simply wait on their socket; jobs are served in submission order.
+// If a worker disconnects mid-chunk, or sends `ready` again without
+// reporting it, its chunk goes back to the job for another worker. The server re-hashes every nonce a worker reports, so a
+// faulty worker cannot make up solutions: a chunk with a bogus one is
+// redone by someone else. In k-smallest mode the server stops handing out
+// chunks above the k-th best nonce, like the in-process queue.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::difficulty::Difficulty;
+use crate::hash::{Algorithm, NonceHasher};
+use crate::header::BlockHeader;
+use crate::search::Solution;
+use crate::verify::ReportedSolution;
+
+/// Pause after a failed accept before trying the next connection.
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// One search, as submitted to the server.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Job {
+    pub prefix: String,
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub algorithm: Algorithm,
+    /// Hash this header instead of `prefix:nonce`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<BlockHeader>,
+    pub k: usize,
+    pub start: u64,
+    pub end: u64,
+    /// Report exactly the k smallest valid nonces.
+    #[serde(default)]
+    pub smallest: bool,
+}
+
+impl Job {
+    /// What the job hashes for each nonce.
+    pub fn hasher(&self) -> NonceHasher {
+        NonceHasher::new(self.algorithm, &self.prefix, self.header.as_ref())
+    }
+}
+
+/// What the server sends back when a job is over.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobResult {
+    pub job_id: u64,
+    /// Ascending by nonce, at most k.
+    pub solutions: Vec<ReportedSolution>,
+    /// Hashes computed by all workers for this job.
+    pub hashes: u64,
+    pub elapsed_ms: u64,
+}
+
+/// What a worker did before the server let it go.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WorkerReport {
+    pub chunks: u64,
+    pub hashes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Request {
+    Submit {
+        job: Job,
+    },
+    Ready,
+    Done {
+        job_id: u64,
+        start: u64,
+        len: u64,
+        nonces: Vec<u64>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Reply {
+    Accepted {
+        job_id: u64,
+    },
+    Result(JobResult),
+    Work {
+        job_id: u64,
+        prefix: String,
+        difficulty: Difficulty,
+        algorithm: Algorithm,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        header: Option<BlockHeader>,
+        start: u64,
+        len: u64,
+    },
+    Shutdown,
+    Error {
+        message: String,
+    },
+}
+
+/// A connection that carries one JSON message per line.
+struct Conn {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Conn {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        Ok(Self {
+            writer: stream.try_clone()?,
+            reader: BufReader::new(stream),
+        })
+    }
+
+    fn send<T: Serialize>(&mut self, msg: &T) -> io::Result<()> {
+        let mut line = serde_json::to_string(msg).expect("protocol messages always serialize");
+        line.push('\n');
+        self.writer.write_all(line.as_bytes())
+    }
+
+    /// The next message, or `None` once the other side has hung up.
+    fn recv<T: DeserializeOwned>(&mut self) -> io::Result<Option<T>> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        serde_json::from_str(&line)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// A submitted job and how far it has got.
+struct JobState {
+    job: Job,
+    hasher: NonceHasher,
+    /// Every nonce below this has been handed out.
+    cursor: u64,
+    /// Chunks given back by workers that disconnected.
+    returned: Vec<(u64, u64)>,
+    /// Chunks out with workers.
+    outstanding: usize,
+    solutions: Vec<Solution>,
+    /// k-smallest mode: the k-th best nonce so far.
+    bound: u64,
+    hashes: u64,
+    started: Instant,
+    result: Option<JobResult>,
+}
+
+impl JobState {
+    fn new(id: u64, job: Job) -> Self {
+        let mut state = Self {
+            hasher: job.hasher(),
+            cursor: job.start,
+            returned: Vec::new(),
+            outstanding: 0,
+            solutions: Vec::new(),
+            bound: u64::MAX,
+            hashes: 0,
+            started: Instant::now(),
+            result: None,
+            job,
+        };
+        state.finish_if_done(id);
+        state
+    }
+
+    /// Nothing at or above this is handed out.
+    fn limit(&self) -> u64 {
+        self.job.end.min(self.bound)
+    }
+
+    fn claim(&mut self, chunk_size: u64) -> Option<(u64, u64)> {
+        if self.result.is_some() {
+            return None;
+        }
+        let limit = self.limit();
+        let chunk = if let Some(i) = self.returned.iter().position(|&(s, _)| s < limit) {
+            self.returned.swap_remove(i)
+        } else if self.cursor < limit {
+            let end = self.cursor.saturating_add(chunk_size).min(self.job.end);
+            let chunk = (self.cursor, end);
+            self.cursor = end;
+            chunk
+        } else {
+            return None;
+        };
+        self.outstanding += 1;
+        Some(chunk)
+    }
+
+    /// A worker hashed the chunk `[start, end)` it was given and found
+    /// `nonces` in it.
+    fn done(&mut self, id: u64, (start, end): (u64, u64), nonces: Vec<u64>) {
+        if self.result.is_some() {
+            return;
+        }
+        self.outstanding -= 1;
+        let mut found = Vec::with_capacity(nonces.len());
+        for nonce in nonces {
+            let hash = self.hasher.digest(nonce);
+            if !(start..end).contains(&nonce) || !self.job.difficulty.meets(&hash) {
+                // Nothing else it says about the chunk can be trusted either.
+                eprintln!(
+                    "warning: job {}: worker reported invalid nonce {}; redoing {}..{}",
+                    id, nonce, start, end
+                );
+                self.returned.push((start, end));
+                return;
+            }
+            found.push(Solution { nonce, hash });
+        }
+        self.hashes += end - start;
+        self.solutions.extend(found);
+        if self.job.smallest && self.solutions.len() >= self.job.k {
+            self.solutions.sort_unstable_by_key(|s| s.nonce);
+            self.solutions.dedup_by_key(|s| s.nonce);
+            self.solutions.truncate(self.job.k);
+            if self.solutions.len() == self.job.k {
+                self.bound = self.solutions[self.job.k - 1].nonce;
+            }
+        }
+        self.finish_if_done(id);
+    }
+
+    /// A worker disconnected before finishing `chunk`.
+    fn give_back(&mut self, chunk: (u64, u64)) {
+        if self.result.is_none() {
+            self.outstanding -= 1;
+            self.returned.push(chunk);
+        }
+    }
+
+    fn finish_if_done(&mut self, id: u64) {
+        let limit = self.limit();
+        let reached_k = !self.job.smallest && self.solutions.len() >= self.job.k;
+        let exhausted = self.cursor >= limit
+            && self.outstanding == 0
+            && self.returned.iter().all(|&(s, _)| s >= limit);
+        if !(reached_k || exhausted) {
+            return;
+        }
+        self.solutions.sort_by_key(|s| s.nonce);
+        self.solutions.dedup_by_key(|s| s.nonce);
+        self.solutions.truncate(self.job.k);
+        self.result = Some(JobResult {
+            job_id: id,
+            solutions: self
+                .solutions
+                .iter()
+                .map(|s| ReportedSolution::new(s.nonce, &s.hash))
+                .collect(),
+            hashes: self.hashes,
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+        });
+    }
+}
+
+#[derive(Default)]
+struct Jobs {
+    next_id: u64,
+    /// By id, so the oldest job is served first.
+    jobs: BTreeMap<u64, JobState>,
+    shutdown: bool,
+}
+
+/// Server state shared by the connection threads. Every change is
+/// announced on `changed`.
+struct Shared {
+    jobs: Mutex<Jobs>,
+    changed: Condvar,
+    chunk_size: u64,
+}
+
+impl Shared {
+    fn submit(&self, job: Job) -> Result<u64, String> {
+        if job.k == 0 {
+            return Err("k must be at least 1".to_string());
+        }
+        if job.start >= job.end {
+            return Err(format!(
+                "empty nonce range: start ({}) must be less than end ({})",
+                job.start, job.end
+            ));
+        }
+        job.difficulty.validate().map_err(|e| e.to_string())?;
+
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.next_id += 1;
+        let id = jobs.next_id;
+        jobs.jobs.insert(id, JobState::new(id, job));
+        self.changed.notify_all();
+        Ok(id)
+    }
+
+    /// Wait for job `id` to finish and forget it.
+    fn wait_result(&self, id: u64) -> Reply {
+        let mut jobs = self.jobs.lock().unwrap();
+        loop {
+            if let Some(result) = jobs.jobs.get(&id).and_then(|j| j.result.clone()) {
+                jobs.jobs.remove(&id);
+                return Reply::Result(result);
+            }
+            if jobs.shutdown {
+                return Reply::Error {
+                    message: "server is shutting down".to_string(),
+                };
+            }
+            jobs = self.changed.wait(jobs).unwrap();
+        }
+    }
+
+    /// Wait for a chunk of any job; `None` when the server shuts down.
+    fn next_chunk(&self) -> Option<(u64, Reply)> {
+        let mut jobs = self.jobs.lock().unwrap();
+        loop {
+            if jobs.shutdown {
+                return None;
+            }
+            for (&id, state) in jobs.jobs.iter_mut() {
+                if let Some((start, end)) = state.claim(self.chunk_size) {
+                    let work = Reply::Work {
+                        job_id: id,
+                        prefix: state.job.prefix.clone(),
+                        difficulty: state.job.difficulty,
+                        algorithm: state.job.algorithm,
+                        header: state.job.header,
+                        start,
+                        len: end - start,
+                    };
+                    return Some((id, work));
+                }
+            }
+            jobs = self.changed.wait(jobs).unwrap();
+        }
+    }
+
+    fn done(&self, id: u64, chunk: (u64, u64), nonces: Vec<u64>) {
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some(state) = jobs.jobs.get_mut(&id) {
+            state.done(id, chunk, nonces);
+        }
+        self.changed.notify_all();
+    }
+
+    fn give_back(&self, id: u64, chunk: (u64, u64)) {
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some(state) = jobs.jobs.get_mut(&id) {
+            state.give_back(chunk);
+        }
+        self.changed.notify_all();
+    }
+
+    /// Serve one connection, submitter or worker, until it hangs up.
+    fn handle(&self, stream: TcpStream) -> io::Result<()> {
+        let mut conn = Conn::new(stream)?;
+        // The chunk this connection's worker is hashing, if any.
+        let mut out: Option<(u64, (u64, u64))> = None;
+
+        let result = (|| -> io::Result<()> {
+            while let Some(request) = conn.recv::<Request>()? {
+                match request {
+                    Request::Submit { job } => match self.submit(job) {
+                        Ok(job_id) => {
+                            conn.send(&Reply::Accepted { job_id })?;
+                            conn.send(&self.wait_result(job_id))?;
+                        }
+                        Err(message) => conn.send(&Reply::Error { message })?,
+                    },
+                    Request::Ready => {
+                        // A worker asking again before reporting its chunk
+                        // has given up on it.
+                        if let Some((id, chunk)) = out.take() {
+                            self.give_back(id, chunk);
+                        }
+                        match self.next_chunk() {
+                            Some((id, work)) => {
+                                if let Reply::Work { start, len, .. } = work {
+                                    out = Some((id, (start, start + len)));
+                                }
+                                conn.send(&work)?;
+                            }
+                            None => return conn.send(&Reply::Shutdown),
+                        }
+                    }
+                    Request::Done {
+                        job_id,
+                        start,
+                        len,
+                        nonces,
+                    } => {
+                        // Anything but the chunk we handed out is ignored;
+                        // `len` comes from the worker, so it may not fit.
+                        let chunk = start.checked_add(len).map(|end| (start, end));
+                        if chunk.is_some_and(|chunk| out == Some((job_id, chunk))) {
+                            let (id, chunk) = out.take().unwrap();
+                            self.done(id, chunk, nonces);
+                        }
+                    }
+                }
+            }
+            Ok(())
+        })();
+
+        if let Some((id, chunk)) = out {
+            self.give_back(id, chunk);
+        }
+        if let Err(e) = &result
+            && e.kind() == io::ErrorKind::InvalidData
+        {
+            let _ = conn.send(&Reply::Error {
+                message: e.to_string(),
+            });
+        }
+        result
+    }
+}
+
+/// A job server listening on a TCP socket.
+pub struct Server {
+    listener: TcpListener,
+    shared: Arc<Shared>,
+}
+
+impl Server {
+    /// Listen on `addr`, handing out chunks of `chunk_size` nonces.
+    pub fn bind(addr: impl ToSocketAddrs, chunk_size: u64) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            shared: Arc::new(Shared {
+                jobs: Mutex::default(),
+                changed: Condvar::new(),
+                chunk_size: chunk_size.max(1),
+            }),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accept connections until the server is shut down. A failed accept
+    /// (a peer that reset before it was accepted, running out of file
+    /// descriptors) is logged and skipped rather than stopping the server.
+    pub fn run(self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            if self.shared.jobs.lock().unwrap().shutdown {
+                break;
+            }
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("warning: accept failed: {}", e);
+                    // Don't spin if the error persists (e.g. EMFILE).
+                    thread::sleep(ACCEPT_RETRY_DELAY);
+                    continue;
+                }
+            };
+            let shared = Arc::clone(&self.shared);
+            thread::spawn(move || {
+                let peer = stream.peer_addr().ok();
+                if let Err(e) = shared.handle(stream) {
+                    eprintln!("warning: connection {:?}: {}", peer, e);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Run on a background thread.
+    pub fn spawn(self) -> io::Result<ServerHandle> {
+        let addr = self.local_addr()?;
+        let shared = Arc::clone(&self.shared);
+        let thread = thread::spawn(move || self.run());
+        Ok(ServerHandle {
+            addr,
+            shared,
+            thread,
+        })
+    }
+}
+
+/// A server running on a background thread.
+pub struct ServerHandle {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    thread: JoinHandle<io::Result<()>>,
+}
+
+impl ServerHandle {
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Tell waiting workers and submitters to go away and stop listening.
+    pub fn shutdown(self) -> io::Result<()> {
+        self.shared.jobs.lock().unwrap().shutdown = true;
+        self.shared.changed.notify_all();
+        // Wake the accept loop so it sees the flag.
+        let _ = TcpStream::connect(self.addr);
+        self.thread.join().expect("server thread panicked")
+    }
+}
+
+/// Submit `job` to the server at `addr` and wait for its result.
+pub fn submit(addr: impl ToSocketAddrs, job: &Job) -> io::Result<JobResult> {
+    let mut conn = Conn::new(TcpStream::connect(addr)?)?;
+    conn.send(&Request::Submit { job: job.clone() })?;
+    loop {
+        match conn.recv::<Reply>()? {
+            Some(Reply::Accepted { .. }) => continue,
+            Some(Reply::Result(result)) => return Ok(result),
+            Some(Reply::Error { message }) => return Err(io::Error::other(message)),
+            Some(other) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unexpected reply {:?}", other),
+                ));
+            }
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "server closed the connection",
+                ));
+            }
+        }
+    }
+}
+
+/// Connect to the server at `addr` and hash chunks until it shuts down.
+pub fn work(addr: impl ToSocketAddrs) -> io::Result<WorkerReport> {
+    let mut conn = Conn::new(TcpStream::connect(addr)?)?;
+    let mut report = WorkerReport::default();
+    // Keyed by job id, so a job's prefix is only absorbed once.
+    let mut hasher: Option<(u64, NonceHasher)> = None;
+
+    loop {
+        conn.send(&Request::Ready)?;
+        match conn.recv::<Reply>()? {
+            Some(Reply::Work {
+                job_id,
+                prefix,
+                difficulty,
+                algorithm,
+                header,
+                start,
+                len,
+            }) => {
+                if hasher.as_ref().is_none_or(|&(id, _)| id != job_id) {
+                    let h = NonceHasher::new(algorithm, &prefix, header.as_ref());
+                    hasher = Some((job_id, h));
+                }
+                let (_, h) = hasher.as_ref().unwrap();
+                let nonces = (start..start.saturating_add(len))
+                    .filter(|&n| difficulty.meets(&h.digest(n)))
+                    .collect();
+                report.chunks += 1;
+                report.hashes += len;
+                conn.send(&Request::Done {
+                    job_id,
+                    start,
+                    len,
+                    nonces,
+                })?;
+            }
+            None | Some(Reply::Shutdown) => return Ok(report),
+            Some(Reply::Error { message }) => return Err(io::Error::other(message)),
+            Some(other) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unexpected reply {:?}", other),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn job(smallest: bool) -> Job {
+        Job {
+            prefix: "cmkl-pow".to_string(),
+            difficulty: Difficulty::hex_digits(3),
+            algorithm: Algorithm::Sha256,
+            header: None,
+            k: 5,
+            start: 0,
+            end: 200_000,
+            smallest,
+        }
+    }
+
+    fn brute_force(job: &Job) -> Vec<u64> {
+        let hasher = job.hasher();
+        (job.start..job.end)
+            .filter(|&n| job.difficulty.meets(&hasher.digest(n)))
+            .take(job.k)
+            .collect()
+    }
+
+    fn nonces(result: &JobResult) -> Vec<u64> {
+        result.solutions.iter().map(|s| s.nonce).collect()
+    }
+
+    /// A server with `n` worker threads, each on its own connection.
+    fn cluster(n: usize, chunk_size: u64) -> (ServerHandle, Vec<JoinHandle<WorkerReport>>) {
+        let server = Server::bind("127.0.0.1:0", chunk_size)
+            .unwrap()
+            .spawn()
+            .unwrap();
+        let addr = server.addr();
+        let workers = (0..n)
+            .map(|_| thread::spawn(move || work(addr).unwrap()))
+            .collect();
+        (server, workers)
+    }
+
+    #[test]
+    fn workers_find_the_k_smallest_and_exit_on_shutdown() {
+        let (server, workers) = cluster(3, 4_000);
+        let job = job(true);
+        let result = submit(server.addr(), &job).unwrap();
+        assert_eq!(nonces(&result), brute_force(&job));
+        for s in &result.solutions {
+            assert_eq!(s.hash.len(), 64);
+        }
+
+        server.shutdown().unwrap();
+        let reports: Vec<WorkerReport> = workers.into_iter().map(|w| w.join().unwrap()).collect();
+        let hashed: u64 = reports.iter().map(|r| r.hashes).sum();
+        assert_eq!(hashed, result.hashes);
+        assert!(reports.iter().map(|r| r.chunks).sum::<u64>() > 1);
+    }
+
+    #[test]
+    fn concurrent_jobs_are_all_answered() {
+        let (server, _workers) = cluster(2, 10_000);
+        let addr = server.addr();
+        let submitters: Vec<_> = [true, false, true]
+            .into_iter()
+            .enumerate()
+            .map(|(i, smallest)| {
+                thread::spawn(move || {
+                    // The last one is a block-header search.
+                    let header = BlockHeader::new(Difficulty::hex_digits(3).compact_bits());
+                    let job = Job {
+                        prefix: format!("job-{}", i),
+                        header: (i == 2).then_some(header),
+                        ..job(smallest)
+                    };
+                    (submit(addr, &job).unwrap(), job)
+                })
+            })
+            .collect();
+        for s in submitters {
+            let (result, job) = s.join().unwrap();
+            if job.smallest {
+                assert_eq!(nonces(&result), brute_force(&job));
+            } else {
+                assert_eq!(result.solutions.len(), job.k);
+                let hasher = job.hasher();
+                for s in &result.solutions {
+                    assert!(job.difficulty.meets(&hasher.digest(s.nonce)));
+                }
+            }
+        }
+        server.shutdown().unwrap();
+    }
+
+    #[test]
+    fn chunks_of_vanished_or_lying_workers_are_redone() {
+        let server = Server::bind("127.0.0.1:0", 5_000).unwrap().spawn().unwrap();
+        let addr = server.addr();
+        let job = job(true);
+        let submitter = {
+            let job = job.clone();
+            thread::spawn(move || submit(addr, &job).unwrap())
+        };
+
+        // Takes a chunk and disconnects without finishing it.
+        let mut quitter = Conn::new(TcpStream::connect(addr).unwrap()).unwrap();
+        quitter.send(&Request::Ready).unwrap();
+        let Some(Reply::Work { start, .. }) = quitter.recv().unwrap() else {
+            panic!("expected work");
+        };
+        assert_eq!(start, 0);
+        drop(quitter);
+
+        // Claims its chunk holds nonces that are not solutions.
+        let mut liar = Conn::new(TcpStream::connect(addr).unwrap()).unwrap();
+        liar.send(&Request::Ready).unwrap();
+        let Some(Reply::Work {
+            job_id, start, len, ..
+        }) = liar.recv().unwrap()
+        else {
+            panic!("expected work");
+        };
+        let nonces_claimed = vec![start, start + 1, start + 2, start + 3, start + 4];
+        liar.send(&Request::Done {
+            job_id,
+            start,
+            len,
+            nonces: nonces_claimed,
+        })
+        .unwrap();
+        drop(liar);
+
+        let honest = thread::spawn(move || work(addr).unwrap());
+        let result = submitter.join().unwrap();
+        assert_eq!(nonces(&result), brute_force(&job));
+        server.shutdown().unwrap();
+        honest.join().unwrap();
+    }
+
+    #[test]
+    fn chunks_a_worker_asks_past_are_redone() {
+        let server = Server::bind("127.0.0.1:0", 5_000).unwrap().spawn().unwrap();
+        let addr = server.addr();
+        let job = job(true);
+        let (result_tx, result_rx) = mpsc::channel();
+        {
+            let job = job.clone();
+            thread::spawn(move || result_tx.send(submit(addr, &job).unwrap()));
+        }
+
+        // Asks again without reporting its first chunk, reports a length
+        // that overflows, and only finishes the chunk it holds last.
+        let mut sloppy = Conn::new(TcpStream::connect(addr).unwrap()).unwrap();
+        let next = |conn: &mut Conn| {
+            conn.send(&Request::Ready).unwrap();
+            match conn.recv().unwrap() {
+                Some(Reply::Work {
+                    job_id, start, len, ..
+                }) => (job_id, start, len),
+                other => panic!("expected work, got {other:?}"),
+            }
+        };
+        next(&mut sloppy);
+        let (job_id, start, _) = next(&mut sloppy);
+        sloppy
+            .send(&Request::Done {
+                job_id,
+                start,
+                len: u64::MAX,
+                nonces: Vec::new(),
+            })
+            .unwrap();
+        // Still served: the bogus `done` was ignored, not fatal.
+        let (job_id, start, len) = next(&mut sloppy);
+        let hasher = job.hasher();
+        let found = (start..start + len)
+            .filter(|&n| job.difficulty.meets(&hasher.digest(n)))
+            .collect();
+        sloppy
+            .send(&Request::Done {
+                job_id,
+                start,
+                len,
+                nonces: found,
+            })
+            .unwrap();
+
+        let honest = thread::spawn(move || work(addr).unwrap());
+        let result = result_rx
+            .recv_timeout(Duration::from_secs(60))
+            .expect("job never answered");
+        assert_eq!(nonces(&result), brute_force(&job));
+        drop(sloppy);
+        server.shutdown().unwrap();
+        honest.join().unwrap();
+    }
+
+    #[test]
+    fn bad_jobs_and_bad_lines_get_errors() {
+        let server = Server::bind("127.0.0.1:0", 1_000).unwrap().spawn().unwrap();
+        let empty = Job {
+            start: 10,
+            end: 10,
+            ..job(false)
+        };
+        let err = submit(server.addr(), &empty).unwrap_err();
+        assert!(err.to_string().contains("empty nonce range"), "{err}");
+
+        let mut conn = Conn::new(TcpStream::connect(server.addr()).unwrap()).unwrap();
+        conn.writer.write_all(b"{\"type\":\"hello\"}\n").unwrap();
+        assert!(matches!(
+            conn.recv::<Reply>().unwrap(),
+            Some(Reply::Error { .. })
+        ));
+        server.shutdown().unwrap();
+    }
+
+    #[test]
+    fn messages_are_tagged_json_lines() {
+        let line = serde_json::to_string(&Request::Done {
+            job_id: 1,
+            start: 0,
+            len: 10,
+            nonces: vec![3],
+        })
+        .unwrap();
+        assert_eq!(
+            line,
+            r#"{"type":"done","job_id":1,"start":0,"len":10,"nonces":[3]}"#
+        );
+        let reply: Reply = serde_json::from_str(
+            r#"{"type":"result","job_id":2,"solutions":[],"hashes":5,"elapsed_ms":1}"#,
+        )
+        .unwrap();
+        assert!(matches!(reply, Reply::Result(JobResult { job_id: 2, .. })));
+        let job: Job =
+            serde_json::from_str(r#"{"prefix":"p","difficulty":"20b","k":1,"start":0,"end":9}"#)
+                .unwrap();
+        assert_eq!(job.algorithm, Algorithm::Sha256);
+        assert!(!job.smallest);
+    }
+}
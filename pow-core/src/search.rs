@@ -0,0 +1,1549 @@
+// Parallel nonce search shared by `assignment-2` and `lab-3`.
+//
+// A `PowSearch` describes what to look for and how to share the work
+// between threads; `run` blocks until the search is over and returns a
+// `PowReport`:
+//
+//   let report = PowSearch::new("cmkl-pow")
+//       .difficulty(Difficulty::hex_digits(4))
+//       .k(3)
+//       .threads(4)
+//       .schedule(Schedule::CentralQueue { chunk_size: ChunkSize::Adaptive })
+//       .run();
+//
+// Schedules:
+//
+//   StaticSplit  - thread i searches the i-th of `threads` equal, ascending
+//                  slices of the range (lab-3). No coordination at all, but
+//                  the slowest slice decides the run time.
+//   CentralQueue - threads claim chunks from one shared cursor over the
+//                  range and send solutions to a collector (assignment-2).
+//   WorkStealing - the chunks are dealt round-robin into per-thread deques;
+//                  a thread whose deque runs dry steals from the others.
+//
+// The central queue is never materialised: a chunk is claimed by moving an
+// atomic cursor past it, so even a 50-billion-nonce range costs nothing up
+// front. With `ChunkSize::Adaptive` each thread sizes its next chunk from
+// how long its last one took, aiming at `TARGET_CHUNK_TIME`: chunks start at
+// `MIN_CHUNK` (quick first results, fast stops), double towards the target
+// in steady state, and shrink again to a fraction of what is left near the
+// end of the range or the k-smallest bound, so threads finish together.
+//
+// By default the search ends as soon as k solutions are known, which are
+// not necessarily the k smallest. With `smallest(true)` a shared `bound`
+// holds the k-th best nonce found so far; threads skip and abandon work at
+// or above it and the answer is exactly the k smallest valid nonces in
+// [start, end), whatever the schedule and thread count.
+//
+// `stop_flag` lets the caller end a search early (e.g. on Ctrl-C), and
+// `timeout` / `max_hashes` give it a budget: a watchdog thread ends the run
+// once either runs out. Finding k and the watchdog only set the run's own
+// stop flag, never the caller's, so a `PowSearch` (or a clone of it) can be
+// run again. The report's `status` says which of these ended the search,
+// if any; the solutions found until then are reported
+// either way. With `checkpoint`, the searched ranges
+// and the solutions in them are saved periodically and once more at the
+// end, and `resume` continues from such a checkpoint, searching only the
+// nonces it has not covered.
+//
+// With `header`, each candidate is a block header rather than
+// `prefix:nonce` (see `header.rs`); nothing else about the search changes.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_deque::{Steal, Stealer, Worker};
+
+use crate::checkpoint::{Checkpoint, RangeSet, split_even};
+use crate::difficulty::Difficulty;
+use crate::hash::{Algorithm, Hash, NonceHasher};
+use crate::header::BlockHeader;
+use crate::telemetry::{Counters, ProgressFormat, Reporter, WorkerCounters};
+use crate::verify::ReportedSolution;
+
+/// How often (in nonces) a queue worker checks the stop flag inside a chunk.
+const STOP_CHECK_INTERVAL: u64 = 1024;
+
+/// Adaptive chunks: the first chunk, and the smallest one handed out.
+const MIN_CHUNK: u64 = 1 << 10;
+
+/// Adaptive chunks: the largest one handed out.
+const MAX_CHUNK: u64 = 1 << 24;
+
+/// Adaptive chunks: how long one chunk should take to hash.
+const TARGET_CHUNK_TIME: Duration = Duration::from_millis(20);
+
+/// How often the watchdog checks the time and hash budgets.
+const WATCHDOG_TICK: Duration = Duration::from_millis(10);
+
+/// How often (in nonces) a static-split thread adds its progress to the
+/// completed ranges.
+const PROGRESS_INTERVAL: u64 = 1 << 16;
+
+/// A nonce whose hash meets the difficulty.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Solution {
+    pub nonce: u64,
+    pub hash: Hash,
+}
+
+/// How the nonce range is shared between threads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Schedule {
+    /// One equal slice of the range per thread.
+    StaticSplit,
+    /// Chunks claimed from one shared cursor over the range.
+    CentralQueue { chunk_size: ChunkSize },
+    /// `chunk_size` chunks in per-thread deques, with stealing.
+    WorkStealing { chunk_size: u64 },
+}
+
+impl Schedule {
+    pub fn name(self) -> &'static str {
+        match self {
+            Schedule::StaticSplit => "static",
+            Schedule::CentralQueue { .. } => "central",
+            Schedule::WorkStealing { .. } => "stealing",
+        }
+    }
+}
+
+/// Length of the chunks of a `CentralQueue`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkSize {
+    /// Always this many nonces.
+    Fixed(u64),
+    /// Sized per thread from its measured hashrate.
+    Adaptive,
+}
+
+impl fmt::Display for ChunkSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkSize::Fixed(n) => write!(f, "{}", n),
+            ChunkSize::Adaptive => f.write_str("auto"),
+        }
+    }
+}
+
+/// What one thread did.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ThreadStats {
+    pub hashes: u64,
+    /// Chunks taken (own deque or stolen). Queue schedules only.
+    pub chunks: u64,
+    /// k-smallest mode: chunks dropped because they start above the bound.
+    pub skipped: u64,
+    /// `CentralQueue`: claims retried because another thread moved the
+    /// cursor first, the queue's only point of contention.
+    pub cursor_retries: u64,
+    /// `WorkStealing`: successful steals from another thread's deque.
+    pub steals: u64,
+}
+
+impl ThreadStats {
+    fn add(&mut self, other: &ThreadStats) {
+        self.hashes += other.hashes;
+        self.chunks += other.chunks;
+        self.skipped += other.skipped;
+        self.cursor_retries += other.cursor_retries;
+        self.steals += other.steals;
+    }
+}
+
+/// How a search ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    /// It searched everything it had to, or found its k solutions.
+    Finished,
+    /// `timeout` ran out first.
+    TimedOut,
+    /// `max_hashes` ran out first.
+    HashLimit,
+    /// The stop flag was set from outside.
+    Interrupted,
+}
+
+impl Status {
+    /// The name used in output.
+    pub fn name(self) -> &'static str {
+        match self {
+            Status::Finished => "finished",
+            Status::TimedOut => "timed_out",
+            Status::HashLimit => "hash_limit",
+            Status::Interrupted => "interrupted",
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Everything a finished search reports.
+#[derive(Clone, Debug)]
+pub struct PowReport {
+    /// Sorted by nonce, at most k.
+    pub solutions: Vec<Solution>,
+    /// Hashes computed by this run (not by the runs it resumed).
+    pub hashes: u64,
+    pub elapsed: Duration,
+    /// One entry per thread, in thread order.
+    pub threads: Vec<ThreadStats>,
+    /// Nonces searched so far, including by resumed runs.
+    pub searched: u64,
+    /// Whether the search finished, and if not, what stopped it.
+    pub status: Status,
+}
+
+impl PowReport {
+    /// False if the solutions are only what was found before a stop.
+    pub fn finished(&self) -> bool {
+        self.status == Status::Finished
+    }
+
+    /// Hashes per second over the whole run.
+    pub fn hashrate(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.hashes as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    pub fn per_thread_hashes(&self) -> Vec<u64> {
+        self.threads.iter().map(|t| t.hashes).collect()
+    }
+
+    /// All threads' stats added up.
+    pub fn totals(&self) -> ThreadStats {
+        let mut total = ThreadStats::default();
+        for t in &self.threads {
+            total.add(t);
+        }
+        total
+    }
+}
+
+/// A parallel search for nonces whose hash meets a difficulty.
+#[derive(Clone, Debug)]
+pub struct PowSearch {
+    prefix: String,
+    difficulty: Difficulty,
+    algorithm: Algorithm,
+    header: Option<BlockHeader>,
+    k: usize,
+    start: u64,
+    end: u64,
+    threads: usize,
+    schedule: Schedule,
+    smallest: bool,
+    stop: Arc<AtomicBool>,
+    timeout: Option<Duration>,
+    max_hashes: Option<u64>,
+    resume: Option<Checkpoint>,
+    checkpoint: Option<(PathBuf, Duration)>,
+    progress: Option<(Duration, ProgressFormat)>,
+}
+
+impl PowSearch {
+    /// A search for one nonce in `[0, 50_000_000_000)` with 4 leading zero
+    /// hex digits, using SHA-256 and one thread per CPU on a central queue
+    /// with adaptive chunks.
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            difficulty: Difficulty::hex_digits(4),
+            algorithm: Algorithm::default(),
+            header: None,
+            k: 1,
+            start: 0,
+            end: 50_000_000_000,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            schedule: Schedule::CentralQueue {
+                chunk_size: ChunkSize::Adaptive,
+            },
+            smallest: false,
+            stop: Arc::default(),
+            timeout: None,
+            max_hashes: None,
+            resume: None,
+            checkpoint: None,
+            progress: None,
+        }
+    }
+
+    pub fn difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Hash `header` with each nonce filled in, instead of `prefix:nonce`.
+    pub fn header(mut self, header: BlockHeader) -> Self {
+        self.header = Some(header);
+        self
+    }
+
+    /// Number of solutions to find.
+    pub fn k(mut self, k: usize) -> Self {
+        self.k = k;
+        self
+    }
+
+    /// Search the nonces in `[start, end)`.
+    pub fn range(mut self, start: u64, end: u64) -> Self {
+        self.start = start;
+        self.end = end;
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    pub fn schedule(mut self, schedule: Schedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Report exactly the k smallest valid nonces instead of the first k found.
+    pub fn smallest(mut self, smallest: bool) -> Self {
+        self.smallest = smallest;
+        self
+    }
+
+    /// Setting `stop` ends the search early. Only the caller sets it; the
+    /// search never does.
+    pub fn stop_flag(mut self, stop: Arc<AtomicBool>) -> Self {
+        self.stop = stop;
+        self
+    }
+
+    /// Stop after `timeout` of wall-clock time. Running out ends the run
+    /// like finding k solutions does.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Stop after about `max_hashes` hashes: threads notice within a chunk
+    /// or so, and hashes already under way are still counted.
+    pub fn max_hashes(mut self, max_hashes: u64) -> Self {
+        self.max_hashes = Some(max_hashes);
+        self
+    }
+
+    /// Save progress to `path` every `interval` and when the search ends.
+    pub fn checkpoint(mut self, path: impl Into<PathBuf>, interval: Duration) -> Self {
+        self.checkpoint = Some((path.into(), interval));
+        self
+    }
+
+    /// Print a progress line to stderr every `interval`.
+    pub fn progress(mut self, interval: Duration, format: ProgressFormat) -> Self {
+        self.progress = Some((interval, format));
+        self
+    }
+
+    /// Continue the search saved in `ckpt`. Its prefix, difficulty,
+    /// algorithm, header, k, range and mode replace the ones set so far.
+    pub fn resume(mut self, ckpt: Checkpoint) -> Self {
+        self.prefix = ckpt.prefix.clone();
+        self.difficulty = ckpt.difficulty;
+        self.algorithm = ckpt.algorithm;
+        self.header = ckpt.header;
+        self.k = ckpt.k;
+        self.start = ckpt.start;
+        self.end = ckpt.end;
+        self.smallest = ckpt.smallest;
+        self.resume = Some(ckpt);
+        self
+    }
+
+    /// Run the search to the end, or until the stop flag is set or the
+    /// budget runs out.
+    ///
+    /// Panics if `k` or `threads` is zero.
+    pub fn run(&self) -> PowReport {
+        assert!(self.k > 0, "k must be at least 1");
+        assert!(self.threads > 0, "threads must be at least 1");
+
+        // Resuming: start from the saved solutions and search only the gaps.
+        let (todo, completed, mut seed, base_hashes) = match &self.resume {
+            Some(ckpt) => {
+                let hasher = self.hasher();
+                let seed: Vec<Solution> = ckpt
+                    .solutions
+                    .iter()
+                    .map(|s| Solution {
+                        nonce: s.nonce,
+                        hash: hasher.digest(s.nonce),
+                    })
+                    .collect();
+                let completed = ckpt.completed.clone();
+                (ckpt.remaining(), completed, seed, ckpt.hashes)
+            }
+            None => (vec![(self.start, self.end)], RangeSet::new(), Vec::new(), 0),
+        };
+        seed.sort_by_key(|s| s.nonce);
+
+        // A resumed default-mode run that already has k solutions is done.
+        let todo = if !self.smallest && seed.len() >= self.k {
+            Vec::new()
+        } else {
+            todo
+        };
+
+        let state = Arc::new(Shared {
+            caller_stop: Arc::clone(&self.stop),
+            stop: AtomicBool::new(false),
+            hashes: AtomicU64::new(0),
+            worker_hashes: WorkerCounters::new(self.threads),
+            bound: AtomicU64::new(u64::MAX),
+        });
+        // k-smallest mode: the k-th best nonce found so far.
+        if self.smallest && seed.len() >= self.k {
+            state.bound.store(seed[self.k - 1].nonce, Ordering::Relaxed);
+        }
+
+        let watchdog = Watchdog::spawn(self, Arc::clone(&state));
+        let mut report = match self.schedule {
+            Schedule::StaticSplit => self.run_static(todo, completed, seed, base_hashes, state),
+            Schedule::CentralQueue { .. } | Schedule::WorkStealing { .. } => {
+                let progress = Progress {
+                    completed,
+                    solutions: seed,
+                };
+                self.run_queue(todo, progress, base_hashes, state)
+            }
+        };
+        // The engines only know that the run was stopped.
+        if let Some(limit) = watchdog.and_then(Watchdog::finish)
+            && report.status == Status::Interrupted
+        {
+            report.status = limit;
+        }
+        report
+    }
+
+    fn hasher(&self) -> NonceHasher {
+        NonceHasher::new(self.algorithm, &self.prefix, self.header.as_ref())
+    }
+
+    /// Snapshot of this search, with the solutions so far cut down to the k
+    /// smallest (anything past them can never be reported).
+    fn snapshot(&self, completed: RangeSet, solutions: &[Solution], hashes: u64) -> Checkpoint {
+        let mut solutions = solutions.to_vec();
+        solutions.sort_by_key(|s| s.nonce);
+        solutions.truncate(self.k);
+
+        Checkpoint {
+            prefix: self.prefix.clone(),
+            difficulty: self.difficulty,
+            algorithm: self.algorithm,
+            header: self.header,
+            k: self.k,
+            start: self.start,
+            end: self.end,
+            smallest: self.smallest,
+            completed,
+            solutions: solutions
+                .iter()
+                .map(|s| ReportedSolution::new(s.nonce, &s.hash))
+                .collect(),
+            hashes,
+        }
+    }
+
+    /// Write the checkpoint, if there is one; failures are only warned about
+    /// so a full disk does not lose the search itself.
+    fn save(&self, snapshot: impl FnOnce() -> Checkpoint) {
+        if let Some((path, _)) = &self.checkpoint
+            && let Err(e) = snapshot().save(path)
+        {
+            eprintln!(
+                "warning: cannot write checkpoint `{}`: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+
+    /// Start the progress reporter, if asked for.
+    fn reporter<F>(&self, read: F) -> Option<Reporter>
+    where
+        F: FnMut() -> Counters + Send + 'static,
+    {
+        self.progress
+            .map(|(interval, format)| Reporter::spawn(interval, format, read))
+    }
+
+    /// `StaticSplit`: one thread per slice, publishing solutions straight
+    /// into a shared `Mutex<Vec<Solution>>`.
+    fn run_static(
+        &self,
+        todo: Vec<(u64, u64)>,
+        completed: RangeSet,
+        seed: Vec<Solution>,
+        base_hashes: u64,
+        state: Arc<Shared>,
+    ) -> PowReport {
+        let k = self.k;
+        let smallest = self.smallest;
+        let difficulty = self.difficulty;
+        let results = Arc::new(Mutex::new(seed));
+        let completed = Arc::new(Mutex::new(completed));
+
+        // Thread i gets the i-th of `threads` equal, ascending shares of the
+        // nonces still to search; on a fresh run that is
+        //   [start + i*chunk, min(start + (i+1)*chunk, end))
+        let slices = split_even(&todo, self.threads);
+
+        let t0 = Instant::now();
+        let mut handles = Vec::new();
+
+        for (i, ranges) in slices.into_iter().enumerate() {
+            let hasher = self.hasher();
+            let state = Arc::clone(&state);
+            let results = Arc::clone(&results);
+            let completed = Arc::clone(&completed);
+
+            handles.push(thread::spawn(move || {
+                for (lo, hi) in ranges {
+                    // [lo, searched) has been hashed; [recorded, searched) is
+                    // not in `completed` yet.
+                    let mut searched = lo;
+                    let mut recorded = lo;
+
+                    for nonce in lo..hi {
+                        // k-smallest mode: also stop once past the k-th best
+                        // nonce; the bound is u64::MAX otherwise.
+                        if state.stopped() || nonce >= state.bound.load(Ordering::Relaxed) {
+                            break;
+                        }
+
+                        let hash = hasher.digest(nonce);
+                        state.hashes.fetch_add(1, Ordering::Relaxed);
+                        state.worker_hashes.add(i, 1);
+
+                        if difficulty.meets(&hash) {
+                            let mut res = results.lock().unwrap();
+                            if smallest {
+                                // Keep the k best so far and publish the k-th
+                                // as the bound; no thread stops early.
+                                res.push(Solution { nonce, hash });
+                                if res.len() >= k {
+                                    res.sort_unstable_by_key(|s| s.nonce);
+                                    res.truncate(k);
+                                    state.bound.store(res[k - 1].nonce, Ordering::Relaxed);
+                                }
+                            } else if res.len() < k {
+                                res.push(Solution { nonce, hash });
+                                if res.len() >= k {
+                                    state.stop.store(true, Ordering::Relaxed);
+                                }
+                            }
+                        }
+
+                        // Published before recorded, so every checkpoint has
+                        // the solutions in its completed ranges.
+                        searched = nonce + 1;
+                        if searched - recorded >= PROGRESS_INTERVAL {
+                            completed.lock().unwrap().insert(recorded, searched);
+                            recorded = searched;
+                        }
+                    }
+
+                    completed.lock().unwrap().insert(recorded, searched);
+                    // Stopped early: later ranges are all higher nonces.
+                    if searched < hi {
+                        break;
+                    }
+                }
+            }));
+        }
+
+        // Live progress on stderr until the threads are joined.
+        let reporter = {
+            let (start, end) = (self.start, self.end);
+            let state = Arc::clone(&state);
+            let results = Arc::clone(&results);
+            let completed = Arc::clone(&completed);
+            self.reporter(move || Counters {
+                hashes: state.hashes.load(Ordering::Relaxed),
+                per_worker: state.worker_hashes.snapshot(),
+                queued_chunks: None,
+                solutions: results.lock().unwrap().len(),
+                remaining: remaining(
+                    &completed.lock().unwrap(),
+                    start,
+                    end,
+                    state.bound.load(Ordering::Relaxed),
+                ),
+                total: end - start,
+            })
+        };
+
+        // Completed ranges first: every solution in them is already in
+        // `results`, since threads publish before recording progress.
+        let save = || {
+            self.save(|| {
+                let done = completed.lock().unwrap().clone();
+                let total = base_hashes + state.hashes.load(Ordering::Relaxed);
+                self.snapshot(done, &results.lock().unwrap(), total)
+            })
+        };
+
+        // While checkpointing, save every interval until the threads are done.
+        if let Some((_, interval)) = self.checkpoint {
+            let mut last_save = Instant::now();
+            while !handles.iter().all(|h| h.is_finished()) {
+                thread::sleep(Duration::from_millis(50));
+                if last_save.elapsed() >= interval {
+                    save();
+                    last_save = Instant::now();
+                }
+            }
+        }
+
+        for h in handles {
+            h.join().unwrap();
+        }
+        if let Some(reporter) = reporter {
+            reporter.finish();
+        }
+        // Final checkpoint: everything the threads finished before exiting.
+        save();
+
+        let elapsed = t0.elapsed();
+        let mut solutions = std::mem::take(&mut *results.lock().unwrap());
+        solutions.sort_by_key(|s| s.nonce);
+        solutions.truncate(k);
+
+        // Finding k stops the run too, but only in the default mode.
+        let stopped = state.stopped() && (smallest || solutions.len() < k);
+        let searched = completed.lock().unwrap().count();
+        PowReport {
+            solutions,
+            hashes: state.hashes.load(Ordering::Relaxed),
+            elapsed,
+            threads: state
+                .worker_hashes
+                .snapshot()
+                .into_iter()
+                .map(|hashes| ThreadStats {
+                    hashes,
+                    ..ThreadStats::default()
+                })
+                .collect(),
+            searched,
+            status: if stopped {
+                Status::Interrupted
+            } else {
+                Status::Finished
+            },
+        }
+    }
+
+    /// `CentralQueue` / `WorkStealing`: threads take chunks and send
+    /// solutions to this thread, which collects them.
+    fn run_queue(
+        &self,
+        todo: Vec<(u64, u64)>,
+        progress: Progress,
+        base_hashes: u64,
+        state: Arc<Shared>,
+    ) -> PowReport {
+        let k = self.k;
+        let smallest = self.smallest;
+        let mut solutions = progress.solutions.clone();
+        let progress = Arc::new(Mutex::new(progress));
+
+        // Central: every worker claims chunks from the one cursor.
+        // Stealing: deal chunks round-robin into one deque per worker.
+        // `queued` counts the chunks left, for progress reports.
+        type Queued = Box<dyn Fn() -> Option<u64> + Send>;
+        let (sources, queued): (Vec<WorkSource>, Queued) = match self.schedule {
+            Schedule::CentralQueue { chunk_size } => {
+                let cursor = Arc::new(ChunkCursor::new(todo));
+                let sources = (0..self.threads)
+                    .map(|_| WorkSource::Central {
+                        cursor: Arc::clone(&cursor),
+                        sizer: ChunkSizer::new(chunk_size, self.threads),
+                    })
+                    .collect();
+                // No chunks exist until they are claimed.
+                (sources, Box::new(|| None))
+            }
+            Schedule::WorkStealing { chunk_size } => {
+                let locals: Vec<Worker<Work>> =
+                    (0..self.threads).map(|_| Worker::new_fifo()).collect();
+                let chunks = todo
+                    .iter()
+                    .flat_map(|&(lo, hi)| make_initial_work(lo, hi, chunk_size));
+                for (i, work) in chunks.enumerate() {
+                    locals[i % self.threads].push(work);
+                }
+                let stealers = Arc::new(locals.iter().map(Worker::stealer).collect::<Vec<_>>());
+                let sources = locals
+                    .into_iter()
+                    .map(|local| WorkSource::Stealing {
+                        local,
+                        stealers: Arc::clone(&stealers),
+                    })
+                    .collect();
+                (
+                    sources,
+                    Box::new(move || Some(stealers.iter().map(|s| s.len() as u64).sum())),
+                )
+            }
+            Schedule::StaticSplit => unreachable!("a static split has no queue"),
+        };
+
+        let t0 = Instant::now();
+        let (sol_tx, sol_rx) = mpsc::channel::<Solution>();
+
+        let mut handles = Vec::new();
+        for (id, source) in sources.into_iter().enumerate() {
+            let worker = QueueWorker {
+                id,
+                hasher: self.hasher(),
+                difficulty: self.difficulty,
+                source,
+                sol_tx: sol_tx.clone(),
+                state: Arc::clone(&state),
+                progress: Arc::clone(&progress),
+            };
+            handles.push(thread::spawn(move || worker.run()));
+        }
+        // Only the workers hold senders now, so `recv` fails once they exit.
+        drop(sol_tx);
+
+        // Live progress on stderr until the workers are joined.
+        let reporter = {
+            let (start, end) = (self.start, self.end);
+            let state = Arc::clone(&state);
+            let progress = Arc::clone(&progress);
+            self.reporter(move || {
+                let bound = state.bound.load(Ordering::Relaxed);
+                let p = progress.lock().unwrap();
+                Counters {
+                    hashes: state.hashes.load(Ordering::Relaxed),
+                    per_worker: state.worker_hashes.snapshot(),
+                    queued_chunks: queued(),
+                    solutions: p.solutions.len(),
+                    remaining: remaining(&p.completed, start, end, bound),
+                    total: end - start,
+                }
+            })
+        };
+
+        let save = || {
+            self.save(|| {
+                let p = progress.lock().unwrap();
+                let total = base_hashes + state.hashes.load(Ordering::Relaxed);
+                self.snapshot(p.completed.clone(), &p.solutions, total)
+            })
+        };
+        let interval = self.checkpoint.as_ref().map_or(Duration::MAX, |c| c.1);
+        let mut last_save = Instant::now();
+        let mut reached_k = false;
+
+        // Collect until k solutions arrive or every worker has exited.
+        loop {
+            if last_save.elapsed() >= interval {
+                save();
+                last_save = Instant::now();
+            }
+            // Wake up for the next checkpoint even if no solutions arrive.
+            let sol = match sol_rx.recv_timeout(interval.saturating_sub(last_save.elapsed())) {
+                Ok(sol) => sol,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+
+            solutions.push(sol);
+            if solutions.len() >= k {
+                if !smallest {
+                    state.stop.store(true, Ordering::Relaxed);
+                    reached_k = true;
+                    break;
+                }
+                // k-smallest mode: keep only the k best and let workers drop
+                // everything above the k-th. The loop ends when the workers
+                // run out of chunks and hang up.
+                solutions.sort_unstable_by_key(|s| s.nonce);
+                solutions.truncate(k);
+                state.bound.store(solutions[k - 1].nonce, Ordering::Relaxed);
+            }
+        }
+
+        let mut threads: Vec<ThreadStats> =
+            handles.into_iter().map(|h| h.join().unwrap()).collect();
+        if let Some(reporter) = reporter {
+            reporter.finish();
+        }
+        // Final checkpoint: everything the workers finished before exiting.
+        save();
+
+        // Sort by nonce for deterministic output, and drop any extra
+        // solutions that arrived concurrently.
+        solutions.sort_by_key(|s| s.nonce);
+        solutions.truncate(k);
+
+        for (t, hashes) in threads.iter_mut().zip(state.worker_hashes.snapshot()) {
+            t.hashes = hashes;
+        }
+        let searched = progress.lock().unwrap().completed.count();
+        PowReport {
+            solutions,
+            hashes: state.hashes.load(Ordering::Relaxed),
+            elapsed: t0.elapsed(),
+            threads,
+            searched,
+            status: if state.stopped() && !reached_k {
+                Status::Interrupted
+            } else {
+                Status::Finished
+            },
+        }
+    }
+}
+
+/// Stops a run once its time or hash budget runs out.
+struct Watchdog {
+    done: Arc<AtomicBool>,
+    thread: thread::JoinHandle<Option<Status>>,
+}
+
+impl Watchdog {
+    /// Start watching `search`, if it has a budget.
+    fn spawn(search: &PowSearch, state: Arc<Shared>) -> Option<Self> {
+        if search.timeout.is_none() && search.max_hashes.is_none() {
+            return None;
+        }
+        let deadline = search.timeout.map(|t| Instant::now() + t);
+        let max_hashes = search.max_hashes.unwrap_or(u64::MAX);
+        let done = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let done = Arc::clone(&done);
+            thread::spawn(move || {
+                // Someone else stopping the search first is not our doing.
+                while !done.load(Ordering::Relaxed) && !state.stopped() {
+                    let limit = if deadline.is_some_and(|d| Instant::now() >= d) {
+                        Some(Status::TimedOut)
+                    } else if state.hashes.load(Ordering::Relaxed) >= max_hashes {
+                        Some(Status::HashLimit)
+                    } else {
+                        None
+                    };
+                    if limit.is_some() {
+                        state.stop.store(true, Ordering::Relaxed);
+                        return limit;
+                    }
+                    let left = deadline.map_or(WATCHDOG_TICK, |d| {
+                        d.saturating_duration_since(Instant::now())
+                    });
+                    thread::sleep(left.min(WATCHDOG_TICK));
+                }
+                None
+            })
+        };
+        Some(Self { done, thread })
+    }
+
+    /// Stop watching; the limit that stopped the search, if one did.
+    fn finish(self) -> Option<Status> {
+        self.done.store(true, Ordering::Relaxed);
+        self.thread.join().unwrap()
+    }
+}
+
+/// State every thread of one run shares.
+struct Shared {
+    /// The caller's `stop_flag`. Only read here: the caller alone sets it.
+    caller_stop: Arc<AtomicBool>,
+    /// This run's own stop flag, set on reaching k or running out of budget.
+    stop: AtomicBool,
+    hashes: AtomicU64,
+    worker_hashes: WorkerCounters,
+    /// k-smallest mode: the k-th best nonce found so far (u64::MAX until
+    /// there are k).
+    bound: AtomicU64,
+}
+
+impl Shared {
+    /// Whether this run should stop, by its own doing or the caller's.
+    fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed) || self.caller_stop.load(Ordering::Relaxed)
+    }
+}
+
+/// A work item is a chunk of nonces: [start, start+len).
+#[derive(Clone, Copy, Debug)]
+struct Work {
+    start: u64,
+    len: u64,
+}
+
+impl Work {
+    fn end_exclusive(self) -> u64 {
+        self.start.saturating_add(self.len)
+    }
+}
+
+/// What has been searched so far, for checkpoints.
+///
+/// A worker records a searched range together with the solutions in it
+/// under one lock, so every snapshot is consistent.
+#[derive(Default)]
+struct Progress {
+    completed: RangeSet,
+    solutions: Vec<Solution>,
+}
+
+impl Progress {
+    fn record(&mut self, start: u64, end: u64, found: Vec<Solution>) {
+        self.completed.insert(start, end);
+        self.solutions.extend(found);
+    }
+}
+
+/// Nonces of [start, end) below `bound` still missing from `completed`.
+fn remaining(completed: &RangeSet, start: u64, end: u64, bound: u64) -> u64 {
+    // Nothing at or above the bound will be searched.
+    let limit = end.min(bound).max(start);
+    completed
+        .gaps(start, limit)
+        .iter()
+        .map(|&(a, b)| b - a)
+        .sum()
+}
+
+/// Split [start, end) into many small chunks to populate the work queue.
+/// Smaller chunk_size => better load balance, more queue contention.
+/// Larger chunk_size => less contention, worse early-stop responsiveness.
+fn make_initial_work(start: u64, end: u64, chunk_size: u64) -> VecDeque<Work> {
+    let mut q = VecDeque::new();
+    let mut cur = start;
+    while cur < end {
+        let remaining = end - cur;
+        let len = remaining.min(chunk_size);
+        q.push_back(Work { start: cur, len });
+        cur += len;
+    }
+    q
+}
+
+/// The nonces still to search, handed out in chunks on demand.
+///
+/// The ranges are laid end to end; `next` is how far into them chunks have
+/// been claimed. Claims only move forward, so chunks come out in ascending
+/// nonce order.
+struct ChunkCursor {
+    /// Non-empty, ascending ranges.
+    ranges: Vec<(u64, u64)>,
+    /// `offsets[i]`: total length of `ranges[..i]`.
+    offsets: Vec<u64>,
+    total: u64,
+    next: AtomicU64,
+}
+
+impl ChunkCursor {
+    fn new(mut ranges: Vec<(u64, u64)>) -> Self {
+        ranges.retain(|&(s, e)| s < e);
+        let mut offsets = Vec::with_capacity(ranges.len());
+        let mut total = 0;
+        for &(s, e) in &ranges {
+            offsets.push(total);
+            total += e - s;
+        }
+        Self {
+            ranges,
+            offsets,
+            total,
+            next: AtomicU64::new(0),
+        }
+    }
+
+    /// Index of the range holding offset `at` (< `total`).
+    fn range_at(&self, at: u64) -> usize {
+        self.offsets.partition_point(|&o| o <= at) - 1
+    }
+
+    /// Unclaimed nonces below `bound`.
+    fn left(&self, bound: u64) -> u64 {
+        let at = self.next.load(Ordering::Relaxed);
+        if at >= self.total {
+            return 0;
+        }
+        let i = self.range_at(at);
+        let mut left = 0;
+        for (j, &(s, e)) in self.ranges.iter().enumerate().skip(i) {
+            let lo = if j == i {
+                s + (at - self.offsets[i])
+            } else {
+                s
+            };
+            if lo >= bound {
+                break;
+            }
+            left += e.min(bound) - lo;
+        }
+        left
+    }
+
+    /// Claim up to `len` nonces, never crossing from one range into the
+    /// next. `None` once everything has been claimed.
+    fn claim(&self, len: u64, stats: &mut ThreadStats) -> Option<Work> {
+        let mut at = self.next.load(Ordering::Relaxed);
+        loop {
+            if at >= self.total {
+                return None;
+            }
+            let i = self.range_at(at);
+            let range_left = self.offsets[i] + (self.ranges[i].1 - self.ranges[i].0) - at;
+            let len = len.clamp(1, range_left);
+            match self
+                .next
+                .compare_exchange(at, at + len, Ordering::Relaxed, Ordering::Relaxed)
+            {
+                Ok(_) => {
+                    let start = self.ranges[i].0 + (at - self.offsets[i]);
+                    return Some(Work { start, len });
+                }
+                Err(now) => {
+                    stats.cursor_retries += 1;
+                    at = now;
+                }
+            }
+        }
+    }
+}
+
+/// Picks one central-queue worker's chunk lengths.
+struct ChunkSizer {
+    chunk_size: ChunkSize,
+    threads: u64,
+    /// Adaptive: the length that should take about `TARGET_CHUNK_TIME`.
+    steady: u64,
+}
+
+impl ChunkSizer {
+    fn new(chunk_size: ChunkSize, threads: usize) -> Self {
+        Self {
+            chunk_size,
+            threads: threads as u64,
+            steady: MIN_CHUNK,
+        }
+    }
+
+    /// Length of the next chunk, with `left` nonces still unclaimed.
+    fn next_len(&self, left: u64) -> u64 {
+        match self.chunk_size {
+            ChunkSize::Fixed(n) => n,
+            // Near the end, take only a share of what is left so the last
+            // chunks are small and every thread finishes at about once.
+            ChunkSize::Adaptive => self
+                .steady
+                .min(left.div_ceil(4 * self.threads))
+                .max(MIN_CHUNK),
+        }
+    }
+
+    /// Learn from a chunk of which `hashed` nonces took `took`.
+    fn observe(&mut self, hashed: u64, took: Duration) {
+        if self.chunk_size != ChunkSize::Adaptive || hashed == 0 {
+            return;
+        }
+        let secs = took.as_secs_f64().max(1e-6);
+        let ideal = (hashed as f64 * TARGET_CHUNK_TIME.as_secs_f64() / secs) as u64;
+        // At most halve or double per chunk, so one odd measurement (a
+        // descheduled thread, a chunk cut short) cannot swing it far.
+        self.steady = ideal
+            .clamp(self.steady / 2, self.steady.saturating_mul(2))
+            .clamp(MIN_CHUNK, MAX_CHUNK);
+    }
+}
+
+/// Where a single worker takes its next chunk from.
+enum WorkSource {
+    Central {
+        cursor: Arc<ChunkCursor>,
+        sizer: ChunkSizer,
+    },
+    Stealing {
+        local: Worker<Work>,
+        stealers: Arc<Vec<Stealer<Work>>>,
+    },
+}
+
+impl WorkSource {
+    /// Take the next chunk, or `None` when no work is left anywhere.
+    /// Nothing at or above `bound` is needed.
+    fn next_work(&self, id: usize, bound: u64, stats: &mut ThreadStats) -> Option<Work> {
+        match self {
+            WorkSource::Central { cursor, sizer } => {
+                cursor.claim(sizer.next_len(cursor.left(bound)), stats)
+            }
+            WorkSource::Stealing { local, stealers } => {
+                if let Some(work) = local.pop() {
+                    return Some(work);
+                }
+
+                // Own deque is empty: try every other worker, starting with
+                // our neighbour so thieves spread out. `Retry` means we lost
+                // a race, not that the victim is empty, so go around again.
+                let n = stealers.len();
+                loop {
+                    let mut retry = false;
+                    for offset in 1..n {
+                        match stealers[(id + offset) % n].steal_batch_and_pop(local) {
+                            Steal::Success(work) => {
+                                stats.steals += 1;
+                                return Some(work);
+                            }
+                            Steal::Retry => retry = true,
+                            Steal::Empty => {}
+                        }
+                    }
+                    if !retry {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Report that the last chunk hashed `hashed` nonces in `took`.
+    fn finished(&mut self, hashed: u64, took: Duration) {
+        if let WorkSource::Central { sizer, .. } = self {
+            sizer.observe(hashed, took);
+        }
+    }
+}
+
+/// One queue-schedule thread and everything it shares with the others.
+struct QueueWorker {
+    id: usize,
+    hasher: NonceHasher,
+    difficulty: Difficulty,
+    source: WorkSource,
+    sol_tx: mpsc::Sender<Solution>,
+    state: Arc<Shared>,
+    progress: Arc<Mutex<Progress>>,
+}
+
+impl QueueWorker {
+    /// Take chunks until the queue is empty or the run stops, hashing each
+    /// with no locks held and sending solutions to the collector.
+    fn run(self) -> ThreadStats {
+        let QueueWorker {
+            id,
+            hasher,
+            difficulty,
+            mut source,
+            sol_tx,
+            state,
+            progress,
+        } = self;
+        let mut stats = ThreadStats::default();
+
+        while !state.stopped() {
+            let bound = state.bound.load(Ordering::Relaxed);
+            let Some(work) = source.next_work(id, bound, &mut stats) else {
+                break;
+            };
+            stats.chunks += 1;
+
+            // k-smallest mode: nothing at or above the k-th best nonce so far
+            // can make the answer (the bound only ever decreases). Always
+            // u64::MAX otherwise.
+            if work.start >= state.bound.load(Ordering::Relaxed) {
+                stats.skipped += 1;
+                // The cursor only moves up, so every later chunk is above
+                // the bound too.
+                if matches!(source, WorkSource::Central { .. }) {
+                    break;
+                }
+                continue;
+            }
+
+            let t0 = Instant::now();
+            let mut done: u64 = 0;
+            let mut found = Vec::new();
+            for nonce in work.start..work.end_exclusive() {
+                if done.is_multiple_of(STOP_CHECK_INTERVAL)
+                    && (state.stopped() || nonce >= state.bound.load(Ordering::Relaxed))
+                {
+                    break;
+                }
+
+                let hash = hasher.digest(nonce);
+                done += 1;
+
+                if difficulty.meets(&hash) {
+                    found.push(Solution { nonce, hash });
+                    // The collector may already have hung up; that's fine.
+                    let _ = sol_tx.send(Solution { nonce, hash });
+                }
+            }
+            source.finished(done, t0.elapsed());
+            state.hashes.fetch_add(done, Ordering::Relaxed);
+            state.worker_hashes.add(id, done);
+
+            // Only the nonces actually hashed count as searched.
+            progress
+                .lock()
+                .unwrap()
+                .record(work.start, work.start + done, found);
+        }
+
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEDULES: [Schedule; 4] = [
+        Schedule::StaticSplit,
+        Schedule::CentralQueue {
+            chunk_size: ChunkSize::Fixed(1_000),
+        },
+        Schedule::CentralQueue {
+            chunk_size: ChunkSize::Adaptive,
+        },
+        Schedule::WorkStealing { chunk_size: 1_000 },
+    ];
+
+    fn small(schedule: Schedule, threads: usize) -> PowSearch {
+        PowSearch::new("cmkl-pow")
+            .difficulty(Difficulty::hex_digits(3))
+            .k(6)
+            .range(0, 100_000)
+            .threads(threads)
+            .schedule(schedule)
+            .smallest(true)
+    }
+
+    fn brute_force(search: &PowSearch) -> Vec<u64> {
+        let hasher = search.hasher();
+        (search.start..search.end)
+            .filter(|&n| search.difficulty.meets(&hasher.digest(n)))
+            .take(search.k)
+            .collect()
+    }
+
+    fn nonces(report: &PowReport) -> Vec<u64> {
+        report.solutions.iter().map(|s| s.nonce).collect()
+    }
+
+    #[test]
+    fn every_schedule_finds_the_k_smallest() {
+        let want = brute_force(&small(Schedule::StaticSplit, 1));
+        assert_eq!(want.len(), 6);
+        for schedule in SCHEDULES {
+            for threads in [1, 5] {
+                let report = small(schedule, threads).run();
+                assert_eq!(nonces(&report), want, "{schedule:?} threads={threads}");
+                assert!(report.finished());
+                assert_eq!(report.threads.len(), threads);
+                assert_eq!(report.totals().hashes, report.hashes);
+                for s in &report.solutions {
+                    assert_eq!(s.hash, small(schedule, 1).hasher().digest(s.nonce));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn default_mode_stops_at_k_valid_solutions() {
+        for schedule in SCHEDULES {
+            let search = small(schedule, 3).smallest(false).range(0, 10_000_000);
+            let report = search.run();
+            assert_eq!(report.solutions.len(), 6, "{schedule:?}");
+            assert!(report.solutions.windows(2).all(|w| w[0].nonce < w[1].nonce));
+            assert!(
+                report
+                    .solutions
+                    .iter()
+                    .all(|s| search.difficulty.meets(&s.hash))
+            );
+            assert!(report.finished());
+        }
+    }
+
+    #[test]
+    fn a_search_can_be_run_again_and_cloned() {
+        for schedule in SCHEDULES {
+            let stop = Arc::new(AtomicBool::new(false));
+            let search = small(schedule, 3)
+                .smallest(false)
+                .range(0, 10_000_000)
+                .stop_flag(Arc::clone(&stop));
+            let first = search.run();
+            assert!(first.finished(), "{schedule:?}");
+            assert_eq!(first.solutions.len(), 6);
+            // Reaching k ends the run without touching the caller's flag.
+            assert!(!stop.load(Ordering::Relaxed));
+
+            for report in [search.run(), search.clone().run()] {
+                assert!(report.finished(), "{schedule:?}");
+                assert_eq!(report.solutions.len(), 6, "{schedule:?}");
+            }
+
+            // Same after a budget ran out.
+            let limited = small(schedule, 2)
+                .difficulty(Difficulty::hex_digits(12))
+                .range(0, 1 << 30)
+                .max_hashes(20_000);
+            assert_eq!(limited.run().status, Status::HashLimit, "{schedule:?}");
+            let report = limited.clone().run();
+            assert_eq!(report.status, Status::HashLimit, "{schedule:?}");
+            assert!(report.hashes >= 20_000, "{schedule:?}");
+        }
+    }
+
+    #[test]
+    fn cursor_hands_out_every_nonce_once_in_order() {
+        let ranges = vec![(10, 25), (40, 41), (100, 160)];
+        let cursor = ChunkCursor::new(ranges.clone());
+        let mut stats = ThreadStats::default();
+        assert_eq!(cursor.left(u64::MAX), 76);
+        assert_eq!(cursor.left(45), 16);
+
+        let mut claimed = RangeSet::new();
+        let mut last_end = 0;
+        while let Some(work) = cursor.claim(7, &mut stats) {
+            assert!(work.start >= last_end && work.len <= 7);
+            last_end = work.end_exclusive();
+            claimed.insert(work.start, work.end_exclusive());
+        }
+        assert_eq!(claimed.ranges(), &ranges);
+        assert_eq!(cursor.left(u64::MAX), 0);
+        assert!(cursor.claim(7, &mut stats).is_none());
+        assert!(ChunkCursor::new(vec![]).claim(1, &mut stats).is_none());
+    }
+
+    #[test]
+    fn cursor_is_shared_safely_between_threads() {
+        let cursor = Arc::new(ChunkCursor::new(vec![(0, 100_000), (200_000, 300_000)]));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let cursor = Arc::clone(&cursor);
+                thread::spawn(move || {
+                    let mut stats = ThreadStats::default();
+                    let mut mine = Vec::new();
+                    while let Some(w) = cursor.claim(13, &mut stats) {
+                        mine.push((w.start, w.end_exclusive()));
+                    }
+                    mine
+                })
+            })
+            .collect();
+        let mut all: Vec<(u64, u64)> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        all.sort_unstable();
+        assert!(
+            all.windows(2).all(|w| w[0].1 <= w[1].0),
+            "overlapping claims"
+        );
+        assert_eq!(all.iter().map(|&(a, b)| b - a).sum::<u64>(), 200_000);
+    }
+
+    #[test]
+    fn adaptive_chunks_grow_to_the_target_and_shrink_at_the_end() {
+        let mut sizer = ChunkSizer::new(ChunkSize::Adaptive, 2);
+        assert_eq!(sizer.next_len(u64::MAX), MIN_CHUNK);
+
+        // 1M nonces/s: the target is 20k nonces, reached by doubling.
+        let mut len = sizer.next_len(u64::MAX);
+        for _ in 0..10 {
+            sizer.observe(len, Duration::from_secs_f64(len as f64 / 1e6));
+            assert!(sizer.next_len(u64::MAX) <= 2 * len);
+            len = sizer.next_len(u64::MAX);
+        }
+        assert!((19_000..=21_000).contains(&len), "{len}");
+
+        // Near the end only a share of what is left, but never below the minimum.
+        assert_eq!(sizer.next_len(80_000), 10_000);
+        assert_eq!(sizer.next_len(10), MIN_CHUNK);
+
+        let mut fixed = ChunkSizer::new(ChunkSize::Fixed(500), 2);
+        fixed.observe(500, Duration::from_secs(5));
+        assert_eq!(fixed.next_len(10), 500);
+    }
+
+    #[test]
+    fn central_queue_needs_no_upfront_work_list() {
+        let search = small(
+            Schedule::CentralQueue {
+                chunk_size: ChunkSize::Adaptive,
+            },
+            3,
+        )
+        .smallest(false)
+        .range(0, u64::MAX);
+        let report = search.run();
+        assert_eq!(report.solutions.len(), 6);
+        assert!(
+            report
+                .solutions
+                .iter()
+                .all(|s| search.difficulty.meets(&s.hash))
+        );
+    }
+
+    #[test]
+    fn queue_stats_follow_the_schedule() {
+        let central = Schedule::CentralQueue {
+            chunk_size: ChunkSize::Fixed(1_000),
+        };
+        let report = small(central, 2).run();
+        assert!(report.totals().chunks > 0);
+        assert_eq!(report.totals().steals, 0);
+
+        let report = small(Schedule::StaticSplit, 2).run();
+        assert_eq!(report.totals().chunks, 0);
+    }
+
+    #[test]
+    fn stop_flag_interrupts_and_resume_finishes() {
+        for schedule in SCHEDULES {
+            let path = std::env::temp_dir().join(format!(
+                "pow-core-search-{}-{}.ckpt",
+                schedule.name(),
+                std::process::id()
+            ));
+            let search = small(schedule, 2).checkpoint(&path, Duration::from_secs(60));
+            let want = brute_force(&search);
+
+            // Already stopped: nothing is searched, but a checkpoint is written.
+            let report = search
+                .clone()
+                .stop_flag(Arc::new(AtomicBool::new(true)))
+                .run();
+            assert_eq!(report.status, Status::Interrupted, "{schedule:?}");
+            assert_eq!(report.searched, 0);
+            let ckpt = Checkpoint::load(&path).unwrap();
+            assert_eq!(ckpt.remaining(), vec![(0, 100_000)]);
+
+            // Pretend an earlier run finished a stretch that holds some, but
+            // not all, of the answer.
+            let mut ckpt = search.snapshot(RangeSet::new(), &[], 0);
+            ckpt.completed.insert(0, want[2] + 1);
+            ckpt.solutions = want[..3]
+                .iter()
+                .map(|&n| ReportedSolution::new(n, &search.hasher().digest(n)))
+                .collect();
+            ckpt.hashes = want[2] + 1;
+
+            let report = search.clone().resume(ckpt).run();
+            assert_eq!(nonces(&report), want, "{schedule:?}");
+            assert!(report.finished());
+            // Nothing in the completed range is hashed again.
+            assert!(report.hashes <= report.searched - (want[2] + 1));
+
+            let done = Checkpoint::load(&path).unwrap();
+            assert_eq!(done.solutions.len(), 6);
+            assert!(done.hashes >= want[2] + 1 + report.hashes);
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn budgets_stop_the_search_and_say_which_ran_out() {
+        for schedule in SCHEDULES {
+            // Far too hard to finish: only the budget can end these.
+            let hard = || {
+                small(schedule, 2)
+                    .difficulty(Difficulty::hex_digits(12))
+                    .range(0, 1 << 30)
+            };
+
+            let t0 = Instant::now();
+            let report = hard().timeout(Duration::from_millis(100)).run();
+            assert_eq!(report.status, Status::TimedOut, "{schedule:?}");
+            assert!(t0.elapsed() >= Duration::from_millis(100));
+            assert!(t0.elapsed() < Duration::from_secs(10), "{schedule:?}");
+
+            let report = hard().max_hashes(20_000).run();
+            assert_eq!(report.status, Status::HashLimit, "{schedule:?}");
+            assert!(report.hashes >= 20_000, "{schedule:?}");
+
+            // A budget that does not run out changes nothing.
+            let report = small(schedule, 2)
+                .timeout(Duration::from_secs(600))
+                .max_hashes(u64::MAX)
+                .run();
+            assert!(report.finished(), "{schedule:?}");
+            assert_eq!(nonces(&report), brute_force(&small(schedule, 1)));
+
+            // Whoever stops the search first is the one reported.
+            let report = hard()
+                .timeout(Duration::from_secs(600))
+                .stop_flag(Arc::new(AtomicBool::new(true)))
+                .run();
+            assert_eq!(report.status, Status::Interrupted, "{schedule:?}");
+        }
+    }
+
+    #[test]
+    fn resume_takes_the_checkpoints_parameters() {
+        let ckpt = small(Schedule::StaticSplit, 1)
+            .algorithm(Algorithm::Blake3)
+            .k(2)
+            .range(10, 20_000)
+            .snapshot(RangeSet::new(), &[], 0);
+        let search = PowSearch::new("other").k(9).resume(ckpt);
+        assert_eq!(search.prefix, "cmkl-pow");
+        assert_eq!(search.algorithm, Algorithm::Blake3);
+        assert_eq!((search.k, search.start, search.end), (2, 10, 20_000));
+        assert!(search.smallest);
+        assert_eq!(nonces(&search.run()), brute_force(&search));
+    }
+
+    #[test]
+    fn header_mode_searches_across_the_extra_nonce_rollover() {
+        let header = BlockHeader::new(Difficulty::hex_digits(3).compact_bits());
+        let (start, end) = ((1 << 32) - 20_000, (1 << 32) + 80_000);
+        for schedule in SCHEDULES {
+            let search = small(schedule, 3)
+                .algorithm(Algorithm::DoubleSha256)
+                .header(header)
+                .k(15)
+                .range(start, end);
+            let want = brute_force(&search);
+            assert!(want[0] < 1 << 32 && want[14] >= 1 << 32, "{want:?}");
+            assert_ne!(
+                want,
+                brute_force(&small(schedule, 1).k(15).range(start, end))
+            );
+            assert_eq!(nonces(&search.run()), want, "{schedule:?}");
+
+            let ckpt = search.snapshot(RangeSet::new(), &[], 0);
+            assert_eq!(ckpt.header, Some(header));
+            assert_eq!(PowSearch::new("x").resume(ckpt).header, Some(header));
+        }
+    }
+}
@@ -0,0 +1,326 @@
+// Live progress reporting for long searches.
+//
+// A `Reporter` thread wakes up every interval, reads the search's counters
+// through a closure supplied by the binary and prints one line to stderr,
+// so stdout stays the parseable result:
+//
+//   [    12.0s] 7.02MH/s  hashes 84.10M  queued 812  solutions 3  left 99.8%  eta 1h58m  workers 10.62M ...
+//   {"elapsed_secs":12.0,"hashes":84100000,"hashrate":7020000.0,"per_worker":[...],...}
+//
+// Workers count their own hashes in a `WorkerCounters` slot so the final
+// per-worker breakdown shows how evenly the work was spread.
+
+use std::fmt::Write as _;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// A counter on its own cache line, so workers bumping neighbouring
+/// counters do not slow each other down.
+#[derive(Default)]
+#[repr(align(64))]
+struct Padded(AtomicU64);
+
+/// One hash counter per worker.
+pub struct WorkerCounters {
+    counters: Box<[Padded]>,
+}
+
+impl WorkerCounters {
+    pub fn new(workers: usize) -> Self {
+        Self {
+            counters: (0..workers).map(|_| Padded::default()).collect(),
+        }
+    }
+
+    pub fn add(&self, worker: usize, hashes: u64) {
+        self.counters[worker].0.fetch_add(hashes, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> Vec<u64> {
+        self.counters
+            .iter()
+            .map(|c| c.0.load(Ordering::Relaxed))
+            .collect()
+    }
+}
+
+/// How progress lines are printed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProgressFormat {
+    /// One human-readable line per sample.
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+impl FromStr for ProgressFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(ProgressFormat::Text),
+            "json" => Ok(ProgressFormat::Json),
+            _ => Err("expected `text` or `json`".to_string()),
+        }
+    }
+}
+
+/// What the reporter reads from a running search.
+pub struct Counters {
+    /// Hashes computed so far by this run.
+    pub hashes: u64,
+    /// Hashes per worker, in worker order.
+    pub per_worker: Vec<u64>,
+    /// Chunks still waiting in the work queue, if there is one.
+    pub queued_chunks: Option<u64>,
+    /// Solutions found so far.
+    pub solutions: usize,
+    /// Nonces that may still have to be searched.
+    pub remaining: u64,
+    /// Size of the whole range.
+    pub total: u64,
+}
+
+/// One progress line.
+#[derive(Clone, Debug, Serialize)]
+pub struct Sample {
+    pub elapsed_secs: f64,
+    pub hashes: u64,
+    /// Hashes per second since the previous sample.
+    pub hashrate: f64,
+    pub per_worker: Vec<u64>,
+    pub queued_chunks: Option<u64>,
+    pub solutions: usize,
+    pub remaining: u64,
+    /// Time to search `remaining` at the current hashrate.
+    pub eta_secs: Option<f64>,
+    #[serde(skip)]
+    total: u64,
+}
+
+impl Sample {
+    pub fn render(&self, format: ProgressFormat) -> String {
+        match format {
+            ProgressFormat::Json => {
+                serde_json::to_string(self).expect("a Sample always serializes")
+            }
+            ProgressFormat::Text => {
+                let mut line = format!(
+                    "[{:>8.1}s] {}H/s  hashes {}  ",
+                    self.elapsed_secs,
+                    si(self.hashrate),
+                    si(self.hashes as f64)
+                );
+                if let Some(q) = self.queued_chunks {
+                    let _ = write!(line, "queued {}  ", q);
+                }
+                let left = if self.total == 0 {
+                    0.0
+                } else {
+                    100.0 * self.remaining as f64 / self.total as f64
+                };
+                let _ = write!(
+                    line,
+                    "solutions {}  left {:.1}%  eta ",
+                    self.solutions, left
+                );
+                match self.eta_secs {
+                    Some(secs) => line.push_str(&human_duration(secs)),
+                    None => line.push('?'),
+                }
+                line.push_str("  workers");
+                for &h in &self.per_worker {
+                    let _ = write!(line, " {}", si(h as f64));
+                }
+                line
+            }
+        }
+    }
+}
+
+/// `7020000.0` -> `"7.02M"`.
+fn si(x: f64) -> String {
+    const UNITS: [(f64, &str); 4] = [(1e12, "T"), (1e9, "G"), (1e6, "M"), (1e3, "k")];
+    for (scale, unit) in UNITS {
+        if x >= scale {
+            return format!("{:.2}{}", x / scale, unit);
+        }
+    }
+    format!("{:.0}", x)
+}
+
+/// `8.3` -> `"8.3s"`, `185.0` -> `"3m05s"`, `7080.0` -> `"1h58m"`.
+fn human_duration(secs: f64) -> String {
+    if secs < 60.0 {
+        return format!("{:.1}s", secs);
+    }
+    let s = secs as u64;
+    if s < 3600 {
+        format!("{}m{:02}s", s / 60, s % 60)
+    } else if s < 86_400 {
+        format!("{}h{:02}m", s / 3600, s % 3600 / 60)
+    } else {
+        format!("{}d{:02}h", s / 86_400, s % 86_400 / 3600)
+    }
+}
+
+/// Background thread printing a progress line every interval.
+pub struct Reporter {
+    done: mpsc::Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+impl Reporter {
+    pub fn spawn<F>(interval: Duration, format: ProgressFormat, mut read: F) -> Self
+    where
+        F: FnMut() -> Counters + Send + 'static,
+    {
+        let (done, wake) = mpsc::channel::<()>();
+        let handle = thread::spawn(move || {
+            let t0 = Instant::now();
+            let mut last = (t0, 0u64);
+            // `finish` sends or hangs up; either way the search is over.
+            while let Err(RecvTimeoutError::Timeout) = wake.recv_timeout(interval) {
+                let now = Instant::now();
+                let c = read();
+                let dt = now.duration_since(last.0).as_secs_f64();
+                let hashrate = if dt > 0.0 {
+                    c.hashes.saturating_sub(last.1) as f64 / dt
+                } else {
+                    0.0
+                };
+                last = (now, c.hashes);
+
+                let sample = Sample {
+                    elapsed_secs: now.duration_since(t0).as_secs_f64(),
+                    hashes: c.hashes,
+                    hashrate,
+                    per_worker: c.per_worker,
+                    queued_chunks: c.queued_chunks,
+                    solutions: c.solutions,
+                    remaining: c.remaining,
+                    eta_secs: (hashrate > 0.0).then(|| c.remaining as f64 / hashrate),
+                    total: c.total,
+                };
+                eprintln!("{}", sample.render(format));
+            }
+        });
+        Self { done, handle }
+    }
+
+    /// Stop the reporter without waiting for the next tick.
+    pub fn finish(self) {
+        let _ = self.done.send(());
+        self.handle.join().unwrap();
+    }
+}
+
+/// Final per-worker table, one `key=value` line per worker plus a summary:
+///
+///   worker=0 hashes=1250000 share=25.1%
+///   ...
+///   per_worker: min=1240000 max=1250000 imbalance=1.01
+///
+/// `imbalance` is the busiest worker's count over the mean (1.00 = even).
+pub fn breakdown(per_worker: &[u64]) -> String {
+    let total: u64 = per_worker.iter().sum();
+    let mut out = String::new();
+    for (i, &h) in per_worker.iter().enumerate() {
+        let share = if total == 0 {
+            0.0
+        } else {
+            100.0 * h as f64 / total as f64
+        };
+        let _ = writeln!(out, "worker={} hashes={} share={:.1}%", i, h, share);
+    }
+    let min = per_worker.iter().copied().min().unwrap_or(0);
+    let max = per_worker.iter().copied().max().unwrap_or(0);
+    let imbalance = if total == 0 {
+        1.0
+    } else {
+        max as f64 * per_worker.len() as f64 / total as f64
+    };
+    let _ = write!(
+        out,
+        "per_worker: min={} max={} imbalance={:.2}",
+        min, max, imbalance
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn worker_counters_are_independent() {
+        let c = WorkerCounters::new(3);
+        c.add(0, 5);
+        c.add(2, 7);
+        c.add(0, 1);
+        assert_eq!(c.snapshot(), vec![6, 0, 7]);
+        assert_eq!(std::mem::align_of::<Padded>(), 64);
+    }
+
+    #[test]
+    fn breakdown_reports_shares_and_imbalance() {
+        let text = breakdown(&[300, 100, 0, 400]);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "worker=0 hashes=300 share=37.5%");
+        assert_eq!(lines[2], "worker=2 hashes=0 share=0.0%");
+        assert_eq!(lines[4], "per_worker: min=0 max=400 imbalance=2.00");
+        assert!(breakdown(&[0, 0]).ends_with("imbalance=1.00"));
+    }
+
+    #[test]
+    fn samples_render_as_text_and_json() {
+        let sample = Sample {
+            elapsed_secs: 12.0,
+            hashes: 84_100_000,
+            hashrate: 7_020_000.0,
+            per_worker: vec![42_050_000, 42_050_000],
+            queued_chunks: Some(812),
+            solutions: 3,
+            remaining: 500,
+            eta_secs: Some(185.0),
+            total: 1000,
+        };
+        assert_eq!(
+            sample.render(ProgressFormat::Text),
+            "[    12.0s] 7.02MH/s  hashes 84.10M  queued 812  solutions 3  \
+             left 50.0%  eta 3m05s  workers 42.05M 42.05M"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&sample.render(ProgressFormat::Json)).unwrap();
+        assert_eq!(json["hashes"], 84_100_000);
+        assert_eq!(json["queued_chunks"], 812);
+        assert_eq!(json["per_worker"][1], 42_050_000);
+        assert_eq!(json["eta_secs"], 185.0);
+        assert!(json.get("total").is_none());
+    }
+
+    #[test]
+    fn durations_are_human_readable() {
+        assert_eq!(human_duration(8.3), "8.3s");
+        assert_eq!(human_duration(7080.0), "1h58m");
+        assert_eq!(human_duration(200_000.0), "2d07h");
+        assert_eq!(si(999.0), "999");
+        assert_eq!(si(1500.0), "1.50k");
+    }
+
+    #[test]
+    fn reporter_stops_promptly() {
+        let t0 = Instant::now();
+        let reporter = Reporter::spawn(Duration::from_secs(3600), ProgressFormat::Text, || {
+            unreachable!("no tick within the test")
+        });
+        reporter.finish();
+        assert!(t0.elapsed() < Duration::from_secs(60));
+    }
+}
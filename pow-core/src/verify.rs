@@ -0,0 +1,683 @@
+// Independent checking of a reported solution set.
+//
+// A run's result is a `SolutionSet`: the search parameters plus the
+// solutions it reported. It can be read back from either
+//
+//   - the text the binaries print:
+//
+//       prefix=cmkl-pow threads=8 difficulty=6 target=10 algorithm=sha256
+//       range=0..50000000000
+//       ...
+//       solutions:
+//       nonce=4598276 hash=000000...
+//
+//     (`target=` is k, the number of solutions asked for; output without
+//     `algorithm=` is SHA-256; block-header searches add a `header=` line,
+//     see `header.rs`), or
+//
+//   - a JSON file written with `--json FILE`:
+//
+//       {"prefix": "cmkl-pow", "difficulty": "6", "algorithm": "sha256", "k": 10,
+//        "start": 0, "end": 50000000000,
+//        "solutions": [{"nonce": 4598276, "hash": "000000..."}]}
+//
+// `verify` recomputes every hash and checks the difficulty, and (unless
+// told to only check validity) rescans [start, last reported nonce] to
+// confirm the list is exactly the k smallest valid nonces in the range.
+// `run_verify` is the binaries' `verify` subcommand around it.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::VerifyArgs;
+use crate::difficulty::Difficulty;
+use crate::hash::{Algorithm, Hash, NonceHasher};
+use crate::header::BlockHeader;
+
+/// Nonces each thread hashes per block of the k-smallest rescan.
+const SCAN_BLOCK_PER_THREAD: u64 = 1 << 22;
+
+/// One reported solution.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportedSolution {
+    pub nonce: u64,
+    /// Lowercase hex digest.
+    pub hash: String,
+}
+
+impl ReportedSolution {
+    pub fn new(nonce: u64, hash: &Hash) -> Self {
+        Self {
+            nonce,
+            hash: hex::encode(hash),
+        }
+    }
+}
+
+/// The parameters of a run and the solutions it reported.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SolutionSet {
+    pub prefix: String,
+    pub difficulty: Difficulty,
+    /// Missing in files from before `--algorithm`: SHA-256.
+    #[serde(default)]
+    pub algorithm: Algorithm,
+    /// The header hashed instead of `prefix:nonce`, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<BlockHeader>,
+    /// Number of solutions asked for.
+    pub k: usize,
+    /// Searched nonce range `[start, end)`.
+    pub start: u64,
+    pub end: u64,
+    pub solutions: Vec<ReportedSolution>,
+}
+
+/// Why a solution set could not be read.
+#[derive(Debug)]
+pub enum ParseError {
+    Json(serde_json::Error),
+    /// A required `key=value` was not found in the printed output.
+    MissingField(&'static str),
+    /// A `key=value` whose value does not parse.
+    InvalidField {
+        key: String,
+        value: String,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Json(e) => write!(f, "invalid JSON solution file: {}", e),
+            ParseError::MissingField(key) => write!(f, "no `{}=` found in the output", key),
+            ParseError::InvalidField { key, value } => {
+                write!(f, "invalid value `{}` for `{}=`", value, key)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl SolutionSet {
+    /// Parse either a JSON solution file or the binaries' printed output.
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        if input.trim_start().starts_with('{') {
+            Self::from_json(input)
+        } else {
+            Self::from_text(input)
+        }
+    }
+
+    pub fn from_json(input: &str) -> Result<Self, ParseError> {
+        serde_json::from_str(input).map_err(ParseError::Json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("a SolutionSet always serializes")
+    }
+
+    /// Parse the printed output. Unknown lines and keys are ignored.
+    pub fn from_text(input: &str) -> Result<Self, ParseError> {
+        fn field<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, ParseError> {
+            value.parse().map_err(|_| ParseError::InvalidField {
+                key: key.to_string(),
+                value: value.to_string(),
+            })
+        }
+
+        let mut prefix = None;
+        let mut difficulty = None;
+        let mut algorithm = Algorithm::default();
+        let mut header = None;
+        let mut k = None;
+        let mut range = None;
+        let mut solutions = Vec::new();
+        let mut in_solutions = false;
+
+        for line in input.lines() {
+            let line = line.trim();
+            if line == "solutions:" {
+                in_solutions = true;
+                continue;
+            }
+
+            let mut nonce = None;
+            let mut hash = None;
+            for (key, value) in line.split_whitespace().filter_map(|t| t.split_once('=')) {
+                match key {
+                    "nonce" if in_solutions => nonce = Some(field::<u64>(key, value)?),
+                    "hash" if in_solutions => hash = Some(value.to_string()),
+                    "prefix" if !in_solutions => prefix = Some(value.to_string()),
+                    "difficulty" if !in_solutions => difficulty = Some(field(key, value)?),
+                    "target" if !in_solutions => k = Some(field(key, value)?),
+                    "algorithm" if !in_solutions => algorithm = field(key, value)?,
+                    "header" if !in_solutions => header = Some(field(key, value)?),
+                    "range" if !in_solutions => {
+                        let (start, end) =
+                            value
+                                .split_once("..")
+                                .ok_or_else(|| ParseError::InvalidField {
+                                    key: key.to_string(),
+                                    value: value.to_string(),
+                                })?;
+                        range = Some((field(key, start)?, field(key, end)?));
+                    }
+                    _ => {}
+                }
+            }
+            if let (Some(nonce), Some(hash)) = (nonce, hash) {
+                solutions.push(ReportedSolution { nonce, hash });
+            }
+        }
+
+        let (start, end) = range.ok_or(ParseError::MissingField("range"))?;
+        Ok(SolutionSet {
+            prefix: prefix.ok_or(ParseError::MissingField("prefix"))?,
+            difficulty: difficulty.ok_or(ParseError::MissingField("difficulty"))?,
+            algorithm,
+            header,
+            k: k.ok_or(ParseError::MissingField("target"))?,
+            start,
+            end,
+            solutions,
+        })
+    }
+}
+
+/// One way a solution set fails verification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// The parameters themselves cannot describe a search.
+    InvalidParameters(String),
+    /// A reported nonce outside `[start, end)`.
+    OutOfRange { nonce: u64 },
+    /// The reported hash is not H(prefix:nonce).
+    HashMismatch {
+        nonce: u64,
+        reported: String,
+        actual: String,
+    },
+    /// The nonce's hash does not meet the difficulty.
+    BelowDifficulty { nonce: u64 },
+    /// Nonces not strictly increasing (unsorted or duplicated).
+    NotAscending { nonce: u64 },
+    /// More solutions than asked for.
+    TooMany { count: usize, k: usize },
+    /// A valid nonce that belongs in the list but is not in it.
+    Missing { nonce: u64 },
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Discrepancy::InvalidParameters(msg) => write!(f, "invalid parameters: {}", msg),
+            Discrepancy::OutOfRange { nonce } => {
+                write!(f, "nonce {} is outside the searched range", nonce)
+            }
+            Discrepancy::HashMismatch {
+                nonce,
+                reported,
+                actual,
+            } => write!(
+                f,
+                "nonce {}: reported hash {} but recomputing it gives {}",
+                nonce, reported, actual
+            ),
+            Discrepancy::BelowDifficulty { nonce } => {
+                write!(f, "nonce {} does not meet the difficulty", nonce)
+            }
+            Discrepancy::NotAscending { nonce } => {
+                write!(f, "nonce {} is out of order or duplicated", nonce)
+            }
+            Discrepancy::TooMany { count, k } => {
+                write!(f, "{} solutions reported but only {} asked for", count, k)
+            }
+            Discrepancy::Missing { nonce } => write!(
+                f,
+                "nonce {} is valid and belongs in the list, but is missing",
+                nonce
+            ),
+        }
+    }
+}
+
+/// Check a solution set. An empty result means it verified.
+///
+/// With `check_smallest`, also rescan the range (using `threads` threads)
+/// to confirm the list is the k smallest valid nonces. If fewer than k
+/// solutions are reported, that means the whole of `[start, end)` is
+/// rescanned. The rescan stops at the first block containing a missing
+/// nonce, so only the earliest gaps are reported.
+pub fn verify(set: &SolutionSet, check_smallest: bool, threads: usize) -> Vec<Discrepancy> {
+    let mut found = Vec::new();
+
+    if let Err(e) = set.difficulty.validate() {
+        found.push(Discrepancy::InvalidParameters(e.to_string()));
+    }
+    if set.start >= set.end {
+        found.push(Discrepancy::InvalidParameters(format!(
+            "empty range {}..{}",
+            set.start, set.end
+        )));
+    }
+    if set.k == 0 {
+        // Nothing was asked for, so there is nothing to check the
+        // solutions against; the searches themselves reject k = 0.
+        found.push(Discrepancy::InvalidParameters(
+            "k must be at least 1".to_string(),
+        ));
+    }
+    if !found.is_empty() {
+        return found;
+    }
+
+    if set.solutions.len() > set.k {
+        found.push(Discrepancy::TooMany {
+            count: set.solutions.len(),
+            k: set.k,
+        });
+    }
+
+    let hasher = NonceHasher::new(set.algorithm, &set.prefix, set.header.as_ref());
+    let mut prev: Option<u64> = None;
+    for s in &set.solutions {
+        let nonce = s.nonce;
+        if prev.is_some_and(|p| nonce <= p) {
+            found.push(Discrepancy::NotAscending { nonce });
+        }
+        prev = Some(nonce);
+
+        if !(set.start..set.end).contains(&nonce) {
+            found.push(Discrepancy::OutOfRange { nonce });
+            continue;
+        }
+        let hash = hasher.digest(nonce);
+        let actual = hex::encode(hash);
+        if !s.hash.eq_ignore_ascii_case(&actual) {
+            found.push(Discrepancy::HashMismatch {
+                nonce,
+                reported: s.hash.clone(),
+                actual,
+            });
+        }
+        if !set.difficulty.meets(&hash) {
+            found.push(Discrepancy::BelowDifficulty { nonce });
+        }
+    }
+
+    if check_smallest && found.is_empty() {
+        // A full list only claims the nonces up to its last one; a short
+        // list claims to be everything in the range.
+        let scan_end = match set.solutions.last() {
+            Some(last) if set.solutions.len() == set.k => last.nonce + 1,
+            _ => set.end,
+        };
+        let reported: Vec<u64> = set.solutions.iter().map(|s| s.nonce).collect();
+
+        // Scan in ascending blocks and stop at the first block with a gap,
+        // so a wrong answer far into the range fails fast.
+        let block = SCAN_BLOCK_PER_THREAD.saturating_mul(threads.max(1) as u64);
+        let mut lo = set.start;
+        while lo < scan_end && found.is_empty() {
+            let hi = lo.saturating_add(block).min(scan_end);
+            found.extend(
+                find_solutions(&hasher, set.difficulty, lo, hi, threads)
+                    .into_iter()
+                    .filter(|n| reported.binary_search(n).is_err())
+                    .map(|nonce| Discrepancy::Missing { nonce }),
+            );
+            lo = hi;
+        }
+    }
+
+    found
+}
+
+/// `verify` subcommand: re-check a printed run or JSON solution file.
+/// Returns the process exit code.
+pub fn run_verify(args: &VerifyArgs) -> i32 {
+    let input = match args.input.as_deref() {
+        None | Some("-") => io::read_to_string(io::stdin()),
+        Some(path) => fs::read_to_string(path),
+    };
+    let text = match input {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("error: cannot read input: {}", e);
+            return 2;
+        }
+    };
+    let set = match SolutionSet::parse(&text) {
+        Ok(set) => set,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return 2;
+        }
+    };
+
+    let problems = verify(&set, !args.valid_only, args.threads);
+    if problems.is_empty() {
+        let claim = if args.valid_only {
+            "valid"
+        } else {
+            "valid and the smallest"
+        };
+        println!(
+            "OK: {} solution(s) for prefix={} difficulty={} in {}..{} are {}",
+            set.solutions.len(),
+            set.prefix,
+            set.difficulty,
+            set.start,
+            set.end,
+            claim
+        );
+        0
+    } else {
+        for p in &problems {
+            println!("FAIL: {}", p);
+        }
+        1
+    }
+}
+
+/// Every valid nonce in `[start, end)`, ascending, using `threads` threads.
+pub fn find_solutions(
+    hasher: &NonceHasher,
+    difficulty: Difficulty,
+    start: u64,
+    end: u64,
+    threads: usize,
+) -> Vec<u64> {
+    if start >= end {
+        return Vec::new();
+    }
+    let threads = threads.max(1) as u64;
+    let chunk = (end - start).div_ceil(threads);
+
+    // Contiguous slices, joined in order, keep the result sorted.
+    thread::scope(|s| {
+        let handles: Vec<_> = (0..threads)
+            .map(|i| {
+                let lo = start.saturating_add(i * chunk).min(end);
+                let hi = lo.saturating_add(chunk).min(end);
+                let hasher = hasher.clone();
+                s.spawn(move || {
+                    (lo..hi)
+                        .filter(|&n| difficulty.meets(&hasher.digest(n)))
+                        .collect::<Vec<u64>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PREFIX: &str = "cmkl-pow";
+
+    /// A correct set: the k smallest nonces with 8 leading zero bits.
+    fn good_set(k: usize) -> SolutionSet {
+        let difficulty = Difficulty::LeadingZeroBits(8);
+        let hasher = NonceHasher::new(Algorithm::Sha256, PREFIX, None);
+        let solutions = find_solutions(
+            &NonceHasher::new(Algorithm::Sha256, PREFIX, None),
+            difficulty,
+            0,
+            100_000,
+            3,
+        )
+        .into_iter()
+        .take(k)
+        .map(|n| ReportedSolution::new(n, &hasher.digest(n)))
+        .collect();
+        SolutionSet {
+            prefix: PREFIX.to_string(),
+            difficulty,
+            algorithm: Algorithm::Sha256,
+            header: None,
+            k,
+            start: 0,
+            end: 100_000,
+            solutions,
+        }
+    }
+
+    fn to_text(set: &SolutionSet) -> String {
+        let mut out = format!(
+            "prefix={} threads=4 difficulty={} target={} algorithm={}\nrange={}..{}\nhashes=1 time_ms=2\n",
+            set.prefix, set.difficulty, set.k, set.algorithm, set.start, set.end
+        );
+        if let Some(header) = &set.header {
+            out += &format!("header={}\n", header);
+        }
+        out += "solutions:\n";
+        for s in &set.solutions {
+            out += &format!("nonce={} hash={}\n", s.nonce, s.hash);
+        }
+        out
+    }
+
+    #[test]
+    fn find_solutions_is_independent_of_thread_count() {
+        let d = Difficulty::LeadingZeroBits(6);
+        let one = find_solutions(
+            &NonceHasher::new(Algorithm::Sha256, PREFIX, None),
+            d,
+            10,
+            5_000,
+            1,
+        );
+        assert!(!one.is_empty());
+        assert!(one.windows(2).all(|w| w[0] < w[1]));
+        for threads in [2, 3, 16] {
+            assert_eq!(
+                find_solutions(
+                    &NonceHasher::new(Algorithm::Sha256, PREFIX, None),
+                    d,
+                    10,
+                    5_000,
+                    threads
+                ),
+                one
+            );
+        }
+        assert_eq!(
+            find_solutions(
+                &NonceHasher::new(Algorithm::Sha256, PREFIX, None),
+                d,
+                5,
+                5,
+                4
+            ),
+            Vec::<u64>::new()
+        );
+    }
+
+    #[test]
+    fn text_and_json_round_trip() {
+        let set = good_set(5);
+        assert_eq!(SolutionSet::parse(&to_text(&set)).unwrap(), set);
+        assert_eq!(SolutionSet::parse(&set.to_json()).unwrap(), set);
+
+        assert!(matches!(
+            SolutionSet::parse("prefix=a difficulty=3 target=2\nsolutions:\n"),
+            Err(ParseError::MissingField("range"))
+        ));
+        assert!(matches!(
+            SolutionSet::parse("prefix=a difficulty=x target=2 range=0..9"),
+            Err(ParseError::InvalidField { .. })
+        ));
+        assert!(matches!(
+            SolutionSet::parse("{\"prefix\": 1}"),
+            Err(ParseError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn sets_are_checked_with_their_own_algorithm() {
+        let difficulty = Difficulty::LeadingZeroBits(8);
+        let hasher = NonceHasher::new(Algorithm::Blake3, PREFIX, None);
+        let blake3 = SolutionSet {
+            algorithm: Algorithm::Blake3,
+            solutions: find_solutions(
+                &NonceHasher::new(Algorithm::Blake3, PREFIX, None),
+                difficulty,
+                0,
+                100_000,
+                2,
+            )
+            .into_iter()
+            .take(5)
+            .map(|n| ReportedSolution::new(n, &hasher.digest(n)))
+            .collect(),
+            ..good_set(5)
+        };
+        assert_ne!(blake3.solutions, good_set(5).solutions);
+        assert_eq!(verify(&blake3, true, 2), vec![]);
+        assert_eq!(SolutionSet::parse(&to_text(&blake3)).unwrap(), blake3);
+
+        let as_sha256 = SolutionSet {
+            algorithm: Algorithm::Sha256,
+            ..blake3
+        };
+        assert!(matches!(
+            verify(&as_sha256, false, 2)[0],
+            Discrepancy::HashMismatch { .. }
+        ));
+
+        // Output from before `--algorithm` is SHA-256.
+        let old = "prefix=a difficulty=3 target=2 range=0..9\nsolutions:\n";
+        assert_eq!(
+            SolutionSet::parse(old).unwrap().algorithm,
+            Algorithm::Sha256
+        );
+        let old_json =
+            r#"{"prefix": "a", "difficulty": "3", "k": 2, "start": 0, "end": 9, "solutions": []}"#;
+        assert_eq!(
+            SolutionSet::parse(old_json).unwrap().algorithm,
+            Algorithm::Sha256
+        );
+    }
+
+    #[test]
+    fn header_sets_are_checked_against_the_header() {
+        // Straddle the first extra-nonce rollover.
+        let (start, end) = ((1 << 32) - 20_000, (1 << 32) + 20_000);
+        let difficulty = Difficulty::LeadingZeroBits(8);
+        let header = BlockHeader::new(difficulty.compact_bits());
+        let hasher = NonceHasher::new(Algorithm::DoubleSha256, PREFIX, Some(&header));
+        let found = find_solutions(&hasher, difficulty, start, end, 2);
+        assert!(found.first() < Some(&(1 << 32)) && found.last() >= Some(&(1 << 32)));
+
+        let set = SolutionSet {
+            algorithm: Algorithm::DoubleSha256,
+            header: Some(header),
+            start,
+            end,
+            k: found.len(),
+            solutions: found
+                .iter()
+                .map(|&n| ReportedSolution::new(n, &hasher.digest(n)))
+                .collect(),
+            ..good_set(1)
+        };
+        assert_eq!(verify(&set, true, 2), vec![]);
+        assert_eq!(SolutionSet::parse(&to_text(&set)).unwrap(), set);
+        assert_eq!(SolutionSet::parse(&set.to_json()).unwrap(), set);
+
+        // The same nonces as `prefix:nonce` are not solutions.
+        let without = SolutionSet {
+            header: None,
+            ..set
+        };
+        assert!(matches!(
+            verify(&without, false, 2)[0],
+            Discrepancy::HashMismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn correct_sets_verify() {
+        let set = good_set(5);
+        assert_eq!(verify(&set, true, 2), vec![]);
+
+        // Fewer than k: the whole (small) range is rescanned.
+        let mut all = good_set(1000);
+        assert!(all.solutions.len() < all.k);
+        assert_eq!(verify(&all, true, 2), vec![]);
+        all.solutions.pop();
+        assert_eq!(verify(&all, false, 2), vec![]);
+        assert_eq!(verify(&all, true, 2).len(), 1);
+    }
+
+    #[test]
+    fn discrepancies_are_reported() {
+        let base = good_set(5);
+        let nonce = |i: usize| base.solutions[i].nonce;
+
+        // A gap: drop the second-smallest and claim one more at the end.
+        let mut gap = good_set(6);
+        let skipped = gap.solutions.remove(1).nonce;
+        gap.k = 5;
+        assert_eq!(
+            verify(&gap, true, 2),
+            vec![Discrepancy::Missing { nonce: skipped }]
+        );
+        assert_eq!(verify(&gap, false, 2), vec![]);
+
+        let mut tampered = base.clone();
+        tampered.solutions[0].hash = "00".repeat(32);
+        assert!(matches!(
+            verify(&tampered, true, 2)[..],
+            [Discrepancy::HashMismatch { .. }]
+        ));
+
+        let mut invalid = base.clone();
+        invalid.solutions[2].nonce += 1;
+        let found = verify(&invalid, false, 2);
+        assert!(found.contains(&Discrepancy::BelowDifficulty {
+            nonce: nonce(2) + 1
+        }));
+
+        let mut unsorted = base.clone();
+        unsorted.solutions.swap(0, 1);
+        assert!(
+            verify(&unsorted, false, 2).contains(&Discrepancy::NotAscending { nonce: nonce(0) })
+        );
+
+        let mut too_many = base.clone();
+        too_many.k = 4;
+        assert!(verify(&too_many, false, 2).contains(&Discrepancy::TooMany { count: 5, k: 4 }));
+
+        let mut outside = base.clone();
+        outside.start = nonce(0) + 1;
+        assert!(verify(&outside, false, 2).contains(&Discrepancy::OutOfRange { nonce: nonce(0) }));
+
+        let mut nothing_asked = base.clone();
+        nothing_asked.k = 0;
+        nothing_asked.solutions.clear();
+        assert!(matches!(
+            verify(&nothing_asked, true, 2)[..],
+            [Discrepancy::InvalidParameters(_)]
+        ));
+
+        let mut empty = base;
+        empty.end = empty.start;
+        assert!(matches!(
+            verify(&empty, true, 2)[..],
+            [Discrepancy::InvalidParameters(_)]
+        ));
+    }
+}